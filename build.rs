@@ -0,0 +1,4 @@
+fn main() {
+    // 迁移文件变更时重新编译，确保打包的是最新迁移
+    println!("cargo:rerun-if-changed=migrations");
+}
@@ -0,0 +1,23 @@
+-- 用户表，用户名/手机号/邮箱在租户内唯一
+CREATE TABLE IF NOT EXISTS i18n_users (
+    id BIGINT UNSIGNED AUTO_INCREMENT PRIMARY KEY,
+    tenant_id BIGINT UNSIGNED NOT NULL DEFAULT 0,
+    username VARCHAR(50),
+    phone VARCHAR(20),
+    email VARCHAR(100),
+    password VARCHAR(255) NOT NULL,
+    nickname VARCHAR(64),
+    avatar VARCHAR(512),
+    gender TINYINT UNSIGNED,
+    birthday DATE,
+    status TINYINT UNSIGNED NOT NULL DEFAULT 1,  -- 1 正常 2 禁用 3 锁定 4 待验证 5 已注销
+    crt_by VARCHAR(64),
+    deleted_at TIMESTAMP NULL,
+    last_login TIMESTAMP NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    UNIQUE KEY uk_tenant_username (tenant_id, username),
+    UNIQUE KEY uk_tenant_phone (tenant_id, phone),
+    UNIQUE KEY uk_tenant_email (tenant_id, email),
+    KEY idx_status_deleted (status, deleted_at)
+);
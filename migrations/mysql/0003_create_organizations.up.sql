@@ -0,0 +1,36 @@
+-- 组织、成员与邀请
+CREATE TABLE IF NOT EXISTS i18n_organizations (
+    id BIGINT UNSIGNED AUTO_INCREMENT PRIMARY KEY,
+    tenant_id BIGINT UNSIGNED NOT NULL,
+    name VARCHAR(128) NOT NULL,
+    description VARCHAR(512),
+    crt_by BIGINT UNSIGNED,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    KEY idx_tenant (tenant_id)
+);
+
+CREATE TABLE IF NOT EXISTS i18n_org_members (
+    org_id BIGINT UNSIGNED NOT NULL,
+    user_id BIGINT UNSIGNED NOT NULL,
+    role VARCHAR(32) NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (org_id, user_id),
+    KEY idx_user (user_id)
+);
+
+CREATE TABLE IF NOT EXISTS i18n_org_invitations (
+    id BIGINT UNSIGNED AUTO_INCREMENT PRIMARY KEY,
+    tenant_id BIGINT UNSIGNED NOT NULL,
+    org_id BIGINT UNSIGNED NOT NULL,
+    invite_type VARCHAR(16) NOT NULL,
+    identifier VARCHAR(128) NOT NULL,
+    role VARCHAR(32) NOT NULL,
+    token VARCHAR(64) NOT NULL UNIQUE,
+    status TINYINT UNSIGNED NOT NULL DEFAULT 0,
+    invited_by BIGINT UNSIGNED NOT NULL,
+    accepted_by BIGINT UNSIGNED,
+    expires_at TIMESTAMP NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    KEY idx_org (org_id)
+);
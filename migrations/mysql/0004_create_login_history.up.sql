@@ -0,0 +1,15 @@
+-- 登录历史
+CREATE TABLE IF NOT EXISTS i18n_login_history (
+    id BIGINT UNSIGNED AUTO_INCREMENT PRIMARY KEY,
+    tenant_id BIGINT UNSIGNED NOT NULL,
+    user_id BIGINT UNSIGNED NOT NULL,
+    login_type VARCHAR(16) NOT NULL,
+    ip VARCHAR(64),
+    ip_range VARCHAR(64),
+    user_agent VARCHAR(512),
+    device_id VARCHAR(64) NOT NULL,
+    success TINYINT(1) NOT NULL,
+    reason VARCHAR(255),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    KEY idx_user (user_id, success)
+);
@@ -0,0 +1,38 @@
+-- Webhook 订阅、发件箱与投递记录
+CREATE TABLE IF NOT EXISTS i18n_webhooks (
+    id BIGINT UNSIGNED AUTO_INCREMENT PRIMARY KEY,
+    tenant_id BIGINT UNSIGNED NOT NULL,
+    url VARCHAR(512) NOT NULL,
+    secret VARCHAR(128) NOT NULL,
+    events VARCHAR(255) NOT NULL,  -- 逗号分隔的事件类型
+    enabled TINYINT(1) NOT NULL DEFAULT 1,
+    created_at TIMESTAMP NULL DEFAULT CURRENT_TIMESTAMP,
+    KEY idx_tenant (tenant_id)
+);
+
+CREATE TABLE IF NOT EXISTS i18n_webhook_outbox (
+    id BIGINT UNSIGNED AUTO_INCREMENT PRIMARY KEY,
+    event_id VARCHAR(64) NOT NULL,
+    tenant_id BIGINT UNSIGNED NOT NULL,
+    event_type VARCHAR(64) NOT NULL,
+    payload TEXT NOT NULL,
+    dispatched TINYINT(1) NOT NULL DEFAULT 0,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    KEY idx_dispatched (dispatched, id)
+);
+
+CREATE TABLE IF NOT EXISTS i18n_webhook_deliveries (
+    id BIGINT UNSIGNED AUTO_INCREMENT PRIMARY KEY,
+    webhook_id BIGINT UNSIGNED NOT NULL,
+    outbox_id BIGINT UNSIGNED NOT NULL,
+    event_type VARCHAR(64) NOT NULL,
+    status TINYINT UNSIGNED NOT NULL DEFAULT 0,  -- 0 待投递 1 成功 2 失败
+    attempts INT UNSIGNED NOT NULL DEFAULT 0,
+    response_status SMALLINT UNSIGNED,
+    error VARCHAR(255),
+    next_retry_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    KEY idx_webhook (webhook_id),
+    KEY idx_due (status, next_retry_at)
+);
@@ -0,0 +1,17 @@
+-- 审计日志（只追加）
+CREATE TABLE IF NOT EXISTS i18n_audit_logs (
+    id BIGINT UNSIGNED AUTO_INCREMENT PRIMARY KEY,
+    tenant_id BIGINT UNSIGNED,
+    actor_id BIGINT UNSIGNED,
+    subject_id BIGINT UNSIGNED,
+    subject VARCHAR(128),
+    event_type VARCHAR(64) NOT NULL,
+    ip VARCHAR(64),
+    user_agent VARCHAR(512),
+    outcome TINYINT UNSIGNED NOT NULL,  -- 1 成功 0 失败
+    reason VARCHAR(255),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    KEY idx_tenant_created (tenant_id, created_at),
+    KEY idx_subject (subject_id),
+    KEY idx_event_type (event_type)
+);
@@ -0,0 +1,21 @@
+-- 用户登录标识，一个用户可关联多个用户名/手机号/邮箱及第三方身份，标识在租户内唯一
+CREATE TABLE IF NOT EXISTS i18n_user_identities (
+    id BIGINT UNSIGNED AUTO_INCREMENT PRIMARY KEY,
+    tenant_id BIGINT UNSIGNED NOT NULL,
+    user_id BIGINT UNSIGNED NOT NULL,
+    identity_type VARCHAR(16) NOT NULL,     -- username / phone / email / external
+    provider VARCHAR(32) NOT NULL DEFAULT '',  -- 第三方身份的登录方式，如 github、ldap
+    identifier VARCHAR(255) NOT NULL,
+    verified_at TIMESTAMP NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE KEY uk_tenant_identity (tenant_id, identity_type, provider, identifier),
+    KEY idx_user (user_id)
+);
+
+-- 已有用户的用户名/手机号/邮箱
+INSERT INTO i18n_user_identities (tenant_id, user_id, identity_type, provider, identifier, created_at)
+SELECT tenant_id, id, 'username', '', username, created_at FROM i18n_users WHERE username IS NOT NULL;
+INSERT INTO i18n_user_identities (tenant_id, user_id, identity_type, provider, identifier, created_at)
+SELECT tenant_id, id, 'phone', '', phone, created_at FROM i18n_users WHERE phone IS NOT NULL;
+INSERT INTO i18n_user_identities (tenant_id, user_id, identity_type, provider, identifier, created_at)
+SELECT tenant_id, id, 'email', '', email, created_at FROM i18n_users WHERE email IS NOT NULL;
@@ -0,0 +1 @@
+ALTER TABLE i18n_users DROP COLUMN prev_status;
@@ -0,0 +1,17 @@
+-- 注销用户的登录标识移回登录标识表，已被其他用户使用的标识丢弃
+INSERT IGNORE INTO i18n_user_identities (id, tenant_id, user_id, identity_type, provider, identifier, verified_at, created_at)
+SELECT id, tenant_id, user_id, identity_type, provider, identifier, verified_at, created_at FROM i18n_deleted_user_identities;
+-- 移回成功的标识写回用户表
+UPDATE i18n_users SET username = (
+    SELECT d.identifier FROM i18n_deleted_user_identities d JOIN i18n_user_identities i ON i.id = d.id AND i.user_id = d.user_id
+    WHERE d.user_id = i18n_users.id AND d.identity_type = 'username' AND d.is_primary
+) WHERE status = 5;
+UPDATE i18n_users SET phone = (
+    SELECT d.identifier FROM i18n_deleted_user_identities d JOIN i18n_user_identities i ON i.id = d.id AND i.user_id = d.user_id
+    WHERE d.user_id = i18n_users.id AND d.identity_type = 'phone' AND d.is_primary
+) WHERE status = 5;
+UPDATE i18n_users SET email = (
+    SELECT d.identifier FROM i18n_deleted_user_identities d JOIN i18n_user_identities i ON i.id = d.id AND i.user_id = d.user_id
+    WHERE d.user_id = i18n_users.id AND d.identity_type = 'email' AND d.is_primary
+) WHERE status = 5;
+DROP TABLE IF EXISTS i18n_deleted_user_identities;
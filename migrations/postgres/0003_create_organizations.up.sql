@@ -0,0 +1,36 @@
+-- 组织、成员与邀请
+CREATE TABLE IF NOT EXISTS i18n_organizations (
+    id BIGSERIAL PRIMARY KEY,
+    tenant_id BIGINT NOT NULL,
+    name VARCHAR(128) NOT NULL,
+    description VARCHAR(512),
+    crt_by BIGINT,
+    created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS idx_organizations_tenant ON i18n_organizations (tenant_id);
+
+CREATE TABLE IF NOT EXISTS i18n_org_members (
+    org_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    role VARCHAR(32) NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (org_id, user_id)
+);
+CREATE INDEX IF NOT EXISTS idx_org_members_user ON i18n_org_members (user_id);
+
+CREATE TABLE IF NOT EXISTS i18n_org_invitations (
+    id BIGSERIAL PRIMARY KEY,
+    tenant_id BIGINT NOT NULL,
+    org_id BIGINT NOT NULL,
+    invite_type VARCHAR(16) NOT NULL,
+    identifier VARCHAR(128) NOT NULL,
+    role VARCHAR(32) NOT NULL,
+    token VARCHAR(64) NOT NULL UNIQUE,
+    status SMALLINT NOT NULL DEFAULT 0,
+    invited_by BIGINT NOT NULL,
+    accepted_by BIGINT,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS idx_org_invitations_org ON i18n_org_invitations (org_id);
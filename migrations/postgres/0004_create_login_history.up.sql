@@ -0,0 +1,15 @@
+-- 登录历史
+CREATE TABLE IF NOT EXISTS i18n_login_history (
+    id BIGSERIAL PRIMARY KEY,
+    tenant_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    login_type VARCHAR(16) NOT NULL,
+    ip VARCHAR(64),
+    ip_range VARCHAR(64),
+    user_agent VARCHAR(512),
+    device_id VARCHAR(64) NOT NULL,
+    success BOOLEAN NOT NULL,
+    reason VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS idx_login_history_user ON i18n_login_history (user_id, success);
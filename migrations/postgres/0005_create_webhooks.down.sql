@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS i18n_webhook_deliveries;
+DROP TABLE IF EXISTS i18n_webhook_outbox;
+DROP TABLE IF EXISTS i18n_webhooks;
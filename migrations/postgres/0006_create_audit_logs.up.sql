@@ -0,0 +1,17 @@
+-- 审计日志（只追加）
+CREATE TABLE IF NOT EXISTS i18n_audit_logs (
+    id BIGSERIAL PRIMARY KEY,
+    tenant_id BIGINT,
+    actor_id BIGINT,
+    subject_id BIGINT,
+    subject VARCHAR(128),
+    event_type VARCHAR(64) NOT NULL,
+    ip VARCHAR(64),
+    user_agent VARCHAR(512),
+    outcome SMALLINT NOT NULL,  -- 1 成功 0 失败
+    reason VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS idx_audit_logs_tenant_created ON i18n_audit_logs (tenant_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_audit_logs_subject ON i18n_audit_logs (subject_id);
+CREATE INDEX IF NOT EXISTS idx_audit_logs_event_type ON i18n_audit_logs (event_type);
@@ -0,0 +1 @@
+DROP TABLE IF EXISTS i18n_user_identities;
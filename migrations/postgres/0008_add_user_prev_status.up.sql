@@ -0,0 +1,2 @@
+-- 注销前的用户状态，恢复注销时还原
+ALTER TABLE i18n_users ADD COLUMN prev_status SMALLINT NULL;
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS i18n_tenant_settings;
+DROP TABLE IF EXISTS i18n_tenants;
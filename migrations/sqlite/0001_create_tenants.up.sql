@@ -0,0 +1,21 @@
+-- 租户表
+CREATE TABLE IF NOT EXISTS i18n_tenants (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    code VARCHAR(64) NOT NULL UNIQUE,
+    name VARCHAR(128) NOT NULL,
+    domain VARCHAR(255) UNIQUE,
+    client_id VARCHAR(64) UNIQUE,
+    status SMALLINT NOT NULL DEFAULT 1,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- 默认租户，请求未携带租户信息时使用
+INSERT OR IGNORE INTO i18n_tenants (code, name) VALUES ('default', '默认租户');
+
+-- 租户认证配置（JSON，未配置时使用默认值）
+CREATE TABLE IF NOT EXISTS i18n_tenant_settings (
+    tenant_id BIGINT PRIMARY KEY,
+    settings TEXT NOT NULL,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,23 @@
+-- 用户表，用户名/手机号/邮箱在租户内唯一
+CREATE TABLE IF NOT EXISTS i18n_users (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    tenant_id BIGINT NOT NULL DEFAULT 0,
+    username VARCHAR(50),
+    phone VARCHAR(20),
+    email VARCHAR(100),
+    password VARCHAR(255) NOT NULL,
+    nickname VARCHAR(64),
+    avatar VARCHAR(512),
+    gender SMALLINT,
+    birthday DATE,
+    status SMALLINT NOT NULL DEFAULT 1,  -- 1 正常 2 禁用 3 锁定 4 待验证 5 已注销
+    crt_by VARCHAR(64),
+    deleted_at TIMESTAMP NULL,
+    last_login TIMESTAMP NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT uk_tenant_username UNIQUE (tenant_id, username),
+    CONSTRAINT uk_tenant_phone UNIQUE (tenant_id, phone),
+    CONSTRAINT uk_tenant_email UNIQUE (tenant_id, email)
+);
+CREATE INDEX IF NOT EXISTS idx_users_status_deleted ON i18n_users (status, deleted_at);
@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS i18n_org_invitations;
+DROP TABLE IF EXISTS i18n_org_members;
+DROP TABLE IF EXISTS i18n_organizations;
@@ -0,0 +1,38 @@
+-- Webhook 订阅、发件箱与投递记录
+CREATE TABLE IF NOT EXISTS i18n_webhooks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    tenant_id BIGINT NOT NULL,
+    url VARCHAR(512) NOT NULL,
+    secret VARCHAR(128) NOT NULL,
+    events VARCHAR(255) NOT NULL,  -- 逗号分隔的事件类型
+    enabled BOOLEAN NOT NULL DEFAULT 1,
+    created_at TIMESTAMP NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS idx_webhooks_tenant ON i18n_webhooks (tenant_id);
+
+CREATE TABLE IF NOT EXISTS i18n_webhook_outbox (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    event_id VARCHAR(64) NOT NULL,
+    tenant_id BIGINT NOT NULL,
+    event_type VARCHAR(64) NOT NULL,
+    payload TEXT NOT NULL,
+    dispatched BOOLEAN NOT NULL DEFAULT 0,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS idx_webhook_outbox_dispatched ON i18n_webhook_outbox (dispatched, id);
+
+CREATE TABLE IF NOT EXISTS i18n_webhook_deliveries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    webhook_id BIGINT NOT NULL,
+    outbox_id BIGINT NOT NULL,
+    event_type VARCHAR(64) NOT NULL,
+    status SMALLINT NOT NULL DEFAULT 0,  -- 0 待投递 1 成功 2 失败
+    attempts INTEGER NOT NULL DEFAULT 0,
+    response_status INTEGER,
+    error VARCHAR(255),
+    next_retry_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_webhook ON i18n_webhook_deliveries (webhook_id);
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_due ON i18n_webhook_deliveries (status, next_retry_at);
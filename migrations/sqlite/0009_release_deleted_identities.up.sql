@@ -0,0 +1,21 @@
+-- 注销用户的登录标识，注销时从登录标识表移入，释放用户名/手机号/邮箱供重新注册；恢复注销时移回
+CREATE TABLE IF NOT EXISTS i18n_deleted_user_identities (
+    id BIGINT PRIMARY KEY,  -- 原登录标识 id
+    tenant_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    identity_type VARCHAR(16) NOT NULL,
+    provider VARCHAR(32) NOT NULL DEFAULT '',
+    identifier VARCHAR(255) NOT NULL,
+    verified_at TIMESTAMP NULL,
+    created_at TIMESTAMP NULL,
+    is_primary BOOLEAN NOT NULL DEFAULT 0  -- 是否为用户表中的用户名/手机号/邮箱
+);
+CREATE INDEX IF NOT EXISTS idx_deleted_user_identities_user ON i18n_deleted_user_identities (user_id);
+
+-- 已注销用户的登录标识
+INSERT INTO i18n_deleted_user_identities (id, tenant_id, user_id, identity_type, provider, identifier, verified_at, created_at, is_primary)
+SELECT i.id, i.tenant_id, i.user_id, i.identity_type, i.provider, i.identifier, i.verified_at, i.created_at,
+    CASE WHEN i.provider = '' AND ((i.identity_type = 'username' AND i.identifier = u.username) OR (i.identity_type = 'phone' AND i.identifier = u.phone) OR (i.identity_type = 'email' AND i.identifier = u.email)) THEN 1 ELSE 0 END
+FROM i18n_user_identities i JOIN i18n_users u ON u.id = i.user_id WHERE u.status = 5;
+DELETE FROM i18n_user_identities WHERE user_id IN (SELECT id FROM i18n_users WHERE status = 5);
+UPDATE i18n_users SET username = NULL, phone = NULL, email = NULL WHERE status = 5;
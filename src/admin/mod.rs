@@ -0,0 +1,81 @@
+use std::sync::Arc;
+
+use axum::{async_trait, extract::{FromRef, FromRequestParts, Request, State}, http::request::Parts, middleware::Next, response::{IntoResponse, Response}};
+
+use crate::{
+    audit::{AuditEvent, AuditEventType, AuditOutcome, AuditProvider},
+    common::ClientInfo,
+    config::ConfigHandle,
+    errors::{AuthixError, AuthixResult},
+};
+
+pub mod user;
+
+/// 管理端 API Key 请求头
+const ADMIN_KEY_HEADER: &str = "x-admin-key";
+/// 管理操作人请求头，可选，值为操作人的用户 ID
+const ADMIN_ACTOR_HEADER: &str = "x-admin-actor";
+
+/// 常量时间比较，避免通过响应耗时猜测密钥
+fn secure_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// 管理端鉴权中间件，校验 `x-admin-key` 与配置 `server.admin_api_key` 一致
+/// 未配置密钥时管理端接口全部拒绝
+pub async fn require_admin(State(config): State<ConfigHandle>, req: Request, next: Next) -> Response {
+    let config = config.get();
+    let expected = match config.server.admin_api_key.as_deref() {
+        Some(v) if !v.is_empty() => v,
+        _ => return AuthixError::Forbidden("auth.admin_api_disabled".into()).into_response(),
+    };
+    let provided = req.headers().get(ADMIN_KEY_HEADER).and_then(|v| v.to_str().ok()).unwrap_or_default();
+    if !secure_eq(provided.as_bytes(), expected.as_bytes()) {
+        return AuthixError::InvalidCredentials("auth.invalid_admin_key".into()).into_response();
+    }
+    next.run(req).await
+}
+
+/// 管理操作的审计上下文，API Key 不对应具体用户，操作人取 `x-admin-actor`，未携带时为空
+pub struct AdminAudit {
+    audit: Arc<dyn AuditProvider>,
+    client: ClientInfo,
+    actor_id: Option<u64>,
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AdminAudit
+where
+    S: Send + Sync,
+    Arc<dyn AuditProvider>: FromRef<S>,
+{
+    type Rejection = AuthixError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let actor_id = match parts.headers.get(ADMIN_ACTOR_HEADER) {
+            Some(v) => Some(
+                v.to_str().ok().and_then(|v| v.trim().parse().ok())
+                    .ok_or_else(|| AuthixError::InvalidParameter("param.invalid_admin_actor".into()))?,
+            ),
+            None => None,
+        };
+        let client = ClientInfo::from_request_parts(parts, state).await.unwrap_or_default();
+        Ok(Self { audit: Arc::from_ref(state), client, actor_id })
+    }
+}
+
+impl AdminAudit {
+    /// 审计事件，操作人为当前管理员
+    pub fn event(&self, event_type: AuditEventType) -> AuditEvent {
+        AuditEvent { actor_id: self.actor_id, ..AuditEvent::new(event_type, AuditOutcome::Success, &self.client) }
+    }
+
+    /// 按操作结果记录审计事件，失败时记录原因
+    pub async fn record<T>(&self, event: AuditEvent, result: AuthixResult<T>) -> AuthixResult<T> {
+        match &result {
+            Ok(_) => self.audit.record(event).await,
+            Err(e) => self.audit.record(AuditEvent { outcome: AuditOutcome::Failure, ..event }.reason(e.to_string())).await,
+        }
+        result
+    }
+}
@@ -0,0 +1,313 @@
+use std::sync::Arc;
+
+use argon2::{password_hash::{rand_core::OsRng, PasswordHasher, SaltString}, Argon2};
+use axum::{extract::{Path, Query, State}, http::StatusCode, Json};
+use serde::Deserialize;
+use serde_json::json;
+use tracing::warn;
+
+use crate::{
+    admin::AdminAudit,
+    audit::{AuditEvent, AuditEventType},
+    cache::SessionStore,
+    common::{CursorPage, R},
+    errors::{AuthixError, AuthixResult},
+    tenant::TenantProvider,
+    enums::UserStatus,
+    event::{emit, DomainEvent, EventPublisher},
+    user::{UpdateUserRequest, User, UserProvider, UserSearchQuery, UserSummary},
+    utils::regex::{is_valid_email, is_valid_phone, is_valid_username},
+    webhook::{WebhookProvider, EVENT_SESSION_REVOKED},
+};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AdminCreateUserRequest {
+    pub tenant_id: u64,
+    pub username: Option<String>,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    pub password: String,
+    #[serde(default)]
+    pub status: Option<UserStatus>, // 初始状态，仅支持 active、pending
+}
+
+/// 校验用户名/手机号/邮箱格式及租户内唯一性，`exclude_id` 为修改时的当前用户
+async fn check_identifiers(
+    user_provider: &Arc<dyn UserProvider>,
+    tenant_id: u64,
+    exclude_id: Option<u64>,
+    username: Option<&String>,
+    phone: Option<&String>,
+    email: Option<&String>,
+) -> AuthixResult<()> {
+    let conflict = |u: Option<User>| u.is_some_and(|u| Some(u.id) != exclude_id);
+    if let Some(username) = username {
+        if !is_valid_username(username) {
+            return Err(AuthixError::InvalidParameter("param.username_invalid".into()));
+        }
+        if conflict(user_provider.get_user_by_username(tenant_id, username.clone()).await?) {
+            return Err(AuthixError::UserAlreadyExists("user.username_exists".into()));
+        }
+    }
+    if let Some(phone) = phone {
+        if !is_valid_phone(phone) {
+            return Err(AuthixError::InvalidParameter("param.phone_invalid".into()));
+        }
+        if conflict(user_provider.get_user_by_phone(tenant_id, phone.clone()).await?) {
+            return Err(AuthixError::UserAlreadyExists("user.phone_exists".into()));
+        }
+    }
+    if let Some(email) = email {
+        if !is_valid_email(email) {
+            return Err(AuthixError::InvalidParameter("param.email_invalid".into()));
+        }
+        if conflict(user_provider.get_user_by_email(tenant_id, email.clone()).await?) {
+            return Err(AuthixError::UserAlreadyExists("user.email_exists".into()));
+        }
+    }
+    Ok(())
+}
+
+/// 查询用户，不存在时返回 `UserNotFound`
+async fn find_user(user_provider: &Arc<dyn UserProvider>, id: u64) -> AuthixResult<UserSummary> {
+    user_provider
+        .get_user_summary(id)
+        .await?
+        .ok_or_else(|| AuthixError::UserNotFound("user.not_found".into()))
+}
+
+/// 以用户为被操作对象的管理审计事件
+fn user_event(admin: &AdminAudit, event_type: AuditEventType, user: &UserSummary) -> AuditEvent {
+    let event = admin.event(event_type).tenant(user.tenant_id).subject_id(Some(user.id));
+    match user.username.as_deref().or(user.phone.as_deref()).or(user.email.as_deref()) {
+        Some(subject) => event.subject(subject),
+        None => event,
+    }
+}
+
+pub async fn list_users(
+    State(user_provider): State<Arc<dyn UserProvider>>,
+    Query(q): Query<UserSearchQuery>,
+) -> AuthixResult<(StatusCode, Json<R<CursorPage<UserSummary>>>)> {
+    let page = user_provider.search_users(&q).await?;
+    Ok((StatusCode::OK, Json(R::ok_data(page))))
+}
+
+pub async fn get_user(
+    State(user_provider): State<Arc<dyn UserProvider>>,
+    Path(id): Path<u64>,
+) -> AuthixResult<(StatusCode, Json<R<UserSummary>>)> {
+    let user = find_user(&user_provider, id).await?;
+    Ok((StatusCode::OK, Json(R::ok_data(user))))
+}
+
+pub async fn create_user(
+    admin: AdminAudit,
+    State(user_provider): State<Arc<dyn UserProvider>>,
+    State(tenant_provider): State<Arc<dyn TenantProvider>>,
+    Json(payload): Json<AdminCreateUserRequest>,
+) -> AuthixResult<(StatusCode, Json<R<u64>>)> {
+    let mut event = admin.event(AuditEventType::AdminUserCreate).tenant(payload.tenant_id);
+    if let Some(subject) = payload.username.as_deref().or(payload.phone.as_deref()).or(payload.email.as_deref()) {
+        event = event.subject(subject);
+    }
+    let result = create(&user_provider, &tenant_provider, payload).await;
+    if let Ok(id) = result {
+        event = event.subject_id(Some(id));
+    }
+    let id = admin.record(event, result).await?;
+    Ok((StatusCode::OK, Json(R::ok_data(id))))
+}
+
+/// 校验并创建用户，返回用户 ID
+async fn create(
+    user_provider: &Arc<dyn UserProvider>,
+    tenant_provider: &Arc<dyn TenantProvider>,
+    payload: AdminCreateUserRequest,
+) -> AuthixResult<u64> {
+    if payload.username.is_none() && payload.phone.is_none() && payload.email.is_none() {
+        return Err(AuthixError::InvalidParameter("user.identifier_required".into()));
+    }
+    let status = payload.status.unwrap_or(UserStatus::Active);
+    if !matches!(status, UserStatus::Active | UserStatus::Pending) {
+        return Err(AuthixError::InvalidParameter("user.initial_status_invalid".into()));
+    }
+    if tenant_provider.get_tenant_by_id(payload.tenant_id).await?.is_none() {
+        return Err(AuthixError::TenantNotFound("tenant.not_found".into()));
+    }
+    let settings = tenant_provider.get_tenant_settings(payload.tenant_id).await?;
+    settings.password_policy.check(&payload.password).map_err(AuthixError::InvalidParameter)?;
+    check_identifiers(
+        user_provider,
+        payload.tenant_id,
+        None,
+        payload.username.as_ref(),
+        payload.phone.as_ref(),
+        payload.email.as_ref(),
+    ).await?;
+    // hash 密码
+    let argon2 = Argon2::default();
+    let password_hash = argon2
+        .hash_password(payload.password.as_bytes(), &SaltString::generate(&mut OsRng))
+        .map(|h| h.to_string())
+        .map_err(|e| AuthixError::Internal(format!("hash password error: {}", e)))?;
+    let new_user = User {
+        id: 0,
+        tenant_id: payload.tenant_id,
+        username: payload.username,
+        phone: payload.phone,
+        email: payload.email,
+        password: password_hash,
+        status,
+        crt_by: Some("admin".into()),
+    };
+    let user = user_provider.create_user(new_user).await?;
+    Ok(user.id)
+}
+
+pub async fn update_user(
+    admin: AdminAudit,
+    State(user_provider): State<Arc<dyn UserProvider>>,
+    Path(id): Path<u64>,
+    Json(payload): Json<UpdateUserRequest>,
+) -> AuthixResult<(StatusCode, Json<R<String>>)> {
+    let user = find_user(&user_provider, id).await?;
+    let result = async {
+        check_identifiers(
+            &user_provider,
+            user.tenant_id,
+            Some(id),
+            payload.username.as_ref(),
+            payload.phone.as_ref(),
+            payload.email.as_ref(),
+        ).await?;
+        user_provider.update_user(id, &payload).await
+    }.await;
+    admin.record(user_event(&admin, AuditEventType::AdminUserUpdate, &user), result).await?;
+    Ok((StatusCode::OK, Json(R::<String>::ok())))
+}
+
+/// 发布强制下线事件，失败仅记录日志
+async fn publish_session_revoked(
+    webhook: &Arc<dyn WebhookProvider>,
+    publisher: &Arc<dyn EventPublisher>,
+    tenant_id: u64,
+    id: u64,
+    reason: &str,
+) {
+    emit(publisher, tenant_id, DomainEvent::SessionRevoked { user_id: id, reason: reason.to_owned() }).await;
+    let data = json!({ "user_id": id, "reason": reason });
+    if let Err(e) = webhook.publish(tenant_id, EVENT_SESSION_REVOKED, data).await {
+        warn!("publish {} for user {} error: {}", EVENT_SESSION_REVOKED, id, e);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn set_user_status(
+    admin: AdminAudit,
+    sessions: Arc<dyn SessionStore>,
+    user_provider: Arc<dyn UserProvider>,
+    webhook: Arc<dyn WebhookProvider>,
+    publisher: Arc<dyn EventPublisher>,
+    id: u64,
+    status: UserStatus,
+    event_type: AuditEventType,
+) -> AuthixResult<(StatusCode, Json<R<String>>)> {
+    let user = find_user(&user_provider, id).await?;
+    let result = match user.status {
+        UserStatus::Deleted => Err(AuthixError::Conflict("user.deleted".into())),
+        _ => user_provider.update_user_status(id, status).await,
+    };
+    admin.record(user_event(&admin, event_type, &user), result).await?;
+    emit(&publisher, user.tenant_id, DomainEvent::UserStatusChanged { user_id: id, status }).await;
+    // 禁用或锁定后立即强制下线
+    if matches!(status, UserStatus::Disabled | UserStatus::Locked) {
+        crate::cache::revoke_user_tokens(&*sessions, user.tenant_id, id).await?;
+        let reason = if status == UserStatus::Disabled { "disabled" } else { "locked" };
+        publish_session_revoked(&webhook, &publisher, user.tenant_id, id, reason).await;
+    }
+    Ok((StatusCode::OK, Json(R::<String>::ok())))
+}
+
+pub async fn disable_user(
+    admin: AdminAudit,
+    State(sessions): State<Arc<dyn SessionStore>>,
+    State(user_provider): State<Arc<dyn UserProvider>>,
+    State(webhook): State<Arc<dyn WebhookProvider>>,
+    State(publisher): State<Arc<dyn EventPublisher>>,
+    Path(id): Path<u64>,
+) -> AuthixResult<(StatusCode, Json<R<String>>)> {
+    set_user_status(admin, sessions, user_provider, webhook, publisher, id, UserStatus::Disabled, AuditEventType::AdminUserDisable).await
+}
+
+pub async fn enable_user(
+    admin: AdminAudit,
+    State(sessions): State<Arc<dyn SessionStore>>,
+    State(user_provider): State<Arc<dyn UserProvider>>,
+    State(webhook): State<Arc<dyn WebhookProvider>>,
+    State(publisher): State<Arc<dyn EventPublisher>>,
+    Path(id): Path<u64>,
+) -> AuthixResult<(StatusCode, Json<R<String>>)> {
+    set_user_status(admin, sessions, user_provider, webhook, publisher, id, UserStatus::Active, AuditEventType::AdminUserEnable).await
+}
+
+pub async fn lock_user(
+    admin: AdminAudit,
+    State(sessions): State<Arc<dyn SessionStore>>,
+    State(user_provider): State<Arc<dyn UserProvider>>,
+    State(webhook): State<Arc<dyn WebhookProvider>>,
+    State(publisher): State<Arc<dyn EventPublisher>>,
+    Path(id): Path<u64>,
+) -> AuthixResult<(StatusCode, Json<R<String>>)> {
+    set_user_status(admin, sessions, user_provider, webhook, publisher, id, UserStatus::Locked, AuditEventType::AdminUserLock).await
+}
+
+/// 注销用户（软删除），保留期内可恢复
+pub async fn delete_user(
+    admin: AdminAudit,
+    State(sessions): State<Arc<dyn SessionStore>>,
+    State(user_provider): State<Arc<dyn UserProvider>>,
+    State(publisher): State<Arc<dyn EventPublisher>>,
+    Path(id): Path<u64>,
+) -> AuthixResult<(StatusCode, Json<R<String>>)> {
+    let user = find_user(&user_provider, id).await?;
+    let result = user_provider.delete_user(id).await;
+    admin.record(user_event(&admin, AuditEventType::AdminUserDelete, &user), result).await?;
+    emit(&publisher, user.tenant_id, DomainEvent::UserDeleted { user_id: id }).await;
+    crate::cache::revoke_user_tokens(&*sessions, user.tenant_id, id).await?;
+    Ok((StatusCode::OK, Json(R::<String>::ok())))
+}
+
+/// 恢复保留期内已注销的用户
+pub async fn restore_user(
+    admin: AdminAudit,
+    State(user_provider): State<Arc<dyn UserProvider>>,
+    State(publisher): State<Arc<dyn EventPublisher>>,
+    Path(id): Path<u64>,
+) -> AuthixResult<(StatusCode, Json<R<String>>)> {
+    let user = find_user(&user_provider, id).await?;
+    let result = match user_provider.restore_user(id).await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(AuthixError::UserNotFound("user.deleted_not_found".into())),
+        Err(e) => Err(e),
+    };
+    admin.record(user_event(&admin, AuditEventType::AdminUserRestore, &user), result).await?;
+    emit(&publisher, user.tenant_id, DomainEvent::UserRestored { user_id: id }).await;
+    Ok((StatusCode::OK, Json(R::<String>::ok())))
+}
+
+/// 强制下线：删除访问令牌并使已签发的刷新令牌失效
+pub async fn logout_user(
+    admin: AdminAudit,
+    State(sessions): State<Arc<dyn SessionStore>>,
+    State(user_provider): State<Arc<dyn UserProvider>>,
+    State(webhook): State<Arc<dyn WebhookProvider>>,
+    State(publisher): State<Arc<dyn EventPublisher>>,
+    Path(id): Path<u64>,
+) -> AuthixResult<(StatusCode, Json<R<String>>)> {
+    let user = find_user(&user_provider, id).await?;
+    let result = crate::cache::revoke_user_tokens(&*sessions, user.tenant_id, id).await;
+    admin.record(user_event(&admin, AuditEventType::AdminUserLogout, &user), result).await?;
+    publish_session_revoked(&webhook, &publisher, user.tenant_id, id, "admin_logout").await;
+    Ok((StatusCode::OK, Json(R::<String>::ok())))
+}
@@ -0,0 +1,225 @@
+use std::sync::Arc;
+
+use axum::{middleware, routing::{get, post}, Router};
+use tokio::sync::watch;
+
+use crate::auth_handler::{list_auth_methods, login_handler, social_authorize, logout_handler, refresh_token, register_handler, send_code, verify_code};
+use crate::user::{change_password, delete_user, online_count, user_profile, online_users, spawn_purge_task};
+use crate::user::identity::{link_external_identity, link_identity, list_identities, unlink_identity};
+use crate::tenant::{create_tenant, current_tenant, list_tenants};
+use crate::tenant::settings::{get_tenant_settings, update_tenant_settings};
+use crate::admin::require_admin;
+use crate::login_history::login_history;
+use crate::webhook::delivery::{spawn_webhook_dispatcher, spawn_webhook_purge_task};
+use crate::shutdown::{self, Background};
+use crate::config::reload::spawn_config_watcher;
+use crate::audit::{export_audit_events, list_audit_events};
+use crate::organization::{
+    accept_invitation, create_organization, delete_organization, get_organization, invite_member, list_members,
+    list_organizations, remove_member, switch_organization, update_member, update_organization,
+};
+use crate::state::{AppState, AppStateBuilder};
+use crate::utils::uuid::get_token;
+use crate::provider::{login::LoginProvider, register::RegisterProvider};
+use crate::user::UserProvider;
+use crate::{admin, config::{self, AuthixConfig, ConfigError, ConfigHandle}, health, i18n, metrics, migrate, webhook};
+
+/// 认证服务构建器，创建可嵌入宿主应用的 `Router`：
+///
+/// ```ignore
+/// let authix = AuthixBuilder::from_env()?.users(Arc::new(MyUserStore)).build().await?;
+/// let app = Router::new().nest("/iam", authix.router());
+/// ```
+pub struct AuthixBuilder {
+    state: AppStateBuilder,
+    shutdown: Option<watch::Receiver<bool>>,
+    watch_config: bool,
+}
+
+impl AuthixBuilder {
+    pub fn new(config: ConfigHandle) -> Self {
+        Self { state: AppStateBuilder::new(config), shutdown: None, watch_config: false }
+    }
+
+    /// 按配置文件与环境变量加载配置
+    pub fn from_env() -> Result<Self, ConfigError> {
+        config::init().map(Self::new)
+    }
+
+    /// 替换用户存储
+    pub fn users(self, users: Arc<dyn UserProvider>) -> Self {
+        self.configure(|b| b.users(users))
+    }
+
+    /// 按标识注册登录方式，需同时在 `providers.login_types` 中启用
+    pub fn login_provider(self, login_type: impl Into<String>, provider: Arc<dyn LoginProvider>) -> Self {
+        self.configure(|b| b.login_provider(login_type, provider))
+    }
+
+    /// 按标识注册注册方式，需同时在 `providers.register_types` 中启用
+    pub fn register_provider(self, register_type: impl Into<String>, provider: Arc<dyn RegisterProvider>) -> Self {
+        self.configure(|b| b.register_provider(register_type, provider))
+    }
+
+    /// 替换连接池、存储或其他服务
+    pub fn configure(mut self, f: impl FnOnce(AppStateBuilder) -> AppStateBuilder) -> Self {
+        self.state = f(self.state);
+        self
+    }
+
+    /// 由宿主应用控制后台任务的停机信号，收到 `true` 后后台任务退出
+    pub fn shutdown_signal(mut self, shutdown: watch::Receiver<bool>) -> Self {
+        self.shutdown = Some(shutdown);
+        self
+    }
+
+    /// 配置文件修改或收到 SIGHUP 时按 `AuthixConfig::load` 重新加载配置。
+    /// 会覆盖 `new` 传入的配置并接管进程的 SIGHUP，嵌入宿主应用时通常不开启
+    pub fn watch_config(mut self) -> Self {
+        self.watch_config = true;
+        self
+    }
+
+    /// 创建应用状态，检查依赖与数据库结构版本后启动后台任务
+    pub async fn build(self) -> Result<Authix, String> {
+        let state = self.state.build();
+        check_providers(&state, &state.config.get())?;
+        // 按配置检查数据库/Redis 可用性
+        health::check_startup_dependencies(&state).await.map_err(|e| format!("dependencies unavailable: {}", e))?;
+        // 检查数据库结构版本，按配置执行迁移
+        migrate::prepare_schema(&state.db, &state.config.get()).await.map_err(|e| format!("database schema not ready: {}", e))?;
+        let (stop, shutdown) = match self.shutdown {
+            Some(shutdown) => (None, shutdown),
+            None => {
+                let (stop, shutdown) = watch::channel(false);
+                (Some(stop), shutdown)
+            }
+        };
+        let purge_task = spawn_purge_task(state.users.clone(), state.config.clone(), shutdown.clone());
+        let webhook_dispatcher = spawn_webhook_dispatcher(state.db.clone(), shutdown.clone());
+        let webhook_purge_task = spawn_webhook_purge_task(state.db.clone(), state.config.clone(), shutdown.clone());
+        let mut tasks = vec![webhook_dispatcher, purge_task, webhook_purge_task];
+        if self.watch_config {
+            let registry = state.clone();
+            tasks.push(spawn_config_watcher(state.config.clone(), move |config| check_providers(&registry, config), shutdown));
+        }
+        let router = routes(&state).with_state(state.clone());
+        let background = Background { state, tasks };
+        Ok(Authix { router, background, stop })
+    }
+}
+
+/// 配置中启用的认证方式需已注册 Provider，启动与热加载时检查
+fn check_providers(state: &AppState, config: &AuthixConfig) -> Result<(), String> {
+    let providers = &config.providers;
+    if let Some(t) = providers.login_types.iter().find(|t| !state.login.supports(t)) {
+        return Err(format!("providers.login_types: no login provider registered for {}", t));
+    }
+    if let Some(t) = providers.register_types.iter().find(|t| !state.register.supports(t)) {
+        return Err(format!("providers.register_types: no register provider registered for {}", t));
+    }
+    Ok(())
+}
+
+/// 已启动的认证服务
+pub struct Authix {
+    router: Router,
+    background: Background,
+    stop: Option<watch::Sender<bool>>,
+}
+
+impl Authix {
+    /// 全部接口路由，可直接作为服务或嵌套到宿主应用
+    pub fn router(&self) -> Router {
+        self.router.clone()
+    }
+
+    pub fn state(&self) -> &AppState {
+        &self.background.state
+    }
+
+    /// 停止后台任务、落库审计事件并关闭连接池。
+    /// 通过 `shutdown_signal` 传入停机信号时，需先由宿主应用发送信号
+    pub async fn shutdown(self) {
+        if let Some(stop) = &self.stop {
+            let _ = stop.send(true);
+        }
+        shutdown::finish(self.background).await;
+    }
+}
+
+/// 全部接口路由，依赖 `AppState`
+pub fn routes(state: &AppState) -> Router<AppState> {
+    let auth_router = Router::new()
+        .route("/register", post(register_handler))
+        .route("/code/verify", post(verify_code))
+        .route("/code/send", post(send_code))
+        .route("/login", post(login_handler))
+        .route("/methods", get(list_auth_methods))
+        .route("/social/:login_type/authorize", get(social_authorize))
+        .route("/logout", get(logout_handler));
+    let token_router = Router::new()
+        .route("/refresh", get(refresh_token))
+        .route("/get", get(get_token));
+    let user_router =  Router::new()
+        .route("/online_count", get(online_count))
+        .route("/online_users", get(online_users))
+        .route("/profile", get(user_profile))
+        .route("/login_history", get(login_history))
+        .route("/identities", get(list_identities))
+        .route("/identities/link", post(link_identity))
+        .route("/identities/link_external", post(link_external_identity))
+        .route("/identities/:id/unlink", post(unlink_identity))
+        .route("/password", post(change_password))
+        .route("/delete", get(delete_user));
+    let health_router = Router::new()
+        .route("/live", get(health::liveness))
+        .route("/ready", get(health::readiness));
+    let tenant_router = Router::new()
+        .route("/create", post(create_tenant))
+        .route("/list", get(list_tenants))
+        .route("/settings/:tenant_id", get(get_tenant_settings).post(update_tenant_settings))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_admin))
+        .route("/current", get(current_tenant));
+
+    let org_router = Router::new()
+        .route("/create", post(create_organization))
+        .route("/list", get(list_organizations))
+        .route("/switch", post(switch_organization))
+        .route("/invitation/accept", post(accept_invitation))
+        .route("/:org_id", get(get_organization))
+        .route("/:org_id/update", post(update_organization))
+        .route("/:org_id/delete", post(delete_organization))
+        .route("/:org_id/members", get(list_members))
+        .route("/:org_id/members/update", post(update_member))
+        .route("/:org_id/members/remove", post(remove_member))
+        .route("/:org_id/invite", post(invite_member));
+
+    let admin_router = Router::new()
+        .route("/users", get(admin::user::list_users).post(admin::user::create_user))
+        .route("/users/:id", get(admin::user::get_user).post(admin::user::update_user))
+        .route("/users/:id/disable", post(admin::user::disable_user))
+        .route("/users/:id/enable", post(admin::user::enable_user))
+        .route("/users/:id/lock", post(admin::user::lock_user))
+        .route("/users/:id/delete", post(admin::user::delete_user))
+        .route("/users/:id/restore", post(admin::user::restore_user))
+        .route("/users/:id/logout", post(admin::user::logout_user))
+        .route("/audit", get(list_audit_events))
+        .route("/audit/export", get(export_audit_events))
+        .route("/webhooks", get(webhook::list_webhooks).post(webhook::create_webhook))
+        .route("/webhooks/:id/delete", post(webhook::delete_webhook))
+        .route("/webhooks/:id/deliveries", get(webhook::list_deliveries))
+        .route("/webhooks/deliveries/:id/retry", post(webhook::retry_delivery))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_admin));
+
+    Router::new()
+    .nest("/auth", auth_router)
+    .nest("/token", token_router)
+    .nest("/user", user_router)
+    .nest("/tenant", tenant_router)
+    .nest("/org", org_router)
+    .nest("/admin", admin_router)
+    .nest("/health", health_router)
+    .layer(middleware::from_fn_with_state(state.config.clone(), i18n::negotiate_locale))
+    .layer(middleware::from_fn(metrics::track_http))
+}
@@ -0,0 +1,411 @@
+use std::sync::Arc;
+
+use axum::{async_trait, extract::{Query, State}, http::{header, StatusCode}, response::{IntoResponse, Response}, Json};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, oneshot};
+use tracing::warn;
+
+use crate::{
+    common::{ClientInfo, CursorPage, R},
+    errors::{AuthixError, AuthixResult},
+    storage::{self, decode_error, Column, ColumnIndex, Db, FromRecord, Row, Value},
+};
+
+pub const AUDIT_TABLE_NAME: &str = "i18n_audit_logs";
+/// 待写入审计事件队列长度，队列满时丢弃并告警
+const AUDIT_QUEUE_SIZE: usize = 10000;
+/// 单次导出最大条数
+const AUDIT_EXPORT_MAX: u32 = 10000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditEventType {
+    Login,
+    Register,
+    Logout,
+    UserDelete,
+    PasswordChange,
+    CodeVerify,
+    IdentityLink,
+    IdentityUnlink,
+    AdminUserCreate,
+    AdminUserUpdate,
+    AdminUserDisable,
+    AdminUserEnable,
+    AdminUserLock,
+    AdminUserDelete,
+    AdminUserRestore,
+    AdminUserLogout,
+}
+
+impl AuditEventType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditEventType::Login => "user.login",
+            AuditEventType::Register => "user.register",
+            AuditEventType::Logout => "user.logout",
+            AuditEventType::UserDelete => "user.delete",
+            AuditEventType::PasswordChange => "user.password_change",
+            AuditEventType::CodeVerify => "code.verify",
+            AuditEventType::IdentityLink => "identity.link",
+            AuditEventType::IdentityUnlink => "identity.unlink",
+            AuditEventType::AdminUserCreate => "admin.user_create",
+            AuditEventType::AdminUserUpdate => "admin.user_update",
+            AuditEventType::AdminUserDisable => "admin.user_disable",
+            AuditEventType::AdminUserEnable => "admin.user_enable",
+            AuditEventType::AdminUserLock => "admin.user_lock",
+            AuditEventType::AdminUserDelete => "admin.user_delete",
+            AuditEventType::AdminUserRestore => "admin.user_restore",
+            AuditEventType::AdminUserLogout => "admin.user_logout",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+#[repr(u8)]
+pub enum AuditOutcome {
+    Failure = 0,
+    #[default]
+    Success = 1,
+}
+
+impl From<AuditOutcome> for Value {
+    fn from(outcome: AuditOutcome) -> Self {
+        Value::from(outcome as u8)
+    }
+}
+
+impl Column for AuditOutcome {
+    fn decode<I: ColumnIndex>(row: &Row, index: I) -> Result<Self, sqlx::Error> {
+        match u8::decode(row, index)? {
+            0 => Ok(AuditOutcome::Failure),
+            1 => Ok(AuditOutcome::Success),
+            other => Err(decode_error(index, format!("invalid audit outcome {}", other))),
+        }
+    }
+}
+
+/// 审计事件，只追加不修改
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AuditEvent {
+    pub id: u64,
+    pub tenant_id: Option<u64>,
+    pub actor_id: Option<u64>,      // 操作人
+    pub subject_id: Option<u64>,    // 被操作用户
+    pub subject: Option<String>,    // 被操作标识，如用户名/手机号/邮箱
+    pub event_type: String,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub outcome: AuditOutcome,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Local>,
+}
+
+impl FromRecord for AuditEvent {
+    fn from_record(row: &Row) -> Result<Self, sqlx::Error> {
+        Ok(AuditEvent {
+            id: row.get("id")?,
+            tenant_id: row.get("tenant_id")?,
+            actor_id: row.get("actor_id")?,
+            subject_id: row.get("subject_id")?,
+            subject: row.get("subject")?,
+            event_type: row.get("event_type")?,
+            ip: row.get("ip")?,
+            user_agent: row.get("user_agent")?,
+            outcome: row.get("outcome")?,
+            reason: row.get("reason")?,
+            created_at: row.get("created_at")?,
+        })
+    }
+}
+
+impl AuditEvent {
+    pub fn new(event_type: AuditEventType, outcome: AuditOutcome, client: &ClientInfo) -> Self {
+        Self {
+            event_type: event_type.as_str().to_owned(),
+            outcome,
+            ip: client.ip.clone(),
+            user_agent: client.user_agent.clone(),
+            created_at: Local::now(),
+            ..Default::default()
+        }
+    }
+
+    pub fn tenant(mut self, tenant_id: u64) -> Self {
+        self.tenant_id = Some(tenant_id);
+        self
+    }
+
+    pub fn actor(mut self, actor_id: u64) -> Self {
+        self.actor_id = Some(actor_id);
+        self
+    }
+
+    pub fn subject_id(mut self, subject_id: Option<u64>) -> Self {
+        self.subject_id = subject_id;
+        self
+    }
+
+    pub fn subject(mut self, subject: &str) -> Self {
+        self.subject = Some(subject.to_owned());
+        self
+    }
+
+    pub fn reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AuditQuery {
+    pub tenant_id: Option<u64>,
+    pub actor_id: Option<u64>,
+    pub subject_id: Option<u64>,
+    pub subject: Option<String>,
+    pub event_type: Option<String>,
+    pub outcome: Option<AuditOutcome>,
+    pub ip: Option<String>,
+    pub from: Option<DateTime<Local>>,
+    pub to: Option<DateTime<Local>>,
+    pub cursor: Option<String>,     // 上一页最后一条记录 id
+    pub limit: Option<u32>,
+    pub format: Option<String>,     // 导出格式 "csv" | "json"
+}
+
+#[async_trait]
+pub trait AuditProvider: Send + Sync {
+    /// 记录审计事件，异步落库不阻塞请求
+    async fn record(&self, event: AuditEvent);
+    /// 按条件倒序查询审计事件
+    async fn query(&self, query: &AuditQuery) -> AuthixResult<CursorPage<AuditEvent>>;
+    /// 等待已记录的事件全部落库，停机时调用
+    async fn flush(&self) {}
+}
+
+enum AuditCommand {
+    Record(Box<AuditEvent>),
+    Flush(oneshot::Sender<()>),
+}
+
+pub struct AuditService {
+    db: Db,
+    sender: mpsc::Sender<AuditCommand>,
+}
+
+impl AuditService {
+    /// 创建审计服务并启动后台写入任务，需在 Tokio 运行时内调用
+    pub fn new(db: Db) -> Self {
+        let (sender, receiver) = mpsc::channel(AUDIT_QUEUE_SIZE);
+        tokio::spawn(Self::run(db.clone(), receiver));
+        Self { db, sender }
+    }
+
+    async fn run(db: Db, mut receiver: mpsc::Receiver<AuditCommand>) {
+        while let Some(command) = receiver.recv().await {
+            match command {
+                AuditCommand::Record(event) => {
+                    if let Err(e) = Self::insert(&db, &event).await {
+                        warn!("write audit event {} error: {}", event.event_type, e);
+                    }
+                }
+                // 队列按顺序处理，收到 Flush 时之前的事件均已写入
+                AuditCommand::Flush(done) => {
+                    let _ = done.send(());
+                }
+            }
+        }
+    }
+
+    async fn insert(pool: &Db, event: &AuditEvent) -> AuthixResult<()> {
+        storage::query(format!(
+            "INSERT INTO {} (tenant_id, actor_id, subject_id, subject, event_type, ip, user_agent, outcome, reason, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            AUDIT_TABLE_NAME
+        ))
+            .bind(event.tenant_id)
+            .bind(event.actor_id)
+            .bind(event.subject_id)
+            .bind(&event.subject)
+            .bind(&event.event_type)
+            .bind(&event.ip)
+            .bind(&event.user_agent)
+            .bind(event.outcome)
+            .bind(&event.reason)
+            .bind(event.created_at)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AuditProvider for AuditService {
+    async fn record(&self, event: AuditEvent) {
+        if let Err(e) = self.sender.try_send(AuditCommand::Record(Box::new(event))) {
+            warn!("audit queue is full or closed, drop event: {}", e);
+        }
+    }
+
+    async fn flush(&self) {
+        let (done, wait) = oneshot::channel();
+        if self.sender.send(AuditCommand::Flush(done)).await.is_ok() {
+            let _ = wait.await;
+        }
+    }
+
+    async fn query(&self, query: &AuditQuery) -> AuthixResult<CursorPage<AuditEvent>> {
+        let pool = &self.db;
+        let limit = query.limit.unwrap_or(50).clamp(1, AUDIT_EXPORT_MAX);
+
+        let text = |v: &Option<String>| v.as_deref().filter(|v| !v.is_empty()).map(Value::from);
+        let mut conditions: Vec<&str> = Vec::new();
+        let mut args: Vec<Value> = Vec::new();
+        for (cond, value) in [
+            ("tenant_id = ?", query.tenant_id.map(Value::from)),
+            ("actor_id = ?", query.actor_id.map(Value::from)),
+            ("subject_id = ?", query.subject_id.map(Value::from)),
+            ("subject = ?", text(&query.subject)),
+            ("event_type = ?", text(&query.event_type)),
+            ("outcome = ?", query.outcome.map(Value::from)),
+            ("ip = ?", text(&query.ip)),
+            ("created_at >= ?", query.from.map(Value::from)),
+            ("created_at < ?", query.to.map(Value::from)),
+        ] {
+            if let Some(v) = value {
+                conditions.push(cond);
+                args.push(v);
+            }
+        }
+        if let Some(cursor) = query.cursor.as_deref().filter(|c| !c.is_empty()) {
+            let id: u64 = cursor.parse().map_err(|_| AuthixError::InvalidParameter("param.invalid_cursor".into()))?;
+            conditions.push("id < ?");
+            args.push(id.into());
+        }
+
+        let where_clause = if conditions.is_empty() { String::new() } else { format!("WHERE {}", conditions.join(" AND ")) };
+        let sql = format!(
+            "SELECT id, tenant_id, actor_id, subject_id, subject, event_type, ip, user_agent, outcome, reason, created_at FROM {} {} ORDER BY id DESC LIMIT ?",
+            AUDIT_TABLE_NAME, where_clause
+        );
+        let q = args.into_iter().fold(storage::query(sql), |q, arg| q.bind(arg));
+        let mut records: Vec<AuditEvent> = q.bind(limit + 1).fetch_all(pool).await?;
+
+        let next_cursor = if records.len() as u32 > limit {
+            records.truncate(limit as usize);
+            records.last().map(|e| e.id.to_string())
+        } else {
+            None
+        };
+        Ok(CursorPage { records, next_cursor })
+    }
+}
+
+pub async fn list_audit_events(
+    State(audit_provider): State<Arc<dyn AuditProvider>>,
+    Query(q): Query<AuditQuery>,
+) -> AuthixResult<(StatusCode, Json<R<CursorPage<AuditEvent>>>)> {
+    let page = audit_provider.query(&q).await?;
+    Ok((StatusCode::OK, Json(R::ok_data(page))))
+}
+
+/// CSV 字段转义
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+/// 导出审计事件，默认 CSV，`format=json` 时按行输出 JSON
+pub async fn export_audit_events(
+    State(audit_provider): State<Arc<dyn AuditProvider>>,
+    Query(mut q): Query<AuditQuery>,
+) -> Response {
+    q.limit = Some(q.limit.unwrap_or(AUDIT_EXPORT_MAX).min(AUDIT_EXPORT_MAX));
+    let page = match audit_provider.query(&q).await {
+        Ok(page) => page,
+        Err(e) => return e.into_response(),
+    };
+
+    let opt = |v: Option<u64>| v.map(|v| v.to_string()).unwrap_or_default();
+    if q.format.as_deref() == Some("json") {
+        let body = page
+            .records
+            .iter()
+            .filter_map(|e| serde_json::to_string(e).ok())
+            .collect::<Vec<_>>()
+            .join("\n");
+        return (
+            [(header::CONTENT_TYPE, "application/x-ndjson"), (header::CONTENT_DISPOSITION, "attachment; filename=\"audit.jsonl\"")],
+            body,
+        ).into_response();
+    }
+
+    let mut body = String::from("id,created_at,tenant_id,event_type,outcome,actor_id,subject_id,subject,ip,user_agent,reason\n");
+    for e in &page.records {
+        let outcome = if e.outcome == AuditOutcome::Success { "success" } else { "failure" };
+        let row = [
+            e.id.to_string(),
+            e.created_at.to_rfc3339(),
+            opt(e.tenant_id),
+            e.event_type.clone(),
+            outcome.to_owned(),
+            opt(e.actor_id),
+            opt(e.subject_id),
+            e.subject.clone().unwrap_or_default(),
+            e.ip.clone().unwrap_or_default(),
+            e.user_agent.clone().unwrap_or_default(),
+            e.reason.clone().unwrap_or_default(),
+        ];
+        body.push_str(&row.iter().map(|v| csv_field(v)).collect::<Vec<_>>().join(","));
+        body.push('\n');
+    }
+    (
+        [(header::CONTENT_TYPE, "text/csv; charset=utf-8"), (header::CONTENT_DISPOSITION, "attachment; filename=\"audit.csv\"")],
+        body,
+    ).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::database::test_db;
+
+    #[tokio::test]
+    async fn record_and_query_events() {
+        let audit = AuditService::new(test_db().await);
+        let client = ClientInfo { ip: Some("10.0.0.1".into()), user_agent: Some("ua".into()), ..Default::default() };
+        audit.record(AuditEvent::new(AuditEventType::Register, AuditOutcome::Success, &client).tenant(1).subject_id(Some(7)).subject("alice")).await;
+        audit.record(AuditEvent::new(AuditEventType::Login, AuditOutcome::Failure, &client).tenant(1).subject("alice").reason("auth.invalid_credentials")).await;
+        audit.record(AuditEvent::new(AuditEventType::Login, AuditOutcome::Success, &ClientInfo::default()).tenant(2).subject_id(Some(8))).await;
+        audit.flush().await;
+
+        let page = audit.query(&AuditQuery { event_type: Some("user.login".into()), ..Default::default() }).await.unwrap();
+        assert_eq!(page.records.len(), 2);
+        assert_eq!(page.records[0].tenant_id, Some(2));
+        let failed = &audit.query(&AuditQuery { outcome: Some(AuditOutcome::Failure), ..Default::default() }).await.unwrap().records[0];
+        assert_eq!((failed.ip.as_deref(), failed.reason.as_deref()), (Some("10.0.0.1"), Some("auth.invalid_credentials")));
+
+        // 按 id 倒序分页
+        let mut query = AuditQuery { tenant_id: Some(1), limit: Some(1), ..Default::default() };
+        let first = audit.query(&query).await.unwrap();
+        assert_eq!(first.records[0].event_type, "user.login");
+        query.cursor = first.next_cursor;
+        let second = audit.query(&query).await.unwrap();
+        assert_eq!((second.records[0].event_type.as_str(), second.records[0].subject_id), ("user.register", Some(7)));
+        assert!(second.next_cursor.is_none());
+
+        query.cursor = Some("x".into());
+        assert!(matches!(audit.query(&query).await, Err(AuthixError::InvalidParameter(_))));
+    }
+
+    #[test]
+    fn csv_field_quotes_special_characters() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("a\nb"), "\"a\nb\"");
+    }
+}
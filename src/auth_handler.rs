@@ -1,10 +1,12 @@
 use std::sync::Arc;
 
-use axum::{http::{HeaderMap, StatusCode}, Extension, Json, response::IntoResponse};
+use axum::{extract::{Path, Query, State}, http::{HeaderMap, StatusCode}, Json};
 use axum_extra::TypedHeader;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::warn;
 
-use crate::{common::{UidHeader, R}, enums::{AuthEnum, AuthType}, provider::{login::{LoginProvider, LoginRequest, LoginResponse}, register::{RegisterProvider, RegisterRequest}}, user::UserProvider, utils::jwt};
+use crate::{audit::{AuditEvent, AuditEventType, AuditOutcome, AuditProvider}, cache::{CodeStore, SessionStore}, common::{ClientInfo, UidHeader, R}, config::ConfigHandle, enums::{AuthEnum, AuthType}, errors::{AuthixError, AuthixResult}, i18n::{self, Msg}, metrics, event::{emit, DomainEvent, EventPublisher}, login_history::{track_login, LoginAttempt, LoginHistoryProvider}, provider::{login::{LoginProvider, LoginRequest, LoginResponse, LoginService}, register::{RegisterProvider, RegisterRequest, RegisterService}, SchemaField}, notify::NotifyProvider, organization::OrganizationProvider, state::AppState, tenant::CurrentTenant, user::UserProvider, utils::jwt, webhook::{WebhookProvider, EVENT_SESSION_REVOKED, EVENT_USER_LOGIN}};
 use crate::utils::regex::{is_valid_email, is_valid_phone};
 
 #[derive(Debug, Clone, Deserialize)]
@@ -20,95 +22,276 @@ pub struct SendCodeRequest {
     pub verify_type: AuthType,  // 验证类型sms、email
 }
 
+/// 认证方式及其请求字段
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthMethod {
+    #[serde(rename = "type")]
+    pub auth_type: String,
+    pub fields: Vec<SchemaField>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthMethodsResponse {
+    pub login: Vec<AuthMethod>,
+    pub register: Vec<AuthMethod>,
+    pub allow_register: bool,
+}
+
+/// 当前租户可用的登录/注册方式，供客户端按字段声明渲染表单
+pub async fn list_auth_methods(
+    CurrentTenant(ctx): CurrentTenant,
+    State(state): State<AppState>,
+) -> (StatusCode, Json<R<AuthMethodsResponse>>) {
+    let config = state.config.get();
+    let login = state.login.schemas()
+        .filter(|(t, _)| config.providers.login_type_enabled(t) && ctx.settings.login_type_enabled(t))
+        .map(|(t, schema)| AuthMethod { auth_type: t.to_string(), fields: schema.fields })
+        .collect();
+    let register = state.register.schemas()
+        .filter(|(t, _)| config.providers.register_type_enabled(t) && ctx.settings.register_type_enabled(t))
+        .map(|(t, schema)| AuthMethod { auth_type: t.to_string(), fields: schema.fields })
+        .collect();
+    let resp = AuthMethodsResponse { login, register, allow_register: ctx.settings.allow_register };
+    (StatusCode::OK, Json(R::ok_data(resp)))
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuthorizeQuery {
+    pub redirect_uri: String,   // 身份提供方授权后的回调地址，需在租户配置的允许列表中
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthorizeResponse {
+    pub authorize_url: String,
+}
+
+/// 第三方登录第一步：返回身份提供方授权地址，回调后以 `code`、`state` 调用登录接口
+pub async fn social_authorize(
+    CurrentTenant(ctx): CurrentTenant,
+    State(state): State<AppState>,
+    Path(login_type): Path<String>,
+    Query(query): Query<AuthorizeQuery>,
+) -> AuthixResult<(StatusCode, Json<R<AuthorizeResponse>>)> {
+    let authorize_url = state.login.authorize_url(&ctx, &login_type, &query.redirect_uri, &state).await?;
+    Ok((StatusCode::OK, Json(R::ok_data(AuthorizeResponse { authorize_url }))))
+}
+
 pub async fn register_handler(
-    Extension(user_service): Extension<Arc<dyn UserProvider>>,
-    Extension(register_service): Extension<Arc<dyn RegisterProvider>>,
-    Json(payload): Json<RegisterRequest>,
-) -> impl IntoResponse {
-    match register_service.register(&payload, user_service).await {
+    CurrentTenant(ctx): CurrentTenant,
+    client: ClientInfo,
+    State(state): State<AppState>,
+    State(audit): State<Arc<dyn AuditProvider>>,
+    State(register_service): State<Arc<RegisterService>>,
+    State(org_provider): State<Arc<dyn OrganizationProvider>>,
+    Json(mut payload): Json<RegisterRequest>,
+) -> AuthixResult<(StatusCode, Json<R<u64>>)> {
+    // 邀请注册：邀请码需属于当前租户且与注册方式、标识一致
+    let invitation = match payload.invitation_token.clone() {
+        Some(token) => match org_provider.get_pending_invitation(token).await? {
+            Some(inv) if inv.tenant_id == ctx.tenant.id && inv.invite_type == payload.register_type && inv.identifier == payload.identifier => {
+                payload.invited = true;
+                Some(inv)
+            }
+            _ => return Err(AuthixError::InvalidParameter("org.invitation_not_found".into())),
+        },
+        None => None,
+    };
+    let event = AuditEvent::new(AuditEventType::Register, AuditOutcome::Success, &client)
+        .tenant(ctx.tenant.id)
+        .subject(&payload.identifier);
+    let result = register_service.register(&ctx, &payload, &state).await;
+    metrics::REGISTER_TOTAL.with_label_values(&[metrics::auth_type_label(&payload.register_type, register_service.supports(&payload.register_type)), metrics::outcome(result.is_ok())]).inc();
+    match result {
         Ok(resp) => {
-            let status = if resp.code == 0 {
-                StatusCode::OK
-            } else {
-                StatusCode::from_u16(resp.code as u16).unwrap_or(StatusCode::BAD_REQUEST)
-            };
-            (status, Json(resp))
+            audit.record(event.subject_id(resp.data)).await;
+            if let (Some(inv), Some(user_id)) = (&invitation, resp.data)
+                && let Err(e) = org_provider.accept_invitation(inv, user_id).await
+            {
+                warn!("accept invitation {} for user {} error: {}", inv.id, user_id, e);
+            }
+            Ok((StatusCode::OK, Json(resp)))
+        }
+        Err(e) => {
+            audit.record(AuditEvent { outcome: AuditOutcome::Failure, ..event }.reason(e.to_string())).await;
+            Err(e)
         }
-        Err(e) => (StatusCode::BAD_REQUEST, Json(R::<u64>::error(400, e.to_string()))),
     }
 }
 
-pub async fn send_code(Json(payload): Json<SendCodeRequest>) -> impl IntoResponse {
+pub async fn send_code(
+    CurrentTenant(ctx): CurrentTenant,
+    State(codes): State<Arc<dyn CodeStore>>,
+    State(notify_service): State<Arc<dyn NotifyProvider>>,
+    State(config): State<ConfigHandle>,
+    Json(payload): Json<SendCodeRequest>,
+) -> AuthixResult<(StatusCode, Json<R<String>>)> {
     // 根据 verify_type 校验 identifier
     match payload.verify_type {
         AuthType::Sms => {
             if !is_valid_phone(&payload.identifier) {
-                return (StatusCode::BAD_REQUEST, Json(R::<String>::error(400, "手机号格式不正确".into())));
+                return Err(AuthixError::InvalidParameter("param.phone_invalid".into()));
             }
         }
         AuthType::Email => {
             if !is_valid_email(&payload.identifier) {
-                return (StatusCode::BAD_REQUEST, Json(R::<String>::error(400, "邮箱格式不正确".into())));
+                return Err(AuthixError::InvalidParameter("param.email_invalid".into()));
             }
         }
         _ => {
-            return (StatusCode::BAD_REQUEST, Json(R::<String>::error(400, "不支持的验证类型".into())));
+            return Err(AuthixError::InvalidParameter("auth.unsupported_verify_type".into()));
         }
     }
 
-    match crate::cache::save_verify_code(&payload.identifier).await {
-        Ok(code) => (StatusCode::OK, Json(R::<String>::ok_data(code))),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(R::<String>::error(500, e))),
-    }
+    let config = config.get();
+    let code = crate::cache::save_verify_code(&*codes, ctx.tenant.id, &payload.identifier, config.verify_code.ttl_secs).await?;
+    let branding = &ctx.settings.branding;
+    let content = branding.verify_code_message(&config.templates, &code, config.verify_code.ttl_secs);
+    let sent = match payload.verify_type {
+        AuthType::Sms => notify_service.send_sms(branding, &payload.identifier, &content).await,
+        _ => notify_service.send_email(branding, &payload.identifier, &Msg::new("notify.verify_code_subject").localize(), &content).await,
+    };
+    metrics::VERIFY_CODE_SENT_TOTAL.with_label_values(&[payload.verify_type.as_str(), metrics::outcome(sent.is_ok())]).inc();
+    sent?;
+    Ok((StatusCode::OK, Json(R::<String>::ok())))
 }
 
-pub async fn verify_code(Json(payload): Json<VerifyCodeRequest>) -> impl IntoResponse {
-    match crate::cache::verify_code(&payload.identifier, &payload.credential, payload.verify_type).await {
-        Ok(true) => (StatusCode::OK, Json(R::<String>::ok())),
-        Ok(false) => (StatusCode::UNAUTHORIZED, Json(R::<String>::error(401, "invalid verification code".into()))),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(R::<String>::error(500, e))),
+pub async fn verify_code(
+    CurrentTenant(ctx): CurrentTenant,
+    client: ClientInfo,
+    State(codes): State<Arc<dyn CodeStore>>,
+    State(audit): State<Arc<dyn AuditProvider>>,
+    State(config): State<ConfigHandle>,
+    Json(payload): Json<VerifyCodeRequest>,
+) -> AuthixResult<(StatusCode, Json<R<String>>)> {
+    let event = |outcome| AuditEvent::new(AuditEventType::CodeVerify, outcome, &client)
+        .tenant(ctx.tenant.id)
+        .subject(&payload.identifier);
+    match crate::cache::verify_code(&*codes, ctx.tenant.id, &payload.identifier, &payload.credential, payload.verify_type, config.get().verify_code.ttl_secs).await {
+        Ok(()) => {
+            metrics::VERIFY_CODE_CHECKED_TOTAL.with_label_values(&[metrics::outcome(true)]).inc();
+            audit.record(event(AuditOutcome::Success)).await;
+            Ok((StatusCode::OK, Json(R::<String>::ok())))
+        }
+        Err(e @ (AuthixError::CodeInvalid(_) | AuthixError::CodeExpired(_))) => {
+            metrics::VERIFY_CODE_CHECKED_TOTAL.with_label_values(&[metrics::outcome(false)]).inc();
+            audit.record(event(AuditOutcome::Failure).reason(e.to_string())).await;
+            Err(e)
+        }
+        Err(e) => Err(e),
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn login_handler(
-    Extension(login): Extension<Arc<dyn LoginProvider>>,
-    Extension(user): Extension<Arc<dyn UserProvider>>,
+    CurrentTenant(ctx): CurrentTenant,
+    client: ClientInfo,
+    State(state): State<AppState>,
+    State(login): State<Arc<LoginService>>,
+    State(user): State<Arc<dyn UserProvider>>,
+    State(audit): State<Arc<dyn AuditProvider>>,
+    State(history): State<Arc<dyn LoginHistoryProvider>>,
+    State(notify_service): State<Arc<dyn NotifyProvider>>,
+    State(webhook): State<Arc<dyn WebhookProvider>>,
     Json(payload): Json<LoginRequest>,
-) -> impl IntoResponse {
-    match login.login(&payload, user).await {
-        Ok(result) => (StatusCode::OK, Json(result)),
-        Err(e) => (StatusCode::UNAUTHORIZED, Json(R::<LoginResponse>::error(401, e.to_string()))),
+) -> AuthixResult<(StatusCode, Json<R<LoginResponse>>)> {
+    let event = |outcome| AuditEvent::new(AuditEventType::Login, outcome, &client)
+        .tenant(ctx.tenant.id)
+        .subject(&payload.identifier);
+    let result = login.login(&ctx, &payload, &state).await;
+    metrics::LOGIN_TOTAL.with_label_values(&[metrics::auth_type_label(&payload.login_type, login.supports(&payload.login_type)), metrics::outcome(result.is_ok())]).inc();
+    // 登录历史与新设备提醒异步处理，不影响登录耗时
+    if ctx.settings.login_type_enabled(&payload.login_type) {
+        let attempt = LoginAttempt {
+            login_type: payload.login_type.clone(),
+            identifier: payload.identifier.clone(),
+            client: client.clone(),
+            user_id: result.as_ref().ok().and_then(|r| r.data.as_ref()).map(|r| r.uid),
+            error: result.as_ref().err().map(|e| e.to_string()),
+            locale: i18n::current(),
+        };
+        tokio::spawn(track_login(history, user, notify_service, state.config.clone(), ctx.clone(), attempt));
+    }
+    match result {
+        Ok(result) => {
+            let uid = result.data.as_ref().map(|r| r.uid);
+            let data = json!({ "user_id": uid, "login_type": payload.login_type, "ip": client.ip });
+            if let Err(e) = webhook.publish(ctx.tenant.id, EVENT_USER_LOGIN, data).await {
+                warn!("publish {} for user {:?} error: {}", EVENT_USER_LOGIN, uid, e);
+            }
+            audit.record(event(AuditOutcome::Success).subject_id(uid).actor(uid.unwrap_or_default())).await;
+            Ok((StatusCode::OK, Json(result)))
+        }
+        Err(e) => {
+            audit.record(event(AuditOutcome::Failure).reason(e.to_string())).await;
+            Err(e)
+        }
     }
 }
 
-pub async fn refresh_token(headers: HeaderMap) -> impl IntoResponse {
-    let unauthorized = || (StatusCode::UNAUTHORIZED, Json(R::<LoginResponse>::error(401, "invalid refresh token".to_string())));
+pub async fn refresh_token(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AuthixResult<(StatusCode, Json<R<LoginResponse>>)> {
+    let result = refresh_access_token(&state, headers).await;
+    metrics::TOKEN_REFRESH_TOTAL.with_label_values(&[metrics::outcome(result.is_ok())]).inc();
+    result.map(|resp| (StatusCode::OK, Json(R::ok_data(resp))))
+}
 
-    let auth_header = match headers.get("authorization").and_then(|v| v.to_str().ok()) {
-        Some(v) => v,
-        None => return unauthorized(),
-    };
+async fn refresh_access_token(state: &AppState, headers: HeaderMap) -> AuthixResult<LoginResponse> {
+    let unauthorized = || AuthixError::TokenInvalid("auth.invalid_refresh_token".into());
+
+    let auth_header = headers.get("authorization").and_then(|v| v.to_str().ok()).ok_or_else(unauthorized)?;
     let token = match auth_header.strip_prefix("Bearer ").or_else(|| auth_header.strip_prefix("bearer ")) {
         Some(t) if !t.is_empty() => t,
-        _ => return unauthorized(),
+        _ => return Err(unauthorized()),
     };
 
-    match jwt::verify_refresh_token(token).await {
-        Ok(claims) => match jwt::get_token(&claims.sub, &claims.tenant_id, 1, "access").await {
-            Ok((access_token,exp, _)) => (StatusCode::OK, Json(R::ok_data(LoginResponse { access_token, refresh_token: token.to_string(), exp, iat: claims.iat}))),
-            Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(R::<LoginResponse>::error(500, e.to_string()))),
-        },
-        Err(_) => unauthorized(),
+    let claims = jwt::verify_refresh_token(&state.keys, token).await?;
+    // 强制下线前签发的刷新令牌失效
+    let uid: u64 = claims.sub.parse().map_err(|_| unauthorized())?;
+    if let Some(revoked_at) = state.sessions.get_revoked_at(uid).await?
+        && claims.iat as u64 <= revoked_at
+    {
+        return Err(AuthixError::TokenRevoked("auth.refresh_token_revoked".into()));
     }
+    let tenant_id: u64 = claims.tenant_id.parse().map_err(|_| unauthorized())?;
+    // 禁用、锁定、注销的用户不能续期
+    let user = state.users.get_user_by_id(uid).await?
+        .filter(|u| u.tenant_id == tenant_id)
+        .ok_or_else(unauthorized)?;
+    user.ensure_can_login()?;
+    // 按令牌所属租户的配置签发新的访问令牌
+    let settings = state.tenants.get_tenant_settings(tenant_id).await?;
+    // 已移出组织时不再携带该组织
+    let org_id = match claims.org_id {
+        Some(org_id) if state.orgs.get_membership(org_id, uid).await?.is_some() => Some(org_id),
+        _ => None,
+    };
+    let (access_token, exp, _) = jwt::get_token(state, &claims.sub, &claims.tenant_id, org_id, settings.access_token_exp, "access").await?;
+    Ok(LoginResponse { uid, access_token, refresh_token: token.to_string(), exp, iat: claims.iat })
 }
 
-pub async fn logout_handler(TypedHeader(uid): TypedHeader<UidHeader>) -> impl IntoResponse {
-    let id: u64 = match uid.0.parse() {
-        Ok(v) => v,
-        Err(_) => return (StatusCode::BAD_REQUEST, Json(R::<String>::error(400, "invalid uid".into()))),
-    };
-    match crate::cache::delete_user_access_token(id).await {
-        Ok(_) => (StatusCode::OK, Json(R::<String>::ok())),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(R::<String>::error(500, e))),
+pub async fn logout_handler(
+    client: ClientInfo,
+    State(sessions): State<Arc<dyn SessionStore>>,
+    State(user_provider): State<Arc<dyn UserProvider>>,
+    State(audit): State<Arc<dyn AuditProvider>>,
+    State(webhook): State<Arc<dyn WebhookProvider>>,
+    State(publisher): State<Arc<dyn EventPublisher>>,
+    TypedHeader(uid): TypedHeader<UidHeader>,
+) -> AuthixResult<(StatusCode, Json<R<String>>)> {
+    let id: u64 = uid.0.parse().map_err(|_| AuthixError::InvalidParameter("param.invalid_uid".into()))?;
+    let mut event = AuditEvent::new(AuditEventType::Logout, AuditOutcome::Success, &client).actor(id).subject_id(Some(id));
+    if let Some(user) = user_provider.get_user_by_id(id).await? {
+        // 同时吊销刷新令牌，退出后不能再续期
+        crate::cache::revoke_user_tokens(&*sessions, user.tenant_id, id).await?;
+        event = event.tenant(user.tenant_id);
+        emit(&publisher, user.tenant_id, DomainEvent::UserLoggedOut { user_id: id }).await;
+        let data = json!({ "user_id": id, "reason": "logout" });
+        if let Err(e) = webhook.publish(user.tenant_id, EVENT_SESSION_REVOKED, data).await {
+            warn!("publish {} for user {} error: {}", EVENT_SESSION_REVOKED, id, e);
+        }
     }
-}
\ No newline at end of file
+    audit.record(event).await;
+    Ok((StatusCode::OK, Json(R::<String>::ok())))
+}
@@ -0,0 +1,202 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use axum::async_trait;
+
+use crate::{common::PageResult, errors::AuthixResult};
+
+use super::{now_secs, CodeStore, SessionStore};
+
+#[derive(Default)]
+struct Sessions {
+    /// 用户 ID -> (租户 ID, 会话过期时间（Unix 秒）)，过期时间与 Redis 在线用户 ZSet 的 score 一致。
+    /// 访问令牌仅由客户端持有，服务端不读取，无需保存
+    sessions: HashMap<u64, (u64, u64)>,
+    revoked: HashMap<u64, (u64, Instant)>,
+}
+
+/// 内存实现，用于测试及本地开发，重启后全部会话失效
+#[derive(Default)]
+pub struct InMemorySessionStore {
+    inner: Mutex<Sessions>,
+}
+
+impl InMemorySessionStore {
+    /// 清理过期会话后返回在线用户，按过期时间、用户 ID 升序；`tenant_id` 为空时返回全部租户
+    fn online(&self, tenant_id: Option<u64>) -> Vec<u64> {
+        let now = now_secs();
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        inner.sessions.retain(|_, (_, expire_at)| *expire_at > now);
+        let mut online: Vec<(u64, u64)> = inner
+            .sessions
+            .iter()
+            .filter(|(_, (tenant, _))| tenant_id.is_none_or(|t| t == *tenant))
+            .map(|(id, (_, expire_at))| (*expire_at, *id))
+            .collect();
+        online.sort_unstable();
+        online.into_iter().map(|(_, id)| id).collect()
+    }
+}
+
+#[async_trait]
+impl SessionStore for InMemorySessionStore {
+    async fn save_session(&self, tenant_id: u64, user_id: u64, _access_token: &str, ttl_secs: u64) -> AuthixResult<()> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner()).sessions.insert(user_id, (tenant_id, now_secs() + ttl_secs));
+        Ok(())
+    }
+
+    async fn delete_session(&self, _tenant_id: u64, user_id: u64) -> AuthixResult<()> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner()).sessions.remove(&user_id);
+        Ok(())
+    }
+
+    async fn online_count(&self, tenant_id: Option<u64>) -> AuthixResult<u64> {
+        Ok(self.online(tenant_id).len() as u64)
+    }
+
+    async fn online_user_ids(&self, tenant_id: u64, offset: usize, limit: usize) -> AuthixResult<PageResult<u64>> {
+        let online = self.online(Some(tenant_id));
+        let total = online.len() as u64;
+        let records = online.into_iter().skip(offset).take(limit).collect();
+        Ok(PageResult { total, records })
+    }
+
+    async fn save_revoked_at(&self, user_id: u64, revoked_at: u64, ttl_secs: u64) -> AuthixResult<()> {
+        let expires = Instant::now() + Duration::from_secs(ttl_secs);
+        self.inner.lock().unwrap_or_else(|e| e.into_inner()).revoked.insert(user_id, (revoked_at, expires));
+        Ok(())
+    }
+
+    async fn get_revoked_at(&self, user_id: u64) -> AuthixResult<Option<u64>> {
+        let inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(inner.revoked.get(&user_id).filter(|(_, expires)| *expires > Instant::now()).map(|(at, _)| *at))
+    }
+}
+
+/// 内存实现，用于测试及本地开发，过期键在写入时清理
+#[derive(Default)]
+pub struct InMemoryCodeStore {
+    entries: Mutex<HashMap<String, (String, Instant)>>,
+}
+
+#[async_trait]
+impl CodeStore for InMemoryCodeStore {
+    async fn set(&self, key: &str, value: &str, ttl_secs: u64) -> AuthixResult<()> {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|_, (_, expires)| *expires > now);
+        entries.insert(key.to_owned(), (value.to_owned(), now + Duration::from_secs(ttl_secs)));
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> AuthixResult<Option<String>> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(entries.get(key).filter(|(_, expires)| *expires > Instant::now()).map(|(value, _)| value.clone()))
+    }
+
+    async fn delete(&self, key: &str) -> AuthixResult<()> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).remove(key);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cache, enums::AuthEnum, errors::AuthixError};
+
+    #[tokio::test]
+    async fn online_users_are_scoped_by_tenant() {
+        let sessions = InMemorySessionStore::default();
+        sessions.save_session(1, 10, "a", 60).await.unwrap();
+        sessions.save_session(1, 11, "b", 30).await.unwrap();
+        sessions.save_session(2, 20, "c", 60).await.unwrap();
+
+        assert_eq!(sessions.online_count(Some(1)).await.unwrap(), 2);
+        assert_eq!(sessions.online_count(Some(2)).await.unwrap(), 1);
+        assert_eq!(sessions.online_count(None).await.unwrap(), 3);
+        let page = sessions.online_user_ids(1, 0, 10).await.unwrap();
+        assert_eq!((page.total, page.records), (2, vec![11, 10]));
+
+        sessions.delete_session(1, 11).await.unwrap();
+        assert_eq!(sessions.online_user_ids(1, 0, 10).await.unwrap().records, [10]);
+        assert_eq!(sessions.online_count(None).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn online_users_expire_and_paginate() {
+        let sessions = InMemorySessionStore::default();
+        sessions.save_session(1, 10, "a", 90).await.unwrap();
+        sessions.save_session(1, 11, "b", 30).await.unwrap();
+        sessions.save_session(1, 12, "c", 60).await.unwrap();
+        // 过期时间不晚于当前时间即视为离线
+        sessions.save_session(1, 13, "d", 0).await.unwrap();
+
+        assert_eq!(sessions.online_count(Some(1)).await.unwrap(), 3);
+        let page = sessions.online_user_ids(1, 1, 1).await.unwrap();
+        assert_eq!((page.total, page.records), (3, vec![12]));
+        assert!(sessions.online_user_ids(1, 3, 10).await.unwrap().records.is_empty());
+
+        // 重新登录刷新过期时间，并归属新的租户
+        sessions.save_session(2, 11, "e", 120).await.unwrap();
+        assert_eq!(sessions.online_user_ids(1, 0, 10).await.unwrap().records, [12, 10]);
+        assert_eq!(sessions.online_user_ids(2, 0, 10).await.unwrap().records, [11]);
+    }
+
+    #[tokio::test]
+    async fn revoked_at_expires_with_ttl() {
+        let sessions = InMemorySessionStore::default();
+        sessions.save_revoked_at(1, 100, 60).await.unwrap();
+        sessions.save_revoked_at(2, 200, 0).await.unwrap();
+        assert_eq!(sessions.get_revoked_at(1).await.unwrap(), Some(100));
+        assert_eq!(sessions.get_revoked_at(2).await.unwrap(), None);
+        assert_eq!(sessions.get_revoked_at(3).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn codes_expire_with_ttl() {
+        let codes = InMemoryCodeStore::default();
+        codes.set("a", "1", 60).await.unwrap();
+        codes.set("b", "2", 0).await.unwrap();
+        assert_eq!(codes.get("a").await.unwrap().as_deref(), Some("1"));
+        assert_eq!(codes.get("b").await.unwrap(), None);
+
+        codes.set("a", "3", 1).await.unwrap();
+        assert_eq!(codes.get("a").await.unwrap().as_deref(), Some("3"));
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        assert_eq!(codes.get("a").await.unwrap(), None);
+
+        codes.set("c", "1", 60).await.unwrap();
+        codes.delete("c").await.unwrap();
+        assert_eq!(codes.get("c").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn register_flag_follows_verify_code() {
+        let codes = InMemoryCodeStore::default();
+        let code = cache::save_verify_code(&codes, 1, "a@example.com", 60).await.unwrap();
+
+        let err = cache::verify_code(&codes, 1, "a@example.com", "wrong", AuthEnum::Register, 60).await.unwrap_err();
+        assert!(matches!(err, AuthixError::CodeInvalid(_)));
+        assert!(!cache::has_register_flag(&codes, 1, "a@example.com").await.unwrap());
+
+        cache::verify_code(&codes, 1, "a@example.com", &code, AuthEnum::Register, 60).await.unwrap();
+        assert!(cache::has_register_flag(&codes, 1, "a@example.com").await.unwrap());
+        // 标识按租户区分
+        assert!(!cache::has_register_flag(&codes, 2, "a@example.com").await.unwrap());
+        // 验证码仅能使用一次
+        let err = cache::verify_code(&codes, 1, "a@example.com", &code, AuthEnum::Register, 60).await.unwrap_err();
+        assert!(matches!(err, AuthixError::CodeExpired(_)));
+
+        cache::delete_register_flag(&codes, 1, "a@example.com").await.unwrap();
+        assert!(!cache::has_register_flag(&codes, 1, "a@example.com").await.unwrap());
+
+        // 登录场景不写入注册标识
+        let code = cache::save_verify_code(&codes, 1, "b@example.com", 60).await.unwrap();
+        cache::verify_code(&codes, 1, "b@example.com", &code, AuthEnum::Login, 60).await.unwrap();
+        assert!(!cache::has_register_flag(&codes, 1, "b@example.com").await.unwrap());
+    }
+}
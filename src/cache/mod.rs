@@ -0,0 +1,143 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use axum::async_trait;
+
+use crate::{common::PageResult, enums::AuthEnum, errors::{AuthixError, AuthixResult}, utils};
+
+mod memory;
+mod redis;
+
+pub use memory::{InMemoryCodeStore, InMemorySessionStore};
+pub use redis::{RedisCodeStore, RedisSessionStore};
+
+const VERIFY_CODE_KEY: &str = "user:verify:code";
+const USER_CAN_REGISTER_FLAG_KEY: &str = "user:register:flag";
+const TENANT_SETTINGS_KEY: &str = "tenant:settings";
+/// 租户配置缓存时长
+const TENANT_SETTINGS_SEC_TTL: u64 = 600;
+/// 令牌吊销标记保留时长，需不短于最长的刷新令牌有效期
+const TOKEN_REVOKED_SEC_TTL: u64 = 60 * 60 * 24 * 30;
+
+/// 会话存储：访问令牌、在线用户与令牌吊销时间
+#[async_trait]
+pub trait SessionStore: Send + Sync {
+    /// 保存访问令牌并记录用户在租户内在线，`ttl_secs` 秒后过期
+    async fn save_session(&self, tenant_id: u64, user_id: u64, access_token: &str, ttl_secs: u64) -> AuthixResult<()>;
+    /// 删除访问令牌并移出在线用户
+    async fn delete_session(&self, tenant_id: u64, user_id: u64) -> AuthixResult<()>;
+    /// 在线用户数，不含已过期的会话；`tenant_id` 为空时统计全部租户
+    async fn online_count(&self, tenant_id: Option<u64>) -> AuthixResult<u64>;
+    /// 按会话过期时间升序分页获取租户的在线用户 ID
+    async fn online_user_ids(&self, tenant_id: u64, offset: usize, limit: usize) -> AuthixResult<PageResult<u64>>;
+    /// 记录令牌吊销时间（毫秒）
+    async fn save_revoked_at(&self, user_id: u64, revoked_at: u64, ttl_secs: u64) -> AuthixResult<()>;
+    async fn get_revoked_at(&self, user_id: u64) -> AuthixResult<Option<u64>>;
+}
+
+/// 带过期时间的键值存储，保存验证码、注册标识、一次性令牌与租户配置缓存
+#[async_trait]
+pub trait CodeStore: Send + Sync {
+    async fn set(&self, key: &str, value: &str, ttl_secs: u64) -> AuthixResult<()>;
+    async fn get(&self, key: &str) -> AuthixResult<Option<String>>;
+    async fn delete(&self, key: &str) -> AuthixResult<()>;
+}
+
+/// 当前 Unix 时间（秒）
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+/// 保存访问令牌并记录用户在线，有效期为毫秒
+pub async fn save_user_access_token(
+    sessions: &dyn SessionStore,
+    tenant_id: u64,
+    user_id: u64,
+    access_token: &str,
+    ttl_millis: usize,
+) -> AuthixResult<()> {
+    let ttl_secs = ttl_millis.saturating_div(1000) as u64;
+    sessions.save_session(tenant_id, user_id, access_token, ttl_secs).await
+}
+
+/// 吊销用户此前签发的全部令牌（强制下线）
+/// 记录吊销时间（毫秒），签发时间早于该时间的刷新令牌失效
+pub async fn revoke_user_tokens(sessions: &dyn SessionStore, tenant_id: u64, user_id: u64) -> AuthixResult<()> {
+    sessions.delete_session(tenant_id, user_id).await?;
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+
+    sessions.save_revoked_at(user_id, now, TOKEN_REVOKED_SEC_TTL).await
+}
+
+/// 分页获取租户的在线用户 ID
+pub async fn get_online_user_ids_paginated(
+    sessions: &dyn SessionStore,
+    tenant_id: u64,
+    page: isize,
+    page_size: isize,
+) -> AuthixResult<PageResult<u64>> {
+    // 计算分页起点
+    let offset = (page.saturating_sub(1)) * page_size;
+    sessions.online_user_ids(tenant_id, offset.max(0) as usize, page_size.max(0) as usize).await
+}
+
+/// 注册标识 key，按租户隔离
+fn register_flag_key(tenant_id: u64, identifier: &str) -> String {
+    format!("{}:{}:{}", USER_CAN_REGISTER_FLAG_KEY, tenant_id, identifier)
+}
+
+/// 是否已通过注册验证码校验（有效期与验证码相同）
+pub async fn has_register_flag(codes: &dyn CodeStore, tenant_id: u64, identifier: &str) -> AuthixResult<bool> {
+    Ok(codes.get(&register_flag_key(tenant_id, identifier)).await?.is_some())
+}
+
+/// 注册完成后删除注册标识
+pub async fn delete_register_flag(codes: &dyn CodeStore, tenant_id: u64, identifier: &str) -> AuthixResult<()> {
+    codes.delete(&register_flag_key(tenant_id, identifier)).await
+}
+
+/// 保存验证码，有效期 `ttl_secs` 取自配置 `verify_code.ttl_secs`
+pub async fn save_verify_code(codes: &dyn CodeStore, tenant_id: u64, identifier: &str, ttl_secs: u64) -> AuthixResult<String> {
+    let key = format!("{}:{}:{}", VERIFY_CODE_KEY, tenant_id, identifier);
+    let code = utils::uuid::generate_verify_code();
+    codes.set(&key, &code, ttl_secs).await?;
+
+    Ok(code)
+}
+
+/// 校验验证码，验证码不存在返回 `CodeExpired`，不一致返回 `CodeInvalid`；注册场景的注册标识有效期为 `ttl_secs`
+pub async fn verify_code(codes: &dyn CodeStore, tenant_id: u64, identifier: &str, code: &str, scene: AuthEnum, ttl_secs: u64) -> AuthixResult<()> {
+    let key = format!("{}:{}:{}", VERIFY_CODE_KEY, tenant_id, identifier);
+    let stored_code = codes.get(&key).await?;
+
+    match stored_code {
+        Some(stored) if stored == code => {
+            // 验证成功后删除验证码
+            codes.delete(&key).await?;
+            if let AuthEnum::Register = scene {
+                codes.set(&register_flag_key(tenant_id, identifier), "1", ttl_secs).await?;
+            }
+            Ok(())
+        }
+        Some(_) => Err(AuthixError::CodeInvalid("verify_code.invalid".into())),
+        None => Err(AuthixError::CodeExpired("verify_code.expired".into())),
+    }
+}
+
+/// 读取租户配置缓存（JSON）
+pub async fn get_tenant_settings(codes: &dyn CodeStore, tenant_id: u64) -> AuthixResult<Option<String>> {
+    codes.get(&format!("{}:{}", TENANT_SETTINGS_KEY, tenant_id)).await
+}
+
+/// 写入租户配置缓存（JSON）
+pub async fn save_tenant_settings(codes: &dyn CodeStore, tenant_id: u64, settings: &str) -> AuthixResult<()> {
+    codes.set(&format!("{}:{}", TENANT_SETTINGS_KEY, tenant_id), settings, TENANT_SETTINGS_SEC_TTL).await
+}
+
+/// 删除租户配置缓存（配置更新后调用）
+pub async fn delete_tenant_settings(codes: &dyn CodeStore, tenant_id: u64) -> AuthixResult<()> {
+    codes.delete(&format!("{}:{}", TENANT_SETTINGS_KEY, tenant_id)).await
+}
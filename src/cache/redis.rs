@@ -0,0 +1,129 @@
+use axum::async_trait;
+use deadpool_redis::{redis::AsyncCommands, Pool};
+
+use crate::{common::PageResult, errors::AuthixResult};
+
+use super::{now_secs, CodeStore, SessionStore};
+
+const TOKEN_CACHE_KEY: &str = "user:session:token";
+const ONLINE_USERS_KEY: &str = "user:online";
+const TOKEN_REVOKED_KEY: &str = "user:session:revoked";
+
+/// 在线用户 ZSet 键，按租户区分；不指定租户时为全部租户
+fn online_key(tenant_id: Option<u64>) -> String {
+    match tenant_id {
+        Some(tenant_id) => format!("{}:{}", ONLINE_USERS_KEY, tenant_id),
+        None => ONLINE_USERS_KEY.to_string(),
+    }
+}
+
+/// Redis 实现，多实例共享会话
+pub struct RedisSessionStore {
+    pool: Pool,
+}
+
+impl RedisSessionStore {
+    pub fn new(pool: Pool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl SessionStore for RedisSessionStore {
+    async fn save_session(&self, tenant_id: u64, user_id: u64, access_token: &str, ttl_secs: u64) -> AuthixResult<()> {
+        let mut conn = self.pool.get().await?;
+        let expire_at = now_secs() + ttl_secs;
+
+        // 用 zset 存储：member=user_id，score=过期时间戳；全局与租户各一份
+        let _: () = conn.zadd(ONLINE_USERS_KEY, user_id, expire_at as isize).await?;
+        let _: () = conn.zadd(online_key(Some(tenant_id)), user_id, expire_at as isize).await?;
+
+        let user_token_key = format!("{}:{}", TOKEN_CACHE_KEY, user_id);
+        let _: () = conn.set_ex(&user_token_key, access_token, ttl_secs).await?;
+
+        Ok(())
+    }
+
+    async fn delete_session(&self, tenant_id: u64, user_id: u64) -> AuthixResult<()> {
+        let mut conn = self.pool.get().await?;
+        // 删除单用户 token 键
+        let _: () = conn.del(format!("{}:{}", TOKEN_CACHE_KEY, user_id)).await?;
+        // 同步从在线统计 ZSet 中移除该用户
+        let _: () = conn.zrem(ONLINE_USERS_KEY, user_id).await?;
+        let _: () = conn.zrem(online_key(Some(tenant_id)), user_id).await?;
+
+        Ok(())
+    }
+
+    async fn online_count(&self, tenant_id: Option<u64>) -> AuthixResult<u64> {
+        let mut conn = self.pool.get().await?;
+        let now = now_secs() as isize;
+        let key = online_key(tenant_id);
+
+        // 先清理掉已过期的用户
+        let _: () = conn.zrembyscore(&key, 0, now).await?;
+
+        // 获取剩余的在线人数
+        let count: u64 = conn.zcount(&key, now, "+inf").await?;
+
+        Ok(count)
+    }
+
+    async fn online_user_ids(&self, tenant_id: u64, offset: usize, limit: usize) -> AuthixResult<PageResult<u64>> {
+        let mut conn = self.pool.get().await?;
+        let now = now_secs() as isize;
+        let key = online_key(Some(tenant_id));
+
+        // 清理过期用户
+        let _: () = conn.zrembyscore(&key, 0, now).await?;
+
+        let total: u64 = conn.zcount(&key, now, "+inf").await?;
+        let ids: Vec<u64> = conn.zrangebyscore_limit(&key, now, "+inf", offset as isize, limit as isize).await?;
+
+        Ok(PageResult { total, records: ids })
+    }
+
+    async fn save_revoked_at(&self, user_id: u64, revoked_at: u64, ttl_secs: u64) -> AuthixResult<()> {
+        let mut conn = self.pool.get().await?;
+        let _: () = conn.set_ex(format!("{}:{}", TOKEN_REVOKED_KEY, user_id), revoked_at, ttl_secs).await?;
+        Ok(())
+    }
+
+    async fn get_revoked_at(&self, user_id: u64) -> AuthixResult<Option<u64>> {
+        let mut conn = self.pool.get().await?;
+        let revoked_at: Option<u64> = conn.get(format!("{}:{}", TOKEN_REVOKED_KEY, user_id)).await?;
+        Ok(revoked_at)
+    }
+}
+
+/// Redis 实现，键值以 `SET EX` 写入
+pub struct RedisCodeStore {
+    pool: Pool,
+}
+
+impl RedisCodeStore {
+    pub fn new(pool: Pool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl CodeStore for RedisCodeStore {
+    async fn set(&self, key: &str, value: &str, ttl_secs: u64) -> AuthixResult<()> {
+        let mut conn = self.pool.get().await?;
+        let _: () = conn.set_ex(key, value, ttl_secs).await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> AuthixResult<Option<String>> {
+        let mut conn = self.pool.get().await?;
+        let value: Option<String> = conn.get(key).await?;
+        Ok(value)
+    }
+
+    async fn delete(&self, key: &str) -> AuthixResult<()> {
+        let mut conn = self.pool.get().await?;
+        let _: () = conn.del(key).await?;
+        Ok(())
+    }
+}
@@ -1,12 +1,17 @@
+use std::{convert::Infallible, net::SocketAddr};
+
 use headers::Header;
 use serde::{Deserialize, Serialize};
-use axum::http::{HeaderName, HeaderValue};
+use axum::{async_trait, extract::{ConnectInfo, FromRequestParts}, http::{header::USER_AGENT, request::Parts, HeaderName, HeaderValue}};
 
 #[derive(Debug, Serialize)]
 pub struct R<T> {
     pub success: bool,
     pub code: i32,
     pub message: Option<String>,
+    /// 稳定的错误码，见 `AuthixError::code`，成功时不返回
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<&'static str>,
     pub data: Option<T>
 }
 
@@ -22,6 +27,12 @@ pub struct PageResult<T> {
     pub records: Vec<T>,
 }
 
+/// 游标分页结果，`next_cursor` 为空表示没有更多数据
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CursorPage<T> {
+    pub records: Vec<T>,
+    pub next_cursor: Option<String>,
+}
 
 impl<T> R<T> {
     pub fn ok() -> Self {
@@ -29,6 +40,7 @@ impl<T> R<T> {
             success: true,
             code: 200,
             message: Some("ok".to_owned()),
+            error_code: None,
             data: None
         }
     }
@@ -38,38 +50,30 @@ impl<T> R<T> {
             success: true,
             code: 200,
             message: Some("ok".to_owned()),
+            error_code: None,
             data: Some(data)
         }
     }
     
-    #[allow(dead_code)]
     pub fn ok_message(msg: String) -> Self {
         Self {
             success: true,
             code: 200,
             message: Some(msg),
+            error_code: None,
             data: None
         }
     }
 
-    #[allow(dead_code)]
     pub fn ok_data_message(data: T, msg: String) -> Self {
         Self {
             success: true,
             code: 200,
             message: Some(msg),
+            error_code: None,
             data: Some(data)
         }
     }
-
-    pub fn error(code: i32, msg: String) -> Self {
-        Self {
-            success: false,
-            code,
-            message: Some(msg),
-            data: None
-        }
-    }
 }
 
 pub struct TenantIdHeader(pub String);
@@ -121,4 +125,35 @@ impl Header for UidHeader {
     fn encode<E: Extend<HeaderValue>>(&self, values: &mut E) {
         values.extend(std::iter::once(HeaderValue::from_str(&self.0).unwrap()));
     }
-}
\ No newline at end of file
+}
+
+/// 客户端设备标识请求头，由客户端生成并持久化
+const DEVICE_ID_HEADER: &str = "x-device-id";
+
+/// 客户端信息：IP 优先取 `x-forwarded-for` 首个地址，其次 `x-real-ip`，最后为连接地址
+#[derive(Debug, Clone, Default)]
+pub struct ClientInfo {
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub device_id: Option<String>,
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for ClientInfo
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let header = |name: &str| parts.headers.get(name).and_then(|v| v.to_str().ok()).map(str::trim).filter(|v| !v.is_empty());
+        let ip = header("x-forwarded-for")
+            .and_then(|v| v.split(',').next())
+            .map(|v| v.trim().to_owned())
+            .or_else(|| header("x-real-ip").map(str::to_owned))
+            .or_else(|| parts.extensions.get::<ConnectInfo<SocketAddr>>().map(|c| c.0.ip().to_string()));
+        let user_agent = header(USER_AGENT.as_str()).map(|v| v.chars().take(512).collect());
+        let device_id = header(DEVICE_ID_HEADER).map(|v| v.chars().take(128).collect());
+        Ok(ClientInfo { ip, user_agent, device_id })
+    }
+}
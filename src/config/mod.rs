@@ -0,0 +1,542 @@
+use std::{env, path::PathBuf, sync::{Arc, RwLock}};
+
+use ::config::{Config, Environment, File};
+use serde::{Deserialize, Serialize};
+
+use crate::{i18n::Locale, organization::is_valid_role, storage::Backend, tenant::settings::PasswordPolicy, utils::regex::is_valid_auth_type};
+
+pub use ::config::ConfigError;
+
+pub mod reload;
+
+/// 默认配置文件名，不带扩展名时自动查找 `authix.toml` / `authix.yaml` 等
+const DEFAULT_CONFIG_FILE: &str = "authix";
+/// 环境变量覆盖前缀，层级以 `__` 分隔，如 `AUTHIX_DATABASE__MAX_CONNECTIONS=20`
+const ENV_PREFIX: &str = "AUTHIX";
+/// 令牌吊销标记的保留时长（毫秒），刷新令牌有效期不能超过该值
+pub(crate) const MAX_REFRESH_TOKEN_EXP: usize = 1000 * 60 * 60 * 24 * 30;
+/// 内置的认证方式，自定义方式由宿主应用注册
+const BUILTIN_AUTH_TYPES: [&str; 3] = ["password", "sms", "email"];
+
+/// 兼容早期版本的环境变量，优先级高于配置文件
+const LEGACY_ENV_KEYS: [(&str, &str); 15] = [
+    ("SERVER_ADDR", "server.addr"),
+    ("METRICS_ADDR", "server.metrics_addr"),
+    ("ADMIN_API_KEY", "server.admin_api_key"),
+    ("DATABASE_URL", "database.url"),
+    ("REDIS_URL", "redis.url"),
+    ("JWT_DECODING_KEY", "token.secret"),
+    ("VERIFY_CODE_SEC_TTL", "verify_code.ttl_secs"),
+    ("USER_DELETE_RETENTION_DAYS", "user.delete_retention_days"),
+    ("EVENT_PUBLISHER", "events.publisher"),
+    ("EVENT_STREAM_KEY", "events.stream_key"),
+    ("HEALTH_CHECK_TIMEOUT_MS", "health.check_timeout_ms"),
+    ("STARTUP_DEPENDENCY_MODE", "health.startup_mode"),
+    ("STARTUP_WAIT_TIMEOUT_SECS", "health.startup_wait_secs"),
+    ("SHUTDOWN_READINESS_DELAY_SECS", "shutdown.readiness_delay_secs"),
+    ("SHUTDOWN_DRAIN_TIMEOUT_SECS", "shutdown.drain_timeout_secs"),
+];
+
+/// 服务配置，加载顺序（后者覆盖前者）：默认值 -> 配置文件 -> `AUTHIX_*` 环境变量 -> 兼容环境变量
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AuthixConfig {
+    pub server: ServerConfig,
+    pub database: DatabaseConfig,
+    pub redis: RedisConfig,
+    pub cache: CacheConfig,
+    pub token: TokenConfig,
+    pub verify_code: VerifyCodeConfig,
+    /// 租户未配置时的默认密码策略
+    pub password: PasswordPolicy,
+    pub providers: ProvidersConfig,
+    pub ldap: LdapConfig,
+    pub templates: TemplatesConfig,
+    pub events: EventsConfig,
+    pub health: HealthConfig,
+    pub shutdown: ShutdownConfig,
+    pub user: UserConfig,
+    pub webhook: WebhookConfig,
+    pub i18n: I18nConfig,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    pub addr: String,
+    /// Prometheus 指标监听地址，独立于业务端口
+    pub metrics_addr: String,
+    /// 管理端接口密钥，未配置时管理端接口全部拒绝
+    pub admin_api_key: Option<String>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self { addr: "127.0.0.1:30000".into(), metrics_addr: "127.0.0.1:30001".into(), admin_api_key: None }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct DatabaseConfig {
+    /// 必填，不提供默认值，协议决定存储后端：mysql://、postgres://、sqlite:
+    pub url: String,
+    pub max_connections: u32,
+    /// 启动时自动执行未执行的迁移，关闭时仅检查结构版本
+    pub migrate_on_startup: bool,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self { url: String::new(), max_connections: 10, migrate_on_startup: false }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RedisConfig {
+    pub url: String,
+    /// 连接池大小，未配置时使用 deadpool 默认值
+    pub max_connections: Option<usize>,
+}
+
+impl Default for RedisConfig {
+    fn default() -> Self {
+        Self { url: "redis://127.0.0.1:6379/0".into(), max_connections: None }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheBackendKind {
+    #[default]
+    Redis,
+    /// 进程内存储，重启丢失且不能多实例共享，仅用于本地开发与测试
+    Memory,
+}
+
+/// 会话、在线用户、验证码等缓存数据的存储位置
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    pub backend: CacheBackendKind,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TokenConfig {
+    /// JWT 签名密钥，必填
+    pub secret: String,
+    /// 默认访问令牌有效期（毫秒），租户可覆盖
+    pub access_token_exp: usize,
+    /// 默认刷新令牌有效期（毫秒），租户可覆盖
+    pub refresh_token_exp: usize,
+}
+
+impl Default for TokenConfig {
+    fn default() -> Self {
+        Self { secret: String::new(), access_token_exp: 1000 * 60 * 5, refresh_token_exp: 1000 * 60 * 60 * 24 * 7 }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct VerifyCodeConfig {
+    /// 验证码有效时长（秒）
+    pub ttl_secs: u64,
+}
+
+impl Default for VerifyCodeConfig {
+    fn default() -> Self {
+        Self { ttl_secs: 300 }
+    }
+}
+
+/// 全局启用的登录/注册方式，未在此列出的方式对所有租户关闭；同时作为租户未配置时的默认值
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ProvidersConfig {
+    pub login_types: Vec<String>,
+    pub register_types: Vec<String>,
+    /// 租户未配置时是否允许自助注册
+    pub allow_register: bool,
+}
+
+impl ProvidersConfig {
+    pub fn login_type_enabled(&self, login_type: &str) -> bool {
+        self.login_types.iter().any(|t| t == login_type)
+    }
+
+    pub fn register_type_enabled(&self, register_type: &str) -> bool {
+        self.register_types.iter().any(|t| t == register_type)
+    }
+}
+
+/// LDAP / AD 登录（登录方式 `ldap`），先以服务账号查找用户条目，再以用户 DN 与密码绑定
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct LdapConfig {
+    /// 如 `ldap://ad.example.com:389`、`ldaps://ad.example.com:636`，为空时 `ldap` 登录方式不可用
+    pub url: String,
+    pub starttls: bool,
+    /// 跳过证书校验，仅用于测试环境
+    pub tls_skip_verify: bool,
+    /// 查找用户的服务账号，为空时匿名查找
+    pub bind_dn: String,
+    pub bind_password: String,
+    pub base_dn: String,
+    /// 用户过滤条件，`{username}` 替换为转义后的登录标识
+    pub user_filter: String,
+    pub attributes: LdapAttributes,
+    /// 首次登录时自动创建本地用户，关闭时只允许已关联目录账号的本地用户登录
+    pub auto_create_user: bool,
+    /// 首次登录时按用户名匹配已有本地用户并关联，仅在目录与本地用户名一一对应时开启，
+    /// 否则目录账号可接管同名本地账号
+    pub match_local_username: bool,
+    /// 组到组织角色的映射，每次登录时同步，为空时不同步
+    pub group_roles: Vec<LdapGroupRole>,
+    pub timeout_secs: u64,
+}
+
+impl Default for LdapConfig {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            starttls: false,
+            tls_skip_verify: false,
+            bind_dn: String::new(),
+            bind_password: String::new(),
+            base_dn: String::new(),
+            user_filter: "(&(objectClass=person)(sAMAccountName={username}))".into(),
+            attributes: LdapAttributes::default(),
+            auto_create_user: true,
+            match_local_username: false,
+            group_roles: Vec::new(),
+            timeout_secs: 5,
+        }
+    }
+}
+
+impl LdapConfig {
+    pub fn enabled(&self) -> bool {
+        !self.url.is_empty()
+    }
+}
+
+/// 目录属性到本地用户字段的映射
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct LdapAttributes {
+    pub username: String,
+    pub email: String,
+    pub phone: String,
+    /// 用户所属组的 DN 列表
+    pub groups: String,
+}
+
+impl Default for LdapAttributes {
+    fn default() -> Self {
+        Self { username: "sAMAccountName".into(), email: "mail".into(), phone: "telephoneNumber".into(), groups: "memberOf".into() }
+    }
+}
+
+/// 组 DN 命中时在组织中授予的角色，同一组织命中多条时取第一条
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LdapGroupRole {
+    pub group: String,
+    pub org_id: u64,
+    pub role: String,
+}
+
+/// 通知模板，`{signature}` 为租户短信签名或显示名称
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TemplatesConfig {
+    /// 验证码通知，可用 `{code}`、`{minutes}`
+    pub verify_code: String,
+    /// 组织邀请通知，可用 `{org_name}`、`{token}`、`{days}`
+    pub invitation: String,
+    /// 新设备登录提醒，可用 `{login_at}`、`{ip}`
+    pub new_device: String,
+}
+
+impl Default for TemplatesConfig {
+    fn default() -> Self {
+        Self {
+            verify_code: "【{signature}】您的验证码为 {code}，{minutes} 分钟内有效，请勿泄露给他人。".into(),
+            invitation: "【{signature}】您被邀请加入组织「{org_name}」，邀请码 {token}，{days} 天内有效。".into(),
+            new_device: "【{signature}】您的账号于 {login_at} 在新设备登录（IP: {ip}），如非本人操作请立即修改密码。".into(),
+        }
+    }
+}
+
+impl Default for ProvidersConfig {
+    fn default() -> Self {
+        Self {
+            login_types: BUILTIN_AUTH_TYPES.iter().map(|t| t.to_string()).collect(),
+            register_types: BUILTIN_AUTH_TYPES.iter().map(|t| t.to_string()).collect(),
+            allow_register: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventPublisherKind {
+    #[default]
+    Redis,
+    Memory,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct EventsConfig {
+    pub publisher: EventPublisherKind,
+    pub stream_key: String,
+}
+
+impl Default for EventsConfig {
+    fn default() -> Self {
+        Self { publisher: EventPublisherKind::Redis, stream_key: "authix:events".into() }
+    }
+}
+
+/// 启动时依赖检查方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StartupMode {
+    /// 不检查，首次使用时再连接
+    #[default]
+    Lazy,
+    /// 检查一次，不可用立即退出
+    FailFast,
+    /// 等待依赖可用，超时退出
+    Wait,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct HealthConfig {
+    pub check_timeout_ms: u64,
+    pub startup_mode: StartupMode,
+    pub startup_wait_secs: u64,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self { check_timeout_ms: 1000, startup_mode: StartupMode::Lazy, startup_wait_secs: 60 }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ShutdownConfig {
+    /// 置为未就绪后等待负载均衡摘除流量的时间
+    pub readiness_delay_secs: u64,
+    /// 处理中请求的最长等待时间
+    pub drain_timeout_secs: u64,
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self { readiness_delay_secs: 5, drain_timeout_secs: 30 }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct UserConfig {
+    /// 注销用户保留天数，超过后物理删除
+    pub delete_retention_days: u32,
+    pub store: UserStoreKind,
+}
+
+impl Default for UserConfig {
+    fn default() -> Self {
+        Self { delete_retention_days: 30, store: UserStoreKind::Database }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct WebhookConfig {
+    /// 已完成投递的发件箱事件与投递记录保留天数，超过后删除
+    pub retention_days: u32,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self { retention_days: 14 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UserStoreKind {
+    #[default]
+    Database,
+    /// 进程内存储，重启丢失，仅用于本地开发与测试
+    Memory,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct I18nConfig {
+    /// 请求未指定语言且租户未配置时使用的语言，支持 `zh-CN`、`en-US`
+    pub default_locale: String,
+}
+
+impl Default for I18nConfig {
+    fn default() -> Self {
+        Self { default_locale: Locale::ZhCn.tag().into() }
+    }
+}
+
+impl AuthixConfig {
+    /// 加载配置。配置文件路径由 `AUTHIX_CONFIG` 指定，指定后文件必须存在；
+    /// 未指定时查找当前目录下的 `authix.{toml,yaml,...}`，不存在则跳过
+    pub fn load() -> Result<Self, ConfigError> {
+        let file = match env::var("AUTHIX_CONFIG") {
+            Ok(path) => File::with_name(&path).required(true),
+            Err(_) => File::with_name(DEFAULT_CONFIG_FILE).required(false),
+        };
+        let mut builder = Config::builder().add_source(file).add_source(
+            Environment::with_prefix(ENV_PREFIX)
+                .prefix_separator("_")
+                .separator("__")
+                .list_separator(",")
+                .with_list_parse_key("providers.login_types")
+                .with_list_parse_key("providers.register_types")
+                .try_parsing(true),
+        );
+        for (name, key) in LEGACY_ENV_KEYS {
+            builder = builder.set_override_option(key, env::var(name).ok())?;
+        }
+        let config: AuthixConfig = builder.build()?.try_deserialize()?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// 缓存或事件发布使用 Redis 时才需要连接 Redis
+    pub fn uses_redis(&self) -> bool {
+        self.cache.backend == CacheBackendKind::Redis || self.events.publisher == EventPublisherKind::Redis
+    }
+
+    /// 校验配置，汇总全部错误一次性返回
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut errors = Vec::new();
+        for (key, addr) in [("server.addr", &self.server.addr), ("server.metrics_addr", &self.server.metrics_addr)] {
+            if !addr.rsplit_once(':').is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok()) {
+                errors.push(format!("{} must be host:port like 127.0.0.1:30000, got {:?}", key, addr));
+            }
+        }
+        if self.database.url.is_empty() {
+            errors.push("database.url (DATABASE_URL) is required".into());
+        } else if Backend::from_url(&self.database.url).is_none() {
+            errors.push("database.url must start with mysql://, postgres:// or sqlite:".into());
+        }
+        if self.database.max_connections == 0 {
+            errors.push("database.max_connections must be greater than 0".into());
+        }
+        if !self.redis.url.starts_with("redis://") && !self.redis.url.starts_with("rediss://") {
+            errors.push("redis.url must start with redis:// or rediss://".into());
+        }
+        if self.redis.max_connections == Some(0) {
+            errors.push("redis.max_connections must be greater than 0".into());
+        }
+        if self.token.secret.is_empty() {
+            errors.push("token.secret (JWT_DECODING_KEY) is required".into());
+        }
+        if self.token.access_token_exp == 0 || self.token.access_token_exp > self.token.refresh_token_exp {
+            errors.push("token.access_token_exp must satisfy 0 < access_token_exp <= refresh_token_exp".into());
+        }
+        if self.token.refresh_token_exp > MAX_REFRESH_TOKEN_EXP {
+            errors.push(format!("token.refresh_token_exp must not exceed {}ms (30 days)", MAX_REFRESH_TOKEN_EXP));
+        }
+        if self.verify_code.ttl_secs == 0 {
+            errors.push("verify_code.ttl_secs must be greater than 0".into());
+        }
+        if let Err(e) = self.password.validate() {
+            errors.push(format!("password: {}", e));
+        }
+        for t in self.providers.login_types.iter().chain(self.providers.register_types.iter()) {
+            if !is_valid_auth_type(t) {
+                errors.push(format!("providers: invalid auth type {:?}, expected lowercase letters, digits, _ or -", t));
+            }
+        }
+        if self.ldap.enabled() {
+            if !self.ldap.url.starts_with("ldap://") && !self.ldap.url.starts_with("ldaps://") {
+                errors.push("ldap.url must start with ldap:// or ldaps://".into());
+            }
+            if self.ldap.base_dn.is_empty() {
+                errors.push("ldap.base_dn is required when ldap.url is set".into());
+            }
+            if !self.ldap.user_filter.contains("{username}") {
+                errors.push("ldap.user_filter must contain {username}".into());
+            }
+            if self.ldap.timeout_secs == 0 {
+                errors.push("ldap.timeout_secs must be greater than 0".into());
+            }
+            for rule in &self.ldap.group_roles {
+                if !is_valid_role(&rule.role) {
+                    errors.push(format!("ldap.group_roles: invalid role {:?} for group {}", rule.role, rule.group));
+                }
+            }
+        } else if self.providers.login_type_enabled("ldap") {
+            errors.push("providers.login_types enables ldap but ldap.url is not set".into());
+        }
+        for (key, template, required) in [
+            ("templates.verify_code", &self.templates.verify_code, "{code}"),
+            ("templates.invitation", &self.templates.invitation, "{token}"),
+            ("templates.new_device", &self.templates.new_device, "{ip}"),
+        ] {
+            if !template.contains(required) {
+                errors.push(format!("{} must contain {}", key, required));
+            }
+        }
+        if self.events.stream_key.is_empty() {
+            errors.push("events.stream_key must not be empty".into());
+        }
+        if self.health.check_timeout_ms == 0 {
+            errors.push("health.check_timeout_ms must be greater than 0".into());
+        }
+        if Locale::parse(&self.i18n.default_locale).is_none() {
+            errors.push(format!("i18n.default_locale must be one of zh-CN, en-US, got {:?}", self.i18n.default_locale));
+        }
+        if errors.is_empty() { Ok(()) } else { Err(ConfigError::Message(errors.join("; "))) }
+    }
+}
+
+/// 实际使用的配置文件路径，未找到时返回 `None`
+pub fn config_file_path() -> Option<PathBuf> {
+    let base = env::var("AUTHIX_CONFIG").unwrap_or_else(|_| DEFAULT_CONFIG_FILE.to_owned());
+    ["", ".toml", ".yaml", ".yml", ".json"]
+        .iter()
+        .map(|ext| PathBuf::from(format!("{}{}", base, ext)))
+        .find(|p| p.is_file())
+}
+
+/// 可热加载的配置，克隆后共享同一份快照，热加载时整体替换
+#[derive(Clone)]
+pub struct ConfigHandle(Arc<RwLock<Arc<AuthixConfig>>>);
+
+impl ConfigHandle {
+    pub fn new(config: AuthixConfig) -> Self {
+        Self(Arc::new(RwLock::new(Arc::new(config))))
+    }
+
+    /// 当前配置快照。同一次请求内应只取一次，保证读取到的配置一致
+    pub fn get(&self) -> Arc<AuthixConfig> {
+        self.0.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// 原子替换配置快照，已持有旧快照的请求不受影响
+    pub(crate) fn replace(&self, config: AuthixConfig) {
+        *self.0.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(config);
+    }
+}
+
+/// 启动时加载并校验配置
+pub fn init() -> Result<ConfigHandle, ConfigError> {
+    AuthixConfig::load().map(ConfigHandle::new)
+}
@@ -0,0 +1,143 @@
+use std::{collections::BTreeMap, fs, time::{Duration, SystemTime}};
+
+use ::config::ConfigError;
+use serde_json::Value;
+use tokio::{sync::watch, task::JoinHandle};
+use tracing::{error, info, warn};
+
+use crate::config::{self, AuthixConfig, ConfigHandle};
+
+/// 配置文件变更检查间隔
+const WATCH_INTERVAL_SECS: u64 = 5;
+/// 启动后不可变更的配置段或配置项，修改后需重启，热加载时沿用旧值
+const RESTART_REQUIRED: [&str; 6] = ["server", "database", "redis", "cache", "events", "user.store"];
+/// 敏感配置，热加载时沿用旧值，日志中不输出取值
+const SECRETS: [&str; 3] = ["token.secret", "server.admin_api_key", "database.url"];
+/// 可热加载但日志中不输出取值的配置
+const REDACTED: [&str; 1] = ["ldap.bind_password"];
+
+fn is_pinned(key: &str) -> bool {
+    SECRETS.contains(&key) || RESTART_REQUIRED.iter().any(|p| key == *p || key.strip_prefix(p).is_some_and(|rest| rest.starts_with('.')))
+}
+
+/// 将配置展开为 `a.b.c -> 值` 的形式，数组作为整体比较
+fn flatten(config: &AuthixConfig) -> BTreeMap<String, Value> {
+    fn walk(prefix: &str, value: Value, out: &mut BTreeMap<String, Value>) {
+        match value {
+            Value::Object(map) => {
+                for (k, v) in map {
+                    let key = if prefix.is_empty() { k } else { format!("{}.{}", prefix, k) };
+                    walk(&key, v, out);
+                }
+            }
+            other => {
+                out.insert(prefix.to_owned(), other);
+            }
+        }
+    }
+    let mut out = BTreeMap::new();
+    walk("", serde_json::to_value(config).unwrap_or_default(), &mut out);
+    out
+}
+
+fn display(key: &str, value: Option<&Value>) -> String {
+    match value {
+        _ if SECRETS.contains(&key) || REDACTED.contains(&key) => "***".to_owned(),
+        Some(v) => v.to_string(),
+        None => "null".to_owned(),
+    }
+}
+
+/// 重新加载配置，`check` 为配置自身校验之外的检查（如启用的认证方式已注册 Provider）。
+/// 校验失败时保留当前配置并返回错误；成功时返回变更项
+pub fn reload(handle: &ConfigHandle, check: &(dyn Fn(&AuthixConfig) -> Result<(), String> + Send + Sync)) -> Result<Vec<String>, ConfigError> {
+    let current = handle.get();
+    let mut next = AuthixConfig::load()?;
+    check(&next).map_err(ConfigError::Message)?;
+    let before = flatten(&current);
+    for (key, value) in flatten(&next) {
+        if is_pinned(&key) && before.get(&key) != Some(&value) {
+            warn!("config {} changed, restart required to take effect", key);
+        }
+    }
+    next.server = current.server.clone();
+    next.database = current.database.clone();
+    next.redis = current.redis.clone();
+    next.cache = current.cache.clone();
+    next.events = current.events.clone();
+    next.user.store = current.user.store;
+    next.token.secret = current.token.secret.clone();
+
+    let after = flatten(&next);
+    let changes: Vec<String> = after
+        .iter()
+        .filter(|(key, value)| before.get(*key) != Some(*value))
+        .map(|(key, value)| format!("{}: {} -> {}", key, display(key, before.get(key)), display(key, Some(value))))
+        .collect();
+    if !changes.is_empty() {
+        handle.replace(next);
+    }
+    Ok(changes)
+}
+
+fn modified_at() -> Option<SystemTime> {
+    config::config_file_path().and_then(|p| fs::metadata(p).ok()).and_then(|m| m.modified().ok())
+}
+
+fn apply(handle: &ConfigHandle, check: &(dyn Fn(&AuthixConfig) -> Result<(), String> + Send + Sync), trigger: &str) {
+    match reload(handle, check) {
+        Ok(changes) if changes.is_empty() => info!("config reloaded on {}, nothing changed", trigger),
+        Ok(changes) => info!("config reloaded on {}: {}", trigger, changes.join(", ")),
+        Err(e) => error!("config reload on {} rejected, keep current config: {}", trigger, e),
+    }
+}
+
+#[cfg(unix)]
+type Hangup = Option<tokio::signal::unix::Signal>;
+#[cfg(not(unix))]
+type Hangup = ();
+
+#[cfg(unix)]
+fn listen_hangup() -> Hangup {
+    tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())
+        .inspect_err(|e| warn!("listen SIGHUP error: {}", e))
+        .ok()
+}
+
+#[cfg(not(unix))]
+fn listen_hangup() -> Hangup {}
+
+async fn hangup(_signal: &mut Hangup) {
+    #[cfg(unix)]
+    if let Some(s) = _signal {
+        s.recv().await;
+        return;
+    }
+    std::future::pending().await
+}
+
+/// 启动配置热加载任务：配置文件修改或收到 SIGHUP 时重新加载，`check` 见 [`reload`]
+pub fn spawn_config_watcher(
+    handle: ConfigHandle,
+    check: impl Fn(&AuthixConfig) -> Result<(), String> + Send + Sync + 'static,
+    mut shutdown: watch::Receiver<bool>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut last_modified = modified_at();
+        let mut interval = tokio::time::interval(Duration::from_secs(WATCH_INTERVAL_SECS));
+        let mut sighup = listen_hangup();
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    let modified = modified_at();
+                    if modified != last_modified {
+                        last_modified = modified;
+                        apply(&handle, &check, "file change");
+                    }
+                }
+                _ = hangup(&mut sighup) => apply(&handle, &check, "SIGHUP"),
+                _ = shutdown.wait_for(|v| *v) => return,
+            }
+        }
+    })
+}
@@ -1,4 +1,6 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+use crate::storage::{decode_error, Column, ColumnIndex, Row, Value};
 
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -15,13 +17,92 @@ pub enum AuthType {
     Email
 }
 
-impl From<String> for AuthType {
-    fn from(value: String) -> Self {
-        match value.as_str() {
-            "password" => AuthType::Password,
-            "sms" => AuthType::Sms,
-            "email" => AuthType::Email,
-            _ => AuthType::Password,
+impl AuthType {
+    /// 内置认证方式，未知取值返回 `None`
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "password" => Some(AuthType::Password),
+            "sms" => Some(AuthType::Sms),
+            "email" => Some(AuthType::Email),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuthType::Password => "password",
+            AuthType::Sms => "sms",
+            AuthType::Email => "email",
         }
     }
-}
\ No newline at end of file
+}
+
+/// 用户账号状态，数据库中以整数存储
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+#[repr(u8)]
+pub enum UserStatus {
+    Active = 1,
+    Disabled = 2,
+    Locked = 3,
+    Pending = 4,    // 待验证，完成短信/邮箱验证码登录后激活
+    Deleted = 5,    // 已注销，保留期后清理
+}
+
+impl From<UserStatus> for Value {
+    fn from(status: UserStatus) -> Self {
+        Value::from(status as u8)
+    }
+}
+
+impl Column for UserStatus {
+    fn decode<I: ColumnIndex>(row: &Row, index: I) -> Result<Self, sqlx::Error> {
+        match u8::decode(row, index)? {
+            1 => Ok(UserStatus::Active),
+            2 => Ok(UserStatus::Disabled),
+            3 => Ok(UserStatus::Locked),
+            4 => Ok(UserStatus::Pending),
+            5 => Ok(UserStatus::Deleted),
+            other => Err(decode_error(index, format!("invalid user status {}", other))),
+        }
+    }
+}
+
+/// 登录标识类型，数据库中以字符串存储
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdentityType {
+    Username,
+    Phone,
+    Email,
+    External,   // 第三方身份（社交登录、LDAP），以 provider 区分来源
+}
+
+impl IdentityType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IdentityType::Username => "username",
+            IdentityType::Phone => "phone",
+            IdentityType::Email => "email",
+            IdentityType::External => "external",
+        }
+    }
+}
+
+impl From<IdentityType> for Value {
+    fn from(t: IdentityType) -> Self {
+        Value::from(t.as_str())
+    }
+}
+
+impl Column for IdentityType {
+    fn decode<I: ColumnIndex>(row: &Row, index: I) -> Result<Self, sqlx::Error> {
+        match String::decode(row, index)?.as_str() {
+            "username" => Ok(IdentityType::Username),
+            "phone" => Ok(IdentityType::Phone),
+            "email" => Ok(IdentityType::Email),
+            "external" => Ok(IdentityType::External),
+            other => Err(decode_error(index, format!("invalid identity type {}", other))),
+        }
+    }
+}
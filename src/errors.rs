@@ -1,4 +1,9 @@
+use axum::{http::StatusCode, response::{IntoResponse, Response}, Json};
+use jsonwebtoken::errors::ErrorKind;
 use thiserror::Error;
+use tracing::error;
+
+use crate::{common::R, i18n::Msg};
 
 #[derive(Debug, Error)]
 pub enum AuthixError {
@@ -9,16 +14,70 @@ pub enum AuthixError {
     EnvVarError(#[from] std::env::VarError),
 
     #[error("Unknown login type: {0}")]
-    UnknowLoginType(String),
+    UnknowLoginType(Msg),
 
     #[error("Unknown register type: {0}")]
-    UnknowRegisterType(String),
+    UnknowRegisterType(Msg),
+
+    #[error("Login type disabled: {0}")]
+    LoginTypeDisabled(Msg),
+
+    #[error("Register disabled: {0}")]
+    RegisterDisabled(Msg),
+
+    #[error("MFA required: {0}")]
+    MfaRequired(Msg),
+
+    #[error("Account disabled: {0}")]
+    AccountDisabled(Msg),
+
+    #[error("Account locked: {0}")]
+    AccountLocked(Msg),
+
+    #[error("Account pending verification: {0}")]
+    AccountPending(Msg),
+
+    #[error("Invalid parameter: {0}")]
+    InvalidParameter(Msg),
 
     #[error("Invalid credentials for {0}")]
-    InvalidCredentials(String),
+    InvalidCredentials(Msg),
+
+    #[error("Verification code expired: {0}")]
+    CodeExpired(Msg),
+
+    #[error("Verification code invalid: {0}")]
+    CodeInvalid(Msg),
+
+    #[error("Token invalid: {0}")]
+    TokenInvalid(Msg),
+
+    #[error("Token revoked: {0}")]
+    TokenRevoked(Msg),
+
+    #[error("Forbidden: {0}")]
+    Forbidden(Msg),
+
+    #[error("Not found: {0}")]
+    NotFound(Msg),
+
+    #[error("Conflict: {0}")]
+    Conflict(Msg),
+
+    #[error("Tenant not found: {0}")]
+    TenantNotFound(Msg),
+
+    #[error("Tenant disabled: {0}")]
+    TenantDisabled(Msg),
 
     #[error("User not found: {0}")]
-    UserNotFound(String),
+    UserNotFound(Msg),
+
+    #[error("User already exists: {0}")]
+    UserAlreadyExists(Msg),
+
+    #[error("Internal error: {0}")]
+    Internal(String),
 
     #[error("Database error: {0}")]
     DatabaseError(String),
@@ -30,7 +89,131 @@ pub enum AuthixError {
     RedisError(#[from] redis::RedisError),
 
     #[error("Redis DeadPool error for {0}")]
-    DeadPoolError(#[from] deadpool_redis::redis::RedisError)
+    DeadPoolError(#[from] deadpool_redis::redis::RedisError),
+
+    #[error("Redis pool error: {0}")]
+    RedisPoolError(#[from] deadpool_redis::PoolError),
+
+    #[error("Upstream unavailable: {0}")]
+    UpstreamUnavailable(String),
+}
+
+pub type AuthixResult<T> = Result<T, AuthixError>;
+
+impl AuthixError {
+    /// 稳定的错误码，客户端据此判断错误类型，已发布的错误码不做修改
+    pub fn code(&self) -> &'static str {
+        match self {
+            AuthixError::JsonWebTokenError(e) if matches!(e.kind(), ErrorKind::ExpiredSignature) => "AUTH_TOKEN_EXPIRED",
+            AuthixError::JsonWebTokenError(_) | AuthixError::TokenInvalid(_) => "AUTH_TOKEN_INVALID",
+            AuthixError::TokenRevoked(_) => "AUTH_TOKEN_REVOKED",
+            AuthixError::UnknowLoginType(_) => "AUTH_UNKNOWN_LOGIN_TYPE",
+            AuthixError::UnknowRegisterType(_) => "AUTH_UNKNOWN_REGISTER_TYPE",
+            AuthixError::LoginTypeDisabled(_) => "AUTH_LOGIN_TYPE_DISABLED",
+            AuthixError::RegisterDisabled(_) => "AUTH_REGISTER_DISABLED",
+            AuthixError::MfaRequired(_) => "AUTH_MFA_REQUIRED",
+            AuthixError::AccountDisabled(_) => "AUTH_ACCOUNT_DISABLED",
+            AuthixError::AccountLocked(_) => "AUTH_ACCOUNT_LOCKED",
+            AuthixError::AccountPending(_) => "AUTH_ACCOUNT_PENDING",
+            AuthixError::InvalidCredentials(_) => "AUTH_INVALID_CREDENTIALS",
+            AuthixError::CodeExpired(_) => "AUTH_CODE_EXPIRED",
+            AuthixError::CodeInvalid(_) => "AUTH_CODE_INVALID",
+            AuthixError::InvalidParameter(_) => "INVALID_PARAMETER",
+            AuthixError::Forbidden(_) => "FORBIDDEN",
+            AuthixError::NotFound(_) => "NOT_FOUND",
+            AuthixError::Conflict(_) => "CONFLICT",
+            AuthixError::TenantNotFound(_) => "TENANT_NOT_FOUND",
+            AuthixError::TenantDisabled(_) => "TENANT_DISABLED",
+            AuthixError::UserNotFound(_) => "USER_NOT_FOUND",
+            AuthixError::UserAlreadyExists(_) => "USER_ALREADY_EXISTS",
+            AuthixError::RedisPoolError(_) | AuthixError::UpstreamUnavailable(_) => "SERVICE_UNAVAILABLE",
+            AuthixError::EnvVarError(_)
+            | AuthixError::Internal(_)
+            | AuthixError::DatabaseError(_)
+            | AuthixError::SqlxError(_)
+            | AuthixError::RedisError(_)
+            | AuthixError::DeadPoolError(_) => "INTERNAL_ERROR",
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            AuthixError::JsonWebTokenError(_)
+            | AuthixError::TokenInvalid(_)
+            | AuthixError::TokenRevoked(_)
+            | AuthixError::MfaRequired(_)
+            | AuthixError::InvalidCredentials(_)
+            | AuthixError::CodeExpired(_)
+            | AuthixError::CodeInvalid(_) => StatusCode::UNAUTHORIZED,
+            AuthixError::LoginTypeDisabled(_)
+            | AuthixError::RegisterDisabled(_)
+            | AuthixError::AccountDisabled(_)
+            | AuthixError::AccountLocked(_)
+            | AuthixError::AccountPending(_)
+            | AuthixError::Forbidden(_)
+            | AuthixError::TenantDisabled(_) => StatusCode::FORBIDDEN,
+            AuthixError::UnknowLoginType(_) | AuthixError::UnknowRegisterType(_) | AuthixError::InvalidParameter(_) => StatusCode::BAD_REQUEST,
+            AuthixError::NotFound(_) | AuthixError::TenantNotFound(_) | AuthixError::UserNotFound(_) => StatusCode::NOT_FOUND,
+            AuthixError::Conflict(_) | AuthixError::UserAlreadyExists(_) => StatusCode::CONFLICT,
+            AuthixError::RedisPoolError(_) | AuthixError::UpstreamUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            AuthixError::EnvVarError(_)
+            | AuthixError::Internal(_)
+            | AuthixError::DatabaseError(_)
+            | AuthixError::SqlxError(_)
+            | AuthixError::RedisError(_)
+            | AuthixError::DeadPoolError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// 返回给客户端的提示信息，按当前请求语言渲染，服务端错误不暴露内部细节
+    pub fn public_message(&self) -> String {
+        match self {
+            AuthixError::JsonWebTokenError(e) if matches!(e.kind(), ErrorKind::ExpiredSignature) => Msg::new("code.AUTH_TOKEN_EXPIRED").localize(),
+            AuthixError::JsonWebTokenError(_) => Msg::new("code.AUTH_TOKEN_INVALID").localize(),
+            AuthixError::UnknowLoginType(m)
+            | AuthixError::UnknowRegisterType(m)
+            | AuthixError::LoginTypeDisabled(m)
+            | AuthixError::RegisterDisabled(m)
+            | AuthixError::MfaRequired(m)
+            | AuthixError::AccountDisabled(m)
+            | AuthixError::AccountLocked(m)
+            | AuthixError::AccountPending(m)
+            | AuthixError::InvalidParameter(m)
+            | AuthixError::InvalidCredentials(m)
+            | AuthixError::CodeExpired(m)
+            | AuthixError::CodeInvalid(m)
+            | AuthixError::TokenInvalid(m)
+            | AuthixError::TokenRevoked(m)
+            | AuthixError::Forbidden(m)
+            | AuthixError::NotFound(m)
+            | AuthixError::Conflict(m)
+            | AuthixError::TenantNotFound(m)
+            | AuthixError::TenantDisabled(m)
+            | AuthixError::UserNotFound(m)
+            | AuthixError::UserAlreadyExists(m) => m.localize(),
+            AuthixError::RedisPoolError(_) | AuthixError::UpstreamUnavailable(_) => Msg::new("code.SERVICE_UNAVAILABLE").localize(),
+            AuthixError::EnvVarError(_)
+            | AuthixError::Internal(_)
+            | AuthixError::DatabaseError(_)
+            | AuthixError::SqlxError(_)
+            | AuthixError::RedisError(_)
+            | AuthixError::DeadPoolError(_) => Msg::new("code.INTERNAL_ERROR").localize(),
+        }
+    }
 }
 
-pub type AuthixResult<T> = Result<T, AuthixError>;
\ No newline at end of file
+impl<T> From<AuthixError> for R<T> {
+    fn from(e: AuthixError) -> Self {
+        R { success: false, code: e.status().as_u16() as i32, message: Some(e.public_message()), error_code: Some(e.code()), data: None }
+    }
+}
+
+impl IntoResponse for AuthixError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        if status.is_server_error() {
+            error!("request failed: {}", self);
+        }
+        (status, Json(R::<()>::from(self))).into_response()
+    }
+}
@@ -0,0 +1,200 @@
+use std::sync::{Arc, Mutex};
+
+use axum::async_trait;
+use chrono::{DateTime, Local};
+use deadpool_redis::{redis, Pool};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{enums::UserStatus, errors::{AuthixError, AuthixResult}};
+
+/// 事件流近似最大长度，超出后裁剪最早的事件
+const EVENT_STREAM_MAX_LEN: usize = 100_000;
+
+/// 领域事件。`type` 与各版本的 `data` 结构一经发布不做破坏性修改，
+/// 需要不兼容变更时新增版本号
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum DomainEvent {
+    #[serde(rename = "user.registered")]
+    UserRegistered { user_id: u64, register_type: String, invited: bool },
+    #[serde(rename = "user.logged_in")]
+    UserLoggedIn { user_id: u64, login_type: String },
+    #[serde(rename = "user.login_failed")]
+    UserLoginFailed { login_type: String, reason: String },
+    #[serde(rename = "user.logged_out")]
+    UserLoggedOut { user_id: u64 },
+    #[serde(rename = "user.status_changed")]
+    UserStatusChanged { user_id: u64, status: UserStatus },
+    #[serde(rename = "user.deleted")]
+    UserDeleted { user_id: u64 },
+    #[serde(rename = "user.restored")]
+    UserRestored { user_id: u64 },
+    #[serde(rename = "session.revoked")]
+    SessionRevoked { user_id: u64, reason: String },
+}
+
+impl DomainEvent {
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            DomainEvent::UserRegistered { .. } => "user.registered",
+            DomainEvent::UserLoggedIn { .. } => "user.logged_in",
+            DomainEvent::UserLoginFailed { .. } => "user.login_failed",
+            DomainEvent::UserLoggedOut { .. } => "user.logged_out",
+            DomainEvent::UserStatusChanged { .. } => "user.status_changed",
+            DomainEvent::UserDeleted { .. } => "user.deleted",
+            DomainEvent::UserRestored { .. } => "user.restored",
+            DomainEvent::SessionRevoked { .. } => "session.revoked",
+        }
+    }
+
+    /// 事件 schema 版本
+    pub fn version(&self) -> u16 {
+        1
+    }
+}
+
+/// 事件信封，序列化为 `{"id", "type", "version", "tenant_id", "occurred_at", "data"}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventEnvelope {
+    pub id: String,
+    pub version: u16,
+    pub tenant_id: u64,
+    pub occurred_at: DateTime<Local>,
+    #[serde(flatten)]
+    pub event: DomainEvent,
+}
+
+impl EventEnvelope {
+    pub fn new(tenant_id: u64, event: DomainEvent) -> Self {
+        Self {
+            id: Uuid::new_v4().simple().to_string(),
+            version: event.version(),
+            tenant_id,
+            occurred_at: Local::now(),
+            event,
+        }
+    }
+}
+
+#[async_trait]
+pub trait EventPublisher: Send + Sync {
+    async fn publish(&self, envelope: EventEnvelope) -> AuthixResult<()>;
+}
+
+/// 发布事件，失败仅记录日志，不影响主流程
+pub async fn emit(publisher: &Arc<dyn EventPublisher>, tenant_id: u64, event: DomainEvent) {
+    let event_type = event.event_type();
+    if let Err(e) = publisher.publish(EventEnvelope::new(tenant_id, event)).await {
+        warn!("publish event {} error: {}", event_type, e);
+    }
+}
+
+/// Redis Streams 实现，每个事件以 `type`、`version`、`tenant_id`、`payload` 字段写入事件流
+pub struct RedisStreamPublisher {
+    pool: Pool,
+    stream_key: String,
+}
+
+impl RedisStreamPublisher {
+    pub fn new(pool: Pool, stream_key: impl Into<String>) -> Self {
+        Self { pool, stream_key: stream_key.into() }
+    }
+}
+
+#[async_trait]
+impl EventPublisher for RedisStreamPublisher {
+    async fn publish(&self, envelope: EventEnvelope) -> AuthixResult<()> {
+        let payload = serde_json::to_string(&envelope).map_err(|e| AuthixError::Internal(e.to_string()))?;
+        let mut conn = self.pool.get().await?;
+        let _: String = redis::cmd("XADD")
+            .arg(&self.stream_key)
+            .arg("MAXLEN")
+            .arg("~")
+            .arg(EVENT_STREAM_MAX_LEN)
+            .arg("*")
+            .arg("type")
+            .arg(envelope.event.event_type())
+            .arg("version")
+            .arg(envelope.version)
+            .arg("tenant_id")
+            .arg(envelope.tenant_id)
+            .arg("payload")
+            .arg(payload)
+            .query_async(&mut conn)
+            .await?;
+        Ok(())
+    }
+}
+
+/// 内存实现，用于测试及本地开发
+#[derive(Default)]
+pub struct InMemoryEventPublisher {
+    events: Mutex<Vec<EventEnvelope>>,
+}
+
+impl InMemoryEventPublisher {
+    /// 已发布事件快照
+    pub fn events(&self) -> Vec<EventEnvelope> {
+        self.events.lock().map(|e| e.clone()).unwrap_or_default()
+    }
+}
+
+#[async_trait]
+impl EventPublisher for InMemoryEventPublisher {
+    async fn publish(&self, envelope: EventEnvelope) -> AuthixResult<()> {
+        if let Ok(mut events) = self.events.lock() {
+            events.push(envelope);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn envelope_schema_is_stable() {
+        let envelope = EventEnvelope::new(3, DomainEvent::UserRegistered { user_id: 7, register_type: "email".into(), invited: true });
+        let value = serde_json::to_value(&envelope).unwrap();
+        assert_eq!(value["type"], "user.registered");
+        assert_eq!(value["version"], 1);
+        assert_eq!(value["tenant_id"], 3);
+        assert_eq!(value["data"], json!({ "user_id": 7, "register_type": "email", "invited": true }));
+        assert_eq!(value.as_object().unwrap().len(), 6);
+        assert_eq!(serde_json::from_value::<EventEnvelope>(value).unwrap(), envelope);
+    }
+
+    #[test]
+    fn event_type_matches_serialized_tag() {
+        let events = [
+            DomainEvent::UserRegistered { user_id: 1, register_type: "sms".into(), invited: false },
+            DomainEvent::UserLoggedIn { user_id: 1, login_type: "password".into() },
+            DomainEvent::UserLoginFailed { login_type: "password".into(), reason: "x".into() },
+            DomainEvent::UserLoggedOut { user_id: 1 },
+            DomainEvent::UserStatusChanged { user_id: 1, status: UserStatus::Disabled },
+            DomainEvent::UserDeleted { user_id: 1 },
+            DomainEvent::UserRestored { user_id: 1 },
+            DomainEvent::SessionRevoked { user_id: 1, reason: "logout".into() },
+        ];
+        for event in events {
+            assert_eq!(serde_json::to_value(&event).unwrap()["type"], event.event_type());
+        }
+    }
+
+    #[tokio::test]
+    async fn emit_publishes_envelope() {
+        let memory = Arc::new(InMemoryEventPublisher::default());
+        let publisher: Arc<dyn EventPublisher> = memory.clone();
+        emit(&publisher, 2, DomainEvent::UserLoggedOut { user_id: 5 }).await;
+        emit(&publisher, 2, DomainEvent::UserDeleted { user_id: 5 }).await;
+        let events = memory.events();
+        assert_eq!(events.len(), 2);
+        assert_eq!((events[0].tenant_id, &events[0].event), (2, &DomainEvent::UserLoggedOut { user_id: 5 }));
+        assert_ne!(events[0].id, events[1].id);
+    }
+}
@@ -0,0 +1,169 @@
+use std::{collections::BTreeMap, future::Future, time::{Duration, Instant}};
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use deadpool_redis::{redis, Pool};
+use serde::Serialize;
+use tracing::{info, warn};
+
+use crate::{common::R, config::StartupMode, i18n::Msg, state::AppState, storage::{self, Db}};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DependencyHealth {
+    pub status: &'static str,       // "up" | "down"
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+    pub status: &'static str,
+    pub checks: BTreeMap<&'static str, DependencyHealth>,
+}
+
+impl HealthReport {
+    pub fn is_up(&self) -> bool {
+        self.status == "up"
+    }
+}
+
+async fn timed<F>(check: F, timeout: Duration) -> DependencyHealth
+where
+    F: Future<Output = Result<(), String>>,
+{
+    let start = Instant::now();
+    let result = match tokio::time::timeout(timeout, check).await {
+        Ok(r) => r,
+        Err(_) => Err(format!("timeout after {}ms", timeout.as_millis())),
+    };
+    DependencyHealth {
+        status: if result.is_ok() { "up" } else { "down" },
+        latency_ms: start.elapsed().as_millis() as u64,
+        error: result.err(),
+    }
+}
+
+async fn ping_database(db: &Db) -> Result<(), String> {
+    storage::query("SELECT 1").execute(db).await.map(|_| ()).map_err(|e| e.to_string())
+}
+
+async fn ping_redis(pool: &Pool) -> Result<(), String> {
+    let mut conn = pool.get().await.map_err(|e| e.to_string())?;
+    redis::cmd("PING").query_async::<String>(&mut conn).await.map(|_| ()).map_err(|e| e.to_string())
+}
+
+/// 并发检查数据库与 Redis，未使用 Redis 时不检查 Redis
+pub async fn check_dependencies(state: &AppState) -> HealthReport {
+    let timeout = Duration::from_millis(state.config.get().health.check_timeout_ms);
+    let redis_check = async {
+        match &state.redis {
+            Some(pool) => Some(timed(ping_redis(pool), timeout).await),
+            None => None,
+        }
+    };
+    let (database, redis) = tokio::join!(timed(ping_database(&state.db), timeout), redis_check);
+    let mut checks = BTreeMap::from([("database", database)]);
+    if let Some(redis) = redis {
+        checks.insert("redis", redis);
+    }
+    let status = if checks.values().all(|c| c.status == "up") { "up" } else { "down" };
+    HealthReport { status, checks }
+}
+
+/// 存活探针，进程能响应即视为存活，不检查依赖
+pub async fn liveness() -> impl IntoResponse {
+    (StatusCode::OK, Json(R::ok_data("up")))
+}
+
+/// 就绪探针，依赖全部可用时返回 200，否则 503；停机中直接返回 503
+pub async fn readiness(State(state): State<AppState>) -> impl IntoResponse {
+    if state.is_shutting_down() {
+        let resp = R { success: false, code: 503, message: Some(Msg::new("health.shutting_down").localize()), error_code: Some("SERVICE_UNAVAILABLE"), data: None };
+        return (StatusCode::SERVICE_UNAVAILABLE, Json(resp));
+    }
+    let report = check_dependencies(&state).await;
+    if report.is_up() {
+        (StatusCode::OK, Json(R::ok_data(report)))
+    } else {
+        let resp = R { success: false, code: 503, message: Some(Msg::new("health.dependency_unavailable").localize()), error_code: Some("SERVICE_UNAVAILABLE"), data: Some(report) };
+        (StatusCode::SERVICE_UNAVAILABLE, Json(resp))
+    }
+}
+
+/// 按配置 `health.startup_mode` 检查启动依赖：
+/// - `lazy`（默认）：不检查，首次使用时再连接
+/// - `fail_fast`：检查一次，不可用立即返回错误
+/// - `wait`：每秒重试，直到可用或超过 `health.startup_wait_secs`
+pub async fn check_startup_dependencies(state: &AppState) -> Result<(), String> {
+    let config = state.config.get();
+    let health = &config.health;
+    let describe = |report: &HealthReport| {
+        report
+            .checks
+            .iter()
+            .filter_map(|(name, h)| h.error.as_ref().map(|e| format!("{}: {}", name, e)))
+            .collect::<Vec<_>>()
+            .join("; ")
+    };
+    match health.startup_mode {
+        StartupMode::Lazy => Ok(()),
+        StartupMode::FailFast => {
+            let report = check_dependencies(state).await;
+            if report.is_up() { Ok(()) } else { Err(describe(&report)) }
+        }
+        StartupMode::Wait => {
+            let deadline = Instant::now() + Duration::from_secs(health.startup_wait_secs);
+            loop {
+                let report = check_dependencies(state).await;
+                if report.is_up() {
+                    info!("dependencies are ready");
+                    return Ok(());
+                }
+                if Instant::now() >= deadline {
+                    return Err(describe(&report));
+                }
+                warn!("waiting for dependencies: {}", describe(&report));
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::AuthixConfig, state::test_state};
+
+    #[tokio::test]
+    async fn timed_reports_timeout_as_down() {
+        let slow = timed(async { tokio::time::sleep(Duration::from_secs(5)).await; Ok(()) }, Duration::from_millis(10)).await;
+        assert_eq!((slow.status, slow.error.as_deref()), ("down", Some("timeout after 10ms")));
+        let ok = timed(async { Ok(()) }, Duration::from_millis(10)).await;
+        assert_eq!((ok.status, ok.error), ("up", None));
+    }
+
+    #[tokio::test]
+    async fn readiness_follows_dependencies() {
+        let mut config = AuthixConfig::default();
+        config.health.startup_mode = StartupMode::FailFast;
+        let state = test_state(config).await;
+        let report = check_dependencies(&state).await;
+        assert!(report.is_up());
+        assert_eq!(report.checks.keys().copied().collect::<Vec<_>>(), ["database"]);
+        assert_eq!(readiness(State(state.clone())).await.into_response().status(), StatusCode::OK);
+        assert!(check_startup_dependencies(&state).await.is_ok());
+
+        state.mark_shutting_down();
+        assert_eq!(readiness(State(state.clone())).await.into_response().status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        // 数据库不可达
+        let mut state = test_state(AuthixConfig::default()).await;
+        state.db = Db::connect_lazy("postgres://authix@127.0.0.1:1/authix", 1).unwrap();
+        assert_eq!(readiness(State(state.clone())).await.into_response().status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(check_startup_dependencies(&state).await.is_ok());
+        let mut config = (*state.config.get()).clone();
+        config.health.startup_mode = StartupMode::FailFast;
+        state.config.replace(config);
+        assert!(check_startup_dependencies(&state).await.unwrap_err().starts_with("database: "));
+    }
+}
@@ -0,0 +1,197 @@
+use std::{cell::Cell, collections::HashMap, fmt};
+
+use axum::{
+    extract::{Request, State},
+    http::{header::{ACCEPT_LANGUAGE, CONTENT_LANGUAGE}, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use once_cell::sync::Lazy;
+use tracing::warn;
+
+use crate::config::ConfigHandle;
+
+/// 支持的语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Locale {
+    ZhCn,
+    EnUs,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::ZhCn, Locale::EnUs];
+
+    pub fn tag(self) -> &'static str {
+        match self {
+            Locale::ZhCn => "zh-CN",
+            Locale::EnUs => "en-US",
+        }
+    }
+
+    /// 按语言标签匹配，忽略大小写与地区，如 `zh`、`zh-Hans`、`en-GB`
+    pub fn parse(tag: &str) -> Option<Locale> {
+        let lang = tag.trim().split(['-', '_']).next().unwrap_or_default().to_ascii_lowercase();
+        match lang.as_str() {
+            "zh" => Some(Locale::ZhCn),
+            "en" => Some(Locale::EnUs),
+            _ => None,
+        }
+    }
+
+    /// 解析 `Accept-Language`，按权重选择第一个支持的语言
+    pub fn negotiate(header: &str) -> Option<Locale> {
+        let mut ranges: Vec<(&str, f32)> = header
+            .split(',')
+            .filter_map(|item| {
+                let mut parts = item.split(';');
+                let tag = parts.next()?.trim();
+                let q = parts
+                    .find_map(|p| p.trim().strip_prefix("q="))
+                    .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+                (!tag.is_empty() && q > 0.0).then_some((tag, q))
+            })
+            .collect();
+        ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+        ranges.into_iter().find_map(|(tag, _)| Locale::parse(tag))
+    }
+
+    fn catalog(self) -> &'static str {
+        match self {
+            Locale::ZhCn => include_str!("locales/zh-CN.toml"),
+            Locale::EnUs => include_str!("locales/en-US.toml"),
+        }
+    }
+}
+
+/// 各语言的消息目录，`[section] key = "..."` 展开为 `section.key`
+static CATALOGS: Lazy<HashMap<Locale, HashMap<String, String>>> = Lazy::new(|| {
+    fn walk(prefix: &str, table: toml::Table, out: &mut HashMap<String, String>) {
+        for (k, v) in table {
+            let key = if prefix.is_empty() { k } else { format!("{}.{}", prefix, k) };
+            match v {
+                toml::Value::Table(t) => walk(&key, t, out),
+                toml::Value::String(s) => {
+                    out.insert(key, s);
+                }
+                other => warn!("ignore non-string message {} = {}", key, other),
+            }
+        }
+    }
+    Locale::ALL
+        .iter()
+        .map(|&locale| {
+            let table: toml::Table = locale
+                .catalog()
+                .parse()
+                .unwrap_or_else(|e| panic!("invalid message catalog {}: {}", locale.tag(), e));
+            let mut messages = HashMap::new();
+            walk("", table, &mut messages);
+            (locale, messages)
+        })
+        .collect()
+});
+
+/// 请求的语言上下文
+struct RequestLocale {
+    /// 协商出的语言，未携带 `Accept-Language` 时为空
+    negotiated: Cell<Option<Locale>>,
+    /// 全局默认语言，取自配置 `i18n.default_locale`
+    default: Locale,
+}
+
+tokio::task_local! {
+    static REQUEST_LOCALE: RequestLocale;
+}
+
+/// 全局默认语言，取自配置 `i18n.default_locale`；请求之外（如后台任务）为 zh-CN
+pub fn default_locale() -> Locale {
+    REQUEST_LOCALE.try_with(|l| l.default).unwrap_or(Locale::ZhCn)
+}
+
+/// 当前请求使用的语言：`Accept-Language` -> 租户偏好 -> 全局默认
+pub fn current() -> Locale {
+    REQUEST_LOCALE.try_with(|l| l.negotiated.get().unwrap_or(l.default)).unwrap_or(Locale::ZhCn)
+}
+
+/// 设置租户偏好语言，请求已通过 `Accept-Language` 指定时不覆盖
+pub fn prefer(locale: Option<&str>) {
+    if let Some(locale) = locale.and_then(Locale::parse) {
+        let _ = REQUEST_LOCALE.try_with(|l| {
+            if l.negotiated.get().is_none() {
+                l.negotiated.set(Some(locale));
+            }
+        });
+    }
+}
+
+/// 语言协商中间件，并在响应中返回 `Content-Language`
+pub async fn negotiate_locale(State(config): State<ConfigHandle>, req: Request, next: Next) -> Response {
+    let default = Locale::parse(&config.get().i18n.default_locale).unwrap_or(Locale::ZhCn);
+    let negotiated = req
+        .headers()
+        .get(ACCEPT_LANGUAGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(Locale::negotiate);
+    REQUEST_LOCALE
+        .scope(RequestLocale { negotiated: Cell::new(negotiated), default }, async move {
+            let mut resp = next.run(req).await;
+            resp.headers_mut().insert(CONTENT_LANGUAGE, HeaderValue::from_static(current().tag()));
+            resp
+        })
+        .await
+}
+
+/// 查找消息，当前语言缺失时回退到默认语言，仍缺失则返回消息键
+pub fn lookup(locale: Locale, key: &str) -> &str {
+    [locale, default_locale()]
+        .iter()
+        .find_map(|l| CATALOGS.get(l).and_then(|m| m.get(key)))
+        .map_or(key, String::as_str)
+}
+
+/// 可本地化的提示信息，由消息键与参数组成，响应时按请求语言渲染
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Msg {
+    key: &'static str,
+    args: Vec<(&'static str, String)>,
+}
+
+impl Msg {
+    pub fn new(key: &'static str) -> Self {
+        Self { key, args: Vec::new() }
+    }
+
+    /// 追加参数，替换消息中的 `{name}`
+    pub fn arg(mut self, name: &'static str, value: impl fmt::Display) -> Self {
+        self.args.push((name, value.to_string()));
+        self
+    }
+
+    pub fn key(&self) -> &'static str {
+        self.key
+    }
+
+    pub fn render(&self, locale: Locale) -> String {
+        self.args
+            .iter()
+            .fold(lookup(locale, self.key).to_owned(), |text, (name, value)| text.replace(&format!("{{{}}}", name), value))
+    }
+
+    /// 按当前请求语言渲染
+    pub fn localize(&self) -> String {
+        self.render(current())
+    }
+}
+
+impl From<&'static str> for Msg {
+    fn from(key: &'static str) -> Self {
+        Msg::new(key)
+    }
+}
+
+/// 日志中统一输出英文
+impl fmt::Display for Msg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(Locale::EnUs))
+    }
+}
@@ -0,0 +1,42 @@
+//! Authix 认证服务。既可作为独立服务运行，也可通过 [`AuthixBuilder`] 嵌入其他 Axum 应用：
+//!
+//! ```ignore
+//! let authix = AuthixBuilder::from_env()?.build().await?;
+//! let app = Router::new().nest("/iam", authix.router());
+//! ```
+
+pub mod config;
+pub mod common;
+pub mod auth_handler;
+pub mod provider;
+pub mod user;
+pub mod utils;
+pub mod errors;
+pub mod i18n;
+pub mod cache;
+pub mod enums;
+pub mod tenant;
+pub mod notify;
+pub mod organization;
+pub mod admin;
+pub mod audit;
+pub mod login_history;
+pub mod webhook;
+pub mod event;
+pub mod metrics;
+pub mod health;
+pub mod shutdown;
+pub mod migrate;
+pub mod storage;
+pub mod state;
+mod app;
+
+pub use app::{routes, Authix, AuthixBuilder};
+/// 实现 Provider trait 所需的异步 trait 宏
+pub use axum::async_trait;
+pub use config::{AuthixConfig, ConfigHandle};
+pub use errors::{AuthixError, AuthixResult};
+pub use provider::{login::{LoginProvider, LoginRequest, LoginResponse}, register::{RegisterProvider, RegisterRequest}, FieldKind, RequestSchema};
+pub use state::{AppState, AppStateBuilder};
+pub use tenant::TenantContext;
+pub use user::{User, UserIdentity, UserProvider};
@@ -0,0 +1,348 @@
+use std::{net::IpAddr, sync::Arc};
+
+use axum::{async_trait, extract::{Query, State}, http::StatusCode, Json};
+use axum_extra::TypedHeader;
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+use crate::{
+    common::{ClientInfo, PageQuery, PageResult, UidHeader, R},
+    config::ConfigHandle,
+    enums::AuthType,
+    errors::{AuthixError, AuthixResult},
+    i18n::{Locale, Msg},
+    notify::NotifyProvider,
+    storage::{self, Db, FromRecord, Row},
+    tenant::TenantContext,
+    user::UserProvider,
+};
+
+pub const LOGIN_HISTORY_TABLE_NAME: &str = "i18n_login_history";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LoginRecord {
+    pub id: u64,
+    pub tenant_id: u64,
+    pub user_id: u64,
+    pub login_type: String,
+    pub ip: Option<String>,
+    pub ip_range: Option<String>,   // IPv4 /24、IPv6 /64 网段
+    pub user_agent: Option<String>,
+    pub device_id: String,          // 设备指纹
+    pub success: bool,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Local>,
+}
+
+impl FromRecord for LoginRecord {
+    fn from_record(row: &Row) -> Result<Self, sqlx::Error> {
+        Ok(LoginRecord {
+            id: row.get("id")?,
+            tenant_id: row.get("tenant_id")?,
+            user_id: row.get("user_id")?,
+            login_type: row.get("login_type")?,
+            ip: row.get("ip")?,
+            ip_range: row.get("ip_range")?,
+            user_agent: row.get("user_agent")?,
+            device_id: row.get("device_id")?,
+            success: row.get("success")?,
+            reason: row.get("reason")?,
+            created_at: row.get("created_at")?,
+        })
+    }
+}
+
+/// 一次登录尝试，`user_id` 为空时按登录标识查找用户
+#[derive(Debug, Clone)]
+pub struct LoginAttempt {
+    pub login_type: String,
+    pub identifier: String,
+    pub client: ClientInfo,
+    pub user_id: Option<u64>,
+    pub error: Option<String>,
+    /// 登录请求的语言，新设备提醒按此语言发送
+    pub locale: Locale,
+}
+
+#[async_trait]
+pub trait LoginHistoryProvider: Send + Sync {
+    async fn record(&self, record: &LoginRecord) -> AuthixResult<()>;
+    /// 用户此前有成功登录记录，且设备或 IP 网段未出现过时视为新设备
+    async fn is_new_device(&self, user_id: u64, device_id: &str, ip_range: Option<&str>) -> AuthixResult<bool>;
+    async fn list_login_history(&self, user_id: u64, page: isize, page_size: isize) -> AuthixResult<PageResult<LoginRecord>>;
+}
+
+pub struct LoginHistoryService {
+    db: Db,
+}
+
+impl LoginHistoryService {
+    pub fn new(db: Db) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl LoginHistoryProvider for LoginHistoryService {
+    async fn record(&self, record: &LoginRecord) -> AuthixResult<()> {
+        let pool = &self.db;
+        storage::query(format!(
+            "INSERT INTO {} (tenant_id, user_id, login_type, ip, ip_range, user_agent, device_id, success, reason, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            LOGIN_HISTORY_TABLE_NAME
+        ))
+            .bind(record.tenant_id)
+            .bind(record.user_id)
+            .bind(&record.login_type)
+            .bind(&record.ip)
+            .bind(&record.ip_range)
+            .bind(&record.user_agent)
+            .bind(&record.device_id)
+            .bind(record.success)
+            .bind(&record.reason)
+            .bind(record.created_at)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn is_new_device(&self, user_id: u64, device_id: &str, ip_range: Option<&str>) -> AuthixResult<bool> {
+        let pool = &self.db;
+        let backend = pool.backend();
+        // ip_range 为空时不参与判断，无需按 NULL 相等比较
+        let rows = storage::query(format!(
+            "SELECT COUNT(*) AS total, {} AS device_hits, {} AS range_hits FROM {} WHERE user_id = ? AND success = ?",
+            backend.cast_int("COALESCE(SUM(CASE WHEN device_id = ? THEN 1 ELSE 0 END), 0)"),
+            backend.cast_int("COALESCE(SUM(CASE WHEN ip_range = ? THEN 1 ELSE 0 END), 0)"),
+            LOGIN_HISTORY_TABLE_NAME
+        ))
+            .bind(device_id)
+            .bind(ip_range)
+            .bind(user_id)
+            .bind(true)
+            .fetch_rows(pool)
+            .await?;
+        let row = rows.first().ok_or(sqlx::Error::RowNotFound)?;
+        let total: i64 = row.get("total")?;
+        let device_hits: i64 = row.get("device_hits")?;
+        let range_hits: i64 = row.get("range_hits")?;
+        Ok(total > 0 && (device_hits == 0 || (ip_range.is_some() && range_hits == 0)))
+    }
+
+    async fn list_login_history(&self, user_id: u64, page: isize, page_size: isize) -> AuthixResult<PageResult<LoginRecord>> {
+        let pool = &self.db;
+        let total: i64 = storage::query(format!("SELECT COUNT(*) FROM {} WHERE user_id = ?", LOGIN_HISTORY_TABLE_NAME))
+            .bind(user_id)
+            .fetch_scalar(pool)
+            .await?;
+        let records = storage::query(format!(
+            "SELECT id, tenant_id, user_id, login_type, ip, ip_range, user_agent, device_id, success, reason, created_at FROM {} WHERE user_id = ? ORDER BY id DESC LIMIT ? OFFSET ?",
+            LOGIN_HISTORY_TABLE_NAME
+        ))
+            .bind(user_id)
+            .bind(page_size as i64)
+            .bind(((page - 1) * page_size) as i64)
+            .fetch_all(pool)
+            .await?;
+        Ok(PageResult { total: total as u64, records })
+    }
+}
+
+/// 设备指纹：优先使用客户端上报的设备标识，否则按 User-Agent 计算
+pub fn device_fingerprint(client: &ClientInfo) -> String {
+    let source = client.device_id.as_deref().or(client.user_agent.as_deref()).unwrap_or_default();
+    hex::encode(&Sha256::digest(source.as_bytes())[..16])
+}
+
+/// IP 所在网段，IPv4 取 /24，IPv6 取 /64
+pub fn ip_range(ip: &str) -> Option<String> {
+    match ip.parse::<IpAddr>().ok()? {
+        IpAddr::V4(v4) => {
+            let o = v4.octets();
+            Some(format!("{}.{}.{}.0/24", o[0], o[1], o[2]))
+        }
+        IpAddr::V6(v6) => {
+            let s = v6.segments();
+            Some(format!("{:x}:{:x}:{:x}:{:x}::/64", s[0], s[1], s[2], s[3]))
+        }
+    }
+}
+
+/// 记录登录历史，成功登录且为新设备时通过短信/邮件提醒用户
+pub async fn track_login(
+    history: Arc<dyn LoginHistoryProvider>,
+    user_provider: Arc<dyn UserProvider>,
+    notify: Arc<dyn NotifyProvider>,
+    config: ConfigHandle,
+    ctx: TenantContext,
+    attempt: LoginAttempt,
+) {
+    let tenant_id = ctx.tenant.id;
+    let user_id = match attempt.user_id {
+        Some(id) => Some(id),
+        None => {
+            // 自定义登录方式的标识含义未知，失败时不归属用户
+            let found = match AuthType::parse(&attempt.login_type) {
+                Some(AuthType::Password) => user_provider.get_user_by_username(tenant_id, attempt.identifier.clone()).await,
+                Some(AuthType::Sms) => user_provider.get_user_by_phone(tenant_id, attempt.identifier.clone()).await,
+                Some(AuthType::Email) => user_provider.get_user_by_email(tenant_id, attempt.identifier.clone()).await,
+                None => Ok(None),
+            };
+            found.ok().flatten().map(|u| u.id)
+        }
+    };
+    // 标识不存在的失败登录不归属任何用户
+    let Some(user_id) = user_id else { return };
+
+    let success = attempt.error.is_none();
+    let device_id = device_fingerprint(&attempt.client);
+    let range = attempt.client.ip.as_deref().and_then(ip_range);
+    let new_device = success && match history.is_new_device(user_id, &device_id, range.as_deref()).await {
+        Ok(v) => v,
+        Err(e) => {
+            warn!("check login device for user {} error: {}", user_id, e);
+            false
+        }
+    };
+    let record = LoginRecord {
+        id: 0,
+        tenant_id,
+        user_id,
+        login_type: attempt.login_type.as_str().to_owned(),
+        ip: attempt.client.ip.clone(),
+        ip_range: range,
+        user_agent: attempt.client.user_agent.clone(),
+        device_id,
+        success,
+        reason: attempt.error,
+        created_at: Local::now(),
+    };
+    if let Err(e) = history.record(&record).await {
+        warn!("record login history for user {} error: {}", user_id, e);
+    }
+    if !new_device {
+        return;
+    }
+
+    let user = match user_provider.get_user_by_id(user_id).await {
+        Ok(Some(u)) => u,
+        _ => return,
+    };
+    let branding = &ctx.settings.branding;
+    let content = branding.new_device_message(
+        &config.get().templates,
+        &record.created_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+        record.ip.as_deref().unwrap_or("unknown"),
+    );
+    let sent = match (&user.phone, &user.email) {
+        (Some(phone), _) => notify.send_sms(branding, phone, &content).await,
+        (None, Some(email)) => notify.send_email(branding, email, &Msg::new("notify.new_device_subject").render(attempt.locale), &content).await,
+        _ => return,
+    };
+    if let Err(e) = sent {
+        warn!("send new device alert to user {} error: {}", user_id, e);
+    }
+}
+
+pub async fn login_history(
+    State(history): State<Arc<dyn LoginHistoryProvider>>,
+    TypedHeader(uid): TypedHeader<UidHeader>,
+    Query(q): Query<PageQuery>,
+) -> AuthixResult<(StatusCode, Json<R<PageResult<LoginRecord>>>)> {
+    let id: u64 = uid.0.parse().map_err(|_| AuthixError::InvalidParameter("param.invalid_uid".into()))?;
+    let page = q.page.unwrap_or(1).max(1);
+    let page_size = q.page_size.unwrap_or(20).clamp(1, 200);
+    let data = history.list_login_history(id, page, page_size).await?;
+    Ok((StatusCode::OK, Json(R::ok_data(data))))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::{config::AuthixConfig, enums::UserStatus, state::test_state, tenant::settings::Branding, user::User};
+
+    #[test]
+    fn ip_range_and_fingerprint() {
+        assert_eq!(ip_range("10.1.2.3").as_deref(), Some("10.1.2.0/24"));
+        assert_eq!(ip_range("2001:db8:1:2:3::1").as_deref(), Some("2001:db8:1:2::/64"));
+        assert_eq!(ip_range("unknown"), None);
+
+        let ua = ClientInfo { user_agent: Some("ua".into()), ..Default::default() };
+        let device = ClientInfo { device_id: Some("d1".into()), ..ua.clone() };
+        assert_eq!(device_fingerprint(&ua).len(), 32);
+        assert_ne!(device_fingerprint(&ua), device_fingerprint(&device));
+        assert_eq!(device_fingerprint(&device), device_fingerprint(&ClientInfo { device_id: Some("d1".into()), ..Default::default() }));
+    }
+
+    #[derive(Default)]
+    struct RecordingNotify {
+        sent: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl NotifyProvider for RecordingNotify {
+        async fn send_sms(&self, _branding: &Branding, phone: &str, _content: &str) -> AuthixResult<()> {
+            self.sent.lock().unwrap().push(phone.to_owned());
+            Ok(())
+        }
+
+        async fn send_email(&self, _branding: &Branding, email: &str, _subject: &str, _content: &str) -> AuthixResult<()> {
+            self.sent.lock().unwrap().push(email.to_owned());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn track_login_alerts_on_new_device() {
+        let state = test_state(AuthixConfig::default()).await;
+        let notify = Arc::new(RecordingNotify::default());
+        let ctx = TenantContext {
+            tenant: state.tenants.get_tenant_by_id(1).await.unwrap().unwrap(),
+            settings: state.tenants.get_tenant_settings(1).await.unwrap(),
+        };
+        let user = state
+            .users
+            .create_user(User {
+                id: 0,
+                tenant_id: 1,
+                username: Some("alice".into()),
+                phone: None,
+                email: Some("a@example.com".into()),
+                password: "hash".into(),
+                status: UserStatus::Active,
+                crt_by: None,
+            })
+            .await
+            .unwrap();
+        let login = |device: &str, ip: &str, error: Option<&str>| {
+            let attempt = LoginAttempt {
+                login_type: "password".into(),
+                identifier: "alice".into(),
+                client: ClientInfo { ip: Some(ip.into()), user_agent: None, device_id: Some(device.into()) },
+                user_id: None,
+                error: error.map(str::to_owned),
+                locale: Locale::EnUs,
+            };
+            track_login(state.login_history.clone(), state.users.clone(), notify.clone(), state.config.clone(), ctx.clone(), attempt)
+        };
+
+        // 首次登录及同一设备、同一网段的登录不提醒
+        login("d1", "10.0.0.1", None).await;
+        login("d1", "10.0.0.9", None).await;
+        // 失败的登录只记录，不提醒
+        login("d2", "10.0.0.1", Some("auth.invalid_credentials")).await;
+        assert!(notify.sent.lock().unwrap().is_empty());
+
+        login("d2", "10.0.0.1", None).await;
+        login("d1", "192.168.1.1", None).await;
+        assert_eq!(*notify.sent.lock().unwrap(), ["a@example.com", "a@example.com"]);
+
+        let history = state.login_history.list_login_history(user.id, 1, 2).await.unwrap();
+        assert_eq!(history.total, 5);
+        assert_eq!(history.records[0].ip_range.as_deref(), Some("192.168.1.0/24"));
+        assert!(!history.records[1].device_id.is_empty() && history.records[1].success);
+    }
+}
@@ -1,35 +1,70 @@
-use std::{env, sync::Arc};
+use std::net::SocketAddr;
 
-use axum::{routing::{get, post}, Router};
+use authix::{config, metrics, migrate, shutdown, utils, AuthixBuilder};
 use dotenvy::dotenv;
-use tracing::info;
+use tokio::sync::watch;
+use tracing::{error, info};
 use tracing_subscriber::EnvFilter;
 
-use crate::{auth_handler::{login_handler, logout_handler, refresh_token, register_handler, send_code, verify_code}, provider::{login::{LoginProvider, LoginService}, register::{RegisterProvider, RegisterService}}, user::delete_user};
-use crate::user::{online_count, user_profile, online_users, UserService, UserProvider};
-use crate::utils::uuid::get_token;
-
-mod common;
-mod auth_handler;
-mod provider;
-mod user;
-mod utils;
-mod errors;
-mod cache;
-mod enums;
-
 #[tokio::main]
 async fn main() {
     // 初始化配置
     dotenv().ok();
     // 初始化日志
     init_logger().await;
+    // 加载并校验配置文件与环境变量
+    let config = match config::init() {
+        Ok(config) => config,
+        Err(e) => {
+            error!("invalid configuration, exit: {}", e);
+            std::process::exit(1);
+        }
+    };
+    // 数据库迁移子命令：authix migrate <up|down [version]|status>
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("migrate") {
+        let db = utils::database::connect(&config.get().database);
+        let result = migrate::run_command(&db, &args[1..]).await;
+        db.close().await;
+        if let Err(e) = result {
+            error!("migrate failed: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
     // 初始化app配置
-    let app = init_app().await;
-    let server_addr = env::var("SERVER_ADDR").unwrap_or("127.0.0.1:30000".to_owned());
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let authix = match AuthixBuilder::new(config.clone()).shutdown_signal(shutdown_rx.clone()).watch_config().build().await {
+        Ok(authix) => authix,
+        Err(e) => {
+            error!("{}, exit", e);
+            std::process::exit(1);
+        }
+    };
+    let app = authix.router();
+    let state = authix.state().clone();
+    let server_config = config.get();
+    let server_addr = &server_config.server.addr;
     info!("🚀 Auth service running at http://{}", server_addr);
+    // 指标在独立端口暴露
+    tokio::spawn(metrics::serve_metrics(server_config.server.metrics_addr.clone(), state.clone()));
     let listener = tokio::net::TcpListener::bind(server_addr).await.unwrap();
-    axum::serve(listener, app.into_make_service()).await.unwrap();
+    let mut stop = shutdown_rx;
+    let server = tokio::spawn(async move {
+        let serve = axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+            .with_graceful_shutdown(async move {
+                let _ = stop.wait_for(|v| *v).await;
+            });
+        if let Err(e) = serve.await {
+            error!("server error: {}", e);
+        }
+    });
+
+    // 优雅停机：置为未就绪 -> 停止接收新连接 -> 等待处理中请求 -> 落库审计事件与投递 Webhook -> 关闭连接池
+    shutdown::wait_for_signal().await;
+    shutdown::begin(&state, &shutdown_tx).await;
+    shutdown::drain(&state, server).await;
+    authix.shutdown().await;
 }
 
 pub async fn init_logger() {
@@ -40,31 +75,3 @@ pub async fn init_logger() {
         )
         .init();
 }
-
-pub async fn init_app() -> Router {
-    let login_service = Arc::new(LoginService::default());
-    let register_service = Arc::new(RegisterService::default());
-    let user_service = Arc::new(UserService::default());
-    let auth_router = Router::new()
-        .route("/register", post(register_handler))
-        .route("/code/verify", post(verify_code))
-        .route("/code/send", post(send_code))
-        .route("/login", post(login_handler))
-        .route("/logout", get(logout_handler));
-    let token_router = Router::new()
-        .route("/refresh", get(refresh_token))
-        .route("/get", get(get_token));
-    let user_router =  Router::new()
-        .route("/online_count", get(online_count))
-        .route("/online_users", get(online_users))
-        .route("/profile", get(user_profile))
-        .route("/delete", get(delete_user));
-
-    Router::new()
-    .nest("/auth", auth_router)
-    .nest("/token", token_router)
-    .nest("/user", user_router)
-    .layer(axum::Extension(login_service as Arc<dyn LoginProvider>))
-    .layer(axum::Extension(register_service as Arc<dyn RegisterProvider>))
-    .layer(axum::Extension(user_service as Arc<dyn UserProvider>))
-}
\ No newline at end of file
@@ -0,0 +1,121 @@
+use std::time::Instant;
+
+use axum::{extract::{MatchedPath, Request, State}, http::{header, StatusCode}, middleware::Next, response::{IntoResponse, Response}, routing::get, Router};
+use once_cell::sync::Lazy;
+use prometheus::{HistogramOpts, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry, TextEncoder};
+use tracing::{info, warn};
+
+use crate::state::AppState;
+
+/// 指标注册表，仅在独立的管理端口暴露
+pub static REGISTRY: Lazy<Registry> = Lazy::new(Registry::new);
+
+fn register<T: prometheus::core::Collector + Clone + 'static>(collector: T) -> T {
+    if let Err(e) = REGISTRY.register(Box::new(collector.clone())) {
+        warn!("register metric error: {}", e);
+    }
+    collector
+}
+
+fn counter_vec(name: &str, help: &str, labels: &[&str]) -> IntCounterVec {
+    register(IntCounterVec::new(Opts::new(name, help), labels).expect("valid metric definition"))
+}
+
+fn gauge_vec(name: &str, help: &str, labels: &[&str]) -> IntGaugeVec {
+    register(IntGaugeVec::new(Opts::new(name, help), labels).expect("valid metric definition"))
+}
+
+/// 登录次数，按登录方式与结果
+pub static LOGIN_TOTAL: Lazy<IntCounterVec> =
+    Lazy::new(|| counter_vec("authix_login_total", "Login attempts by type and outcome", &["login_type", "outcome"]));
+/// 注册次数，按注册方式与结果
+pub static REGISTER_TOTAL: Lazy<IntCounterVec> =
+    Lazy::new(|| counter_vec("authix_register_total", "Registrations by type and outcome", &["register_type", "outcome"]));
+/// 验证码发送次数
+pub static VERIFY_CODE_SENT_TOTAL: Lazy<IntCounterVec> =
+    Lazy::new(|| counter_vec("authix_verify_code_sent_total", "Verification codes sent by channel and outcome", &["verify_type", "outcome"]));
+/// 验证码校验次数
+pub static VERIFY_CODE_CHECKED_TOTAL: Lazy<IntCounterVec> =
+    Lazy::new(|| counter_vec("authix_verify_code_checked_total", "Verification code checks by outcome", &["outcome"]));
+/// 刷新令牌次数
+pub static TOKEN_REFRESH_TOTAL: Lazy<IntCounterVec> =
+    Lazy::new(|| counter_vec("authix_token_refresh_total", "Token refreshes by outcome", &["outcome"]));
+/// 令牌校验失败次数，按令牌类型
+pub static TOKEN_VERIFY_FAILURES_TOTAL: Lazy<IntCounterVec> =
+    Lazy::new(|| counter_vec("authix_token_verify_failures_total", "Token verification failures by token type", &["token_type"]));
+/// HTTP 请求耗时，按路由
+pub static HTTP_REQUEST_DURATION: Lazy<HistogramVec> = Lazy::new(|| {
+    let opts = HistogramOpts::new("authix_http_request_duration_seconds", "HTTP request latency by route");
+    register(HistogramVec::new(opts, &["method", "route", "status"]).expect("valid metric definition"))
+});
+/// 连接池连接数，`state` 为 size/idle/max（数据库）或 size/available/max（Redis）
+static DB_POOL_CONNECTIONS: Lazy<IntGaugeVec> =
+    Lazy::new(|| gauge_vec("authix_db_pool_connections", "Database pool connections by state", &["state"]));
+static REDIS_POOL_CONNECTIONS: Lazy<IntGaugeVec> =
+    Lazy::new(|| gauge_vec("authix_redis_pool_connections", "Redis pool connections by state", &["state"]));
+static ONLINE_USERS: Lazy<IntGauge> =
+    Lazy::new(|| register(IntGauge::new("authix_online_users", "Users with an unexpired access token").expect("valid metric definition")));
+
+pub fn outcome(success: bool) -> &'static str {
+    if success { "success" } else { "failure" }
+}
+
+/// 认证方式标签，未注册的取值统一为 `unknown`，避免任意输入产生新的时间序列
+pub fn auth_type_label(auth_type: &str, registered: bool) -> &str {
+    if registered { auth_type } else { "unknown" }
+}
+
+/// HTTP 耗时中间件，路由取匹配的路由模板，避免路径参数造成标签膨胀
+pub async fn track_http(req: Request, next: Next) -> Response {
+    let method = req.method().to_string();
+    let route = req.extensions().get::<MatchedPath>().map(|p| p.as_str().to_owned()).unwrap_or_else(|| "unmatched".to_owned());
+    let start = Instant::now();
+    let response = next.run(req).await;
+    HTTP_REQUEST_DURATION
+        .with_label_values(&[&method, &route, response.status().as_str()])
+        .observe(start.elapsed().as_secs_f64());
+    response
+}
+
+/// 采集时刷新连接池与在线用户数
+async fn refresh_gauges(state: &AppState) {
+    DB_POOL_CONNECTIONS.with_label_values(&["size"]).set(state.db.size() as i64);
+    DB_POOL_CONNECTIONS.with_label_values(&["idle"]).set(state.db.num_idle() as i64);
+    DB_POOL_CONNECTIONS.with_label_values(&["max"]).set(state.db.max_connections() as i64);
+
+    if let Some(redis) = &state.redis {
+        let status = redis.status();
+        REDIS_POOL_CONNECTIONS.with_label_values(&["size"]).set(status.size as i64);
+        REDIS_POOL_CONNECTIONS.with_label_values(&["available"]).set(status.available as i64);
+        REDIS_POOL_CONNECTIONS.with_label_values(&["max"]).set(status.max_size as i64);
+    }
+
+    match state.sessions.online_count(None).await {
+        Ok(count) => ONLINE_USERS.set(count as i64),
+        Err(e) => warn!("collect online users error: {}", e),
+    }
+}
+
+pub async fn metrics_handler(State(state): State<AppState>) -> impl IntoResponse {
+    refresh_gauges(&state).await;
+    match TextEncoder::new().encode_to_string(&REGISTRY.gather()) {
+        Ok(body) => (StatusCode::OK, [(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, [(header::CONTENT_TYPE, "text/plain")], e.to_string()),
+    }
+}
+
+/// 在独立端口暴露 `/metrics`，避免与业务接口一起对外开放
+pub async fn serve_metrics(addr: String, state: AppState) {
+    let app = Router::new().route("/metrics", get(metrics_handler)).with_state(state);
+    let listener = match tokio::net::TcpListener::bind(&addr).await {
+        Ok(l) => l,
+        Err(e) => {
+            warn!("bind metrics addr {} error: {}", addr, e);
+            return;
+        }
+    };
+    info!("📊 Metrics exposed at http://{}/metrics", addr);
+    if let Err(e) = axum::serve(listener, app).await {
+        warn!("metrics server error: {}", e);
+    }
+}
@@ -0,0 +1,381 @@
+use std::{collections::HashMap, time::Duration};
+
+use sqlx::migrate::{Migrate, Migration, Migrator};
+use tracing::{info, warn};
+
+use crate::{config::{AuthixConfig, StartupMode}, storage::{self, Backend, Db}, user::USER_TABLE_NAME};
+
+/// 内置的数据库迁移，按后端分别来自 `migrations/<backend>/` 目录，编译时打包进二进制
+static MYSQL_MIGRATOR: Migrator = sqlx::migrate!("migrations/mysql");
+static POSTGRES_MIGRATOR: Migrator = sqlx::migrate!("migrations/postgres");
+static SQLITE_MIGRATOR: Migrator = sqlx::migrate!("migrations/sqlite");
+
+/// 创建用户表的迁移版本
+const USERS_VERSION: i64 = 2;
+/// 手工建表的旧用户表改名后的表名，升级完成后删除
+const LEGACY_USER_TABLE_NAME: &str = "i18n_users_legacy";
+
+/// 当前数据库后端对应的迁移
+fn migrator(db: &Db) -> &'static Migrator {
+    match db.backend() {
+        Backend::MySql => &MYSQL_MIGRATOR,
+        Backend::Postgres => &POSTGRES_MIGRATOR,
+        Backend::Sqlite => &SQLITE_MIGRATOR,
+    }
+}
+
+/// 迁移状态
+#[derive(Debug, PartialEq, Eq)]
+enum MigrationState {
+    Applied,
+    Pending,
+    /// 已执行的迁移内容被修改
+    Modified,
+}
+
+/// 数据库结构版本与内置迁移的对比结果
+struct SchemaStatus {
+    migrations: Vec<(&'static Migration, MigrationState)>,
+    /// 数据库中存在、当前版本未包含的迁移，说明数据库由更新的版本迁移过
+    unknown: Vec<i64>,
+    /// 执行失败、未完成的迁移
+    dirty: Option<i64>,
+}
+
+impl SchemaStatus {
+    fn pending(&self) -> Vec<i64> {
+        self.migrations.iter().filter(|(_, s)| *s == MigrationState::Pending).map(|(m, _)| m.version).collect()
+    }
+
+    fn modified(&self) -> Vec<i64> {
+        self.migrations.iter().filter(|(_, s)| *s == MigrationState::Modified).map(|(m, _)| m.version).collect()
+    }
+
+    fn applied(&self) -> Vec<i64> {
+        self.migrations.iter().filter(|(_, s)| *s != MigrationState::Pending).map(|(m, _)| m.version).collect()
+    }
+}
+
+fn up_migrations(migrator: &'static Migrator) -> impl Iterator<Item = &'static Migration> {
+    migrator.iter().filter(|m| !m.migration_type.is_down_migration())
+}
+
+/// 内置迁移的最新版本
+pub fn latest_version(db: &Db) -> i64 {
+    up_migrations(migrator(db)).map(|m| m.version).max().unwrap_or(0)
+}
+
+async fn schema_status(db: &Db) -> Result<SchemaStatus, String> {
+    let migrator = migrator(db);
+    match db {
+        Db::MySql(pool) => status_of(migrator, &mut *pool.acquire().await.map_err(|e| e.to_string())?).await,
+        Db::Postgres(pool) => status_of(migrator, &mut *pool.acquire().await.map_err(|e| e.to_string())?).await,
+        Db::Sqlite(pool) => status_of(migrator, &mut *pool.acquire().await.map_err(|e| e.to_string())?).await,
+    }
+}
+
+async fn status_of(migrator: &'static Migrator, conn: &mut (dyn Migrate + Send)) -> Result<SchemaStatus, String> {
+    conn.ensure_migrations_table().await.map_err(|e| e.to_string())?;
+    let dirty = conn.dirty_version().await.map_err(|e| e.to_string())?;
+    let applied: HashMap<i64, _> = conn
+        .list_applied_migrations()
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|m| (m.version, m.checksum))
+        .collect();
+    let migrations = up_migrations(migrator)
+        .map(|m| {
+            let state = match applied.get(&m.version) {
+                None => MigrationState::Pending,
+                Some(checksum) if *checksum != m.checksum => MigrationState::Modified,
+                Some(_) => MigrationState::Applied,
+            };
+            (m, state)
+        })
+        .collect();
+    let mut unknown: Vec<i64> = applied.keys().copied().filter(|v| !migrator.version_exists(*v)).collect();
+    unknown.sort_unstable();
+    Ok(SchemaStatus { migrations, unknown, dirty })
+}
+
+/// 依次执行不超过 `version` 的未执行迁移
+async fn apply_until(migrator: &'static Migrator, conn: &mut (dyn Migrate + Send), version: i64) -> Result<(), String> {
+    conn.ensure_migrations_table().await.map_err(|e| e.to_string())?;
+    let applied: Vec<i64> = conn.list_applied_migrations().await.map_err(|e| e.to_string())?.iter().map(|m| m.version).collect();
+    for m in up_migrations(migrator).filter(|m| m.version <= version && !applied.contains(&m.version)) {
+        conn.apply(m).await.map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// 表的列名，表不存在时为空
+async fn table_columns(db: &Db, table: &str) -> Result<Vec<String>, String> {
+    let sql = match db.backend() {
+        Backend::MySql => "SELECT column_name AS name FROM information_schema.columns WHERE table_schema = DATABASE() AND table_name = ? ORDER BY ordinal_position",
+        Backend::Postgres => "SELECT column_name::text AS name FROM information_schema.columns WHERE table_schema = current_schema() AND table_name = ? ORDER BY ordinal_position",
+        Backend::Sqlite => "SELECT name FROM pragma_table_info(?) ORDER BY cid",
+    };
+    let rows = storage::query(sql).bind(table).fetch_rows(db).await.map_err(|e| e.to_string())?;
+    rows.iter().map(|r| r.get::<String, _>("name")).collect::<Result<_, _>>().map_err(|e| e.to_string())
+}
+
+/// 早期按 README 手工建的用户表缺少状态、注销时间等列，唯一键也未区分租户，`CREATE TABLE IF NOT EXISTS` 不会补齐。
+/// 创建用户表的迁移执行前先将旧表改名，迁移建好新表后复制同名列的数据，
+/// 未区分租户（`tenant_id` 为 0）的用户归属到默认租户，最后删除旧表。中途失败时再次执行会从复制数据继续
+async fn upgrade_legacy_users(db: &Db) -> Result<(), String> {
+    let users_applied = schema_status(db).await?.applied().contains(&USERS_VERSION);
+    if !users_applied && !table_columns(db, USER_TABLE_NAME).await?.is_empty() {
+        warn!("found hand-built {} table, upgrading it to the migrated schema", USER_TABLE_NAME);
+        storage::query(format!("ALTER TABLE {} RENAME TO {}", USER_TABLE_NAME, LEGACY_USER_TABLE_NAME)).execute(db).await.map_err(|e| e.to_string())?;
+    }
+    let legacy = table_columns(db, LEGACY_USER_TABLE_NAME).await?;
+    if legacy.is_empty() {
+        return Ok(());
+    }
+    let migrator = migrator(db);
+    match db {
+        Db::MySql(pool) => apply_until(migrator, &mut *pool.acquire().await.map_err(|e| e.to_string())?, USERS_VERSION).await,
+        Db::Postgres(pool) => apply_until(migrator, &mut *pool.acquire().await.map_err(|e| e.to_string())?, USERS_VERSION).await,
+        Db::Sqlite(pool) => apply_until(migrator, &mut *pool.acquire().await.map_err(|e| e.to_string())?, USERS_VERSION).await,
+    }?;
+
+    let current = table_columns(db, USER_TABLE_NAME).await?;
+    let (columns, dropped): (Vec<&String>, Vec<&String>) = legacy.iter().partition(|c| current.contains(c));
+    if !dropped.is_empty() {
+        warn!("columns {:?} of hand-built {} table are not in the migrated schema and are dropped", dropped, USER_TABLE_NAME);
+    }
+    let columns = columns.iter().map(|c| c.as_str()).collect::<Vec<_>>().join(", ");
+    let mut tx = db.begin().await.map_err(|e| e.to_string())?;
+    let copied = storage::query(format!("INSERT INTO {} ({}) SELECT {} FROM {}", USER_TABLE_NAME, columns, columns, LEGACY_USER_TABLE_NAME))
+        .execute(&mut tx).await.map_err(|e| e.to_string())?;
+    storage::query(format!("UPDATE {} SET tenant_id = (SELECT id FROM i18n_tenants WHERE code = 'default') WHERE tenant_id = 0", USER_TABLE_NAME))
+        .execute(&mut tx).await.map_err(|e| e.to_string())?;
+    // 复制时写入了显式 id，PostgreSQL 需同步自增序列
+    if db.backend() == Backend::Postgres {
+        storage::query(format!("SELECT setval(pg_get_serial_sequence('{0}', 'id'), (SELECT COALESCE(MAX(id), 0) + 1 FROM {0}), false)", USER_TABLE_NAME))
+            .execute(&mut tx).await.map_err(|e| e.to_string())?;
+    }
+    storage::query(format!("DROP TABLE {}", LEGACY_USER_TABLE_NAME)).execute(&mut tx).await.map_err(|e| e.to_string())?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+    info!("upgraded hand-built {} table, {} users copied", USER_TABLE_NAME, copied);
+    Ok(())
+}
+
+/// 执行全部未执行的迁移
+pub async fn up(db: &Db) -> Result<(), String> {
+    let before = latest_applied(db).await?;
+    upgrade_legacy_users(db).await?;
+    match db {
+        Db::MySql(pool) => migrator(db).run(pool).await,
+        Db::Postgres(pool) => migrator(db).run(pool).await,
+        Db::Sqlite(pool) => migrator(db).run(pool).await,
+    }
+    .map_err(|e| e.to_string())?;
+    let after = latest_applied(db).await?;
+    if after == before {
+        info!("database schema is up to date, version {}", after);
+    } else {
+        info!("database schema migrated from version {} to {}", before, after);
+    }
+    Ok(())
+}
+
+/// 回滚到指定版本（不含），未指定时回滚最近一次迁移
+pub async fn down(db: &Db, target: Option<i64>) -> Result<(), String> {
+    let applied = schema_status(db).await?.applied();
+    let target = match target {
+        Some(v) => v,
+        None => applied.iter().rev().nth(1).copied().unwrap_or(0),
+    };
+    match db {
+        Db::MySql(pool) => migrator(db).undo(pool, target).await,
+        Db::Postgres(pool) => migrator(db).undo(pool, target).await,
+        Db::Sqlite(pool) => migrator(db).undo(pool, target).await,
+    }
+    .map_err(|e| e.to_string())?;
+    info!("database schema reverted to version {}", target);
+    Ok(())
+}
+
+async fn latest_applied(db: &Db) -> Result<i64, String> {
+    Ok(schema_status(db).await?.applied().last().copied().unwrap_or(0))
+}
+
+/// 输出每个迁移的执行状态
+pub async fn print_status(db: &Db) -> Result<(), String> {
+    let status = schema_status(db).await?;
+    for (m, state) in &status.migrations {
+        let state = match state {
+            MigrationState::Applied => "applied",
+            MigrationState::Pending => "pending",
+            MigrationState::Modified => "modified",
+        };
+        println!("{:>6}  {:<8}  {}", m.version, state, m.description);
+    }
+    for v in &status.unknown {
+        println!("{:>6}  {:<8}  (not in this binary)", v, "unknown");
+    }
+    if let Some(v) = status.dirty {
+        println!("migration {} is partially applied", v);
+    }
+    Ok(())
+}
+
+/// 启动时检查数据库结构版本，开启 `database.migrate_on_startup` 时先执行迁移。
+/// `lazy` 启动模式下数据库不可用时跳过检查
+pub async fn prepare_schema(db: &Db, config: &AuthixConfig) -> Result<(), String> {
+    let timeout = Duration::from_millis(config.health.check_timeout_ms);
+    let ping = tokio::time::timeout(timeout, storage::query("SELECT 1").execute(db))
+        .await
+        .unwrap_or_else(|_| Err(sqlx::Error::PoolTimedOut));
+    match ping {
+        Ok(_) => {}
+        Err(e) if config.health.startup_mode == StartupMode::Lazy => {
+            warn!("database unavailable, skip schema version check: {}", e);
+            return Ok(());
+        }
+        Err(e) => return Err(e.to_string()),
+    }
+    if config.database.migrate_on_startup {
+        up(db).await?;
+    }
+
+    let status = schema_status(db).await?;
+    if let Some(v) = status.dirty {
+        return Err(format!("migration {} is partially applied, fix it and remove the row from _sqlx_migrations", v));
+    }
+    if let Some(v) = status.unknown.last() {
+        return Err(format!("database schema version {} is newer than this binary (latest {}), upgrade authix", v, latest_version(db)));
+    }
+    let modified = status.modified();
+    if !modified.is_empty() {
+        return Err(format!("applied migrations {:?} were modified", modified));
+    }
+    let pending = status.pending();
+    if !pending.is_empty() {
+        return Err(format!("pending migrations {:?}, run `authix migrate up` or enable database.migrate_on_startup", pending));
+    }
+    info!("{} database schema version {}", db.backend().name(), latest_version(db));
+    Ok(())
+}
+
+/// `authix migrate <up|down [version]|status>`
+pub async fn run_command(db: &Db, args: &[String]) -> Result<(), String> {
+    match args.first().map(String::as_str) {
+        None | Some("up") => up(db).await,
+        Some("down") => {
+            let target = args.get(1).map(|v| v.parse::<i64>().map_err(|_| format!("invalid version: {}", v))).transpose()?;
+            down(db, target).await
+        }
+        Some("status") => print_status(db).await,
+        Some(other) => Err(format!("unknown migrate command: {}, expected up | down [version] | status", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::database::{empty_test_db, test_db};
+
+    #[tokio::test]
+    async fn up_applies_all_migrations() {
+        let db = test_db().await;
+        let status = schema_status(&db).await.unwrap();
+        assert!(status.pending().is_empty());
+        assert_eq!(status.applied().last().copied(), Some(latest_version(&db)));
+        // 再次执行不做任何变更
+        up(&db).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn down_reverts_to_target_version() {
+        let db = test_db().await;
+        down(&db, Some(USERS_VERSION)).await.unwrap();
+        assert_eq!(schema_status(&db).await.unwrap().applied(), [1, USERS_VERSION]);
+        assert!(table_columns(&db, "i18n_user_identities").await.unwrap().is_empty());
+        up(&db).await.unwrap();
+        assert!(schema_status(&db).await.unwrap().pending().is_empty());
+    }
+
+    #[tokio::test]
+    async fn upgrades_hand_built_users_table() {
+        let db = empty_test_db();
+        // 早期 README 中的建表语句，用户名/手机号/邮箱全局唯一
+        storage::query(
+            "CREATE TABLE i18n_users (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                tenant_id BIGINT NOT NULL DEFAULT 0,
+                username VARCHAR(50) UNIQUE,
+                phone VARCHAR(20) UNIQUE,
+                email VARCHAR(100) UNIQUE,
+                password VARCHAR(255) NOT NULL,
+                legacy_flag INTEGER,
+                last_login TIMESTAMP NULL
+            )",
+        )
+        .execute(&db)
+        .await
+        .unwrap();
+        storage::query("INSERT INTO i18n_users (id, tenant_id, username, email, password) VALUES (7, 0, 'alice', 'alice@example.com', 'x'), (9, 5, 'bob', NULL, 'y')")
+            .execute(&db)
+            .await
+            .unwrap();
+
+        up(&db).await.unwrap();
+
+        let columns = table_columns(&db, USER_TABLE_NAME).await.unwrap();
+        assert!(columns.iter().any(|c| c == "status") && columns.iter().any(|c| c == "deleted_at"));
+        assert!(!columns.iter().any(|c| c == "legacy_flag"));
+        assert!(table_columns(&db, LEGACY_USER_TABLE_NAME).await.unwrap().is_empty());
+
+        let default_tenant: u64 = storage::query("SELECT id FROM i18n_tenants WHERE code = 'default'").fetch_scalar(&db).await.unwrap();
+        let tenant_of = |id: u64| storage::query("SELECT tenant_id FROM i18n_users WHERE id = ?").bind(id).fetch_scalar::<u64>(&db);
+        assert_eq!(tenant_of(7).await.unwrap(), default_tenant);
+        assert_eq!(tenant_of(9).await.unwrap(), 5);
+        let status: u8 = storage::query("SELECT status FROM i18n_users WHERE id = 7").fetch_scalar(&db).await.unwrap();
+        assert_eq!(status, 1);
+
+        // 唯一键按租户区分，标识已由后续迁移写入关联表
+        storage::query("INSERT INTO i18n_users (tenant_id, username, password) VALUES (?, 'alice', 'z')").bind(default_tenant + 100).execute(&db).await.unwrap();
+        let identities: i64 = storage::query("SELECT COUNT(*) FROM i18n_user_identities").fetch_scalar(&db).await.unwrap();
+        assert_eq!(identities, 3);
+        // 自增 id 从已有用户之后继续
+        let next = storage::query("INSERT INTO i18n_users (tenant_id, username, password) VALUES (1, 'carol', 'z')").insert(&db).await.unwrap();
+        assert!(next > 9);
+        assert!(schema_status(&db).await.unwrap().pending().is_empty());
+    }
+
+    #[tokio::test]
+    async fn releases_identities_of_deleted_users() {
+        let db = test_db().await;
+        down(&db, Some(8)).await.unwrap();
+        storage::query("INSERT INTO i18n_users (id, tenant_id, username, email, password, status) VALUES (3, 1, 'alice', 'alice@example.com', 'x', 5)")
+            .execute(&db)
+            .await
+            .unwrap();
+        storage::query("INSERT INTO i18n_user_identities (tenant_id, user_id, identity_type, provider, identifier) VALUES (1, 3, 'username', '', 'alice'), (1, 3, 'email', '', 'old@example.com')")
+            .execute(&db)
+            .await
+            .unwrap();
+
+        up(&db).await.unwrap();
+        let username: Option<String> = storage::query("SELECT username FROM i18n_users WHERE id = 3").fetch_scalar(&db).await.unwrap();
+        assert_eq!(username, None);
+        let primary: Vec<bool> = storage::query("SELECT is_primary FROM i18n_deleted_user_identities WHERE user_id = 3 ORDER BY id")
+            .fetch_rows(&db)
+            .await
+            .unwrap()
+            .iter()
+            .map(|r| r.get(0).unwrap())
+            .collect();
+        assert_eq!(primary, [true, false]);
+
+        // 回滚时移回登录标识与用户表列
+        down(&db, Some(8)).await.unwrap();
+        let username: Option<String> = storage::query("SELECT username FROM i18n_users WHERE id = 3").fetch_scalar(&db).await.unwrap();
+        assert_eq!(username.as_deref(), Some("alice"));
+        let identities: i64 = storage::query("SELECT COUNT(*) FROM i18n_user_identities WHERE user_id = 3").fetch_scalar(&db).await.unwrap();
+        assert_eq!(identities, 2);
+    }
+}
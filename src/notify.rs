@@ -0,0 +1,29 @@
+use axum::async_trait;
+use tracing::info;
+
+use crate::{errors::AuthixResult, tenant::settings::Branding};
+
+/// 短信/邮件通知通道
+#[async_trait]
+pub trait NotifyProvider: Send + Sync {
+    async fn send_sms(&self, branding: &Branding, phone: &str, content: &str) -> AuthixResult<()>;
+    async fn send_email(&self, branding: &Branding, email: &str, subject: &str, content: &str) -> AuthixResult<()>;
+}
+
+/// 默认通知实现，仅输出日志，接入短信/邮件网关时替换
+#[derive(Default)]
+pub struct LogNotifyService;
+
+#[async_trait]
+impl NotifyProvider for LogNotifyService {
+    async fn send_sms(&self, _branding: &Branding, phone: &str, content: &str) -> AuthixResult<()> {
+        info!("send sms to {}: {}", phone, content);
+        Ok(())
+    }
+
+    async fn send_email(&self, branding: &Branding, email: &str, subject: &str, content: &str) -> AuthixResult<()> {
+        let sender = branding.email_sender.as_deref().unwrap_or("noreply");
+        info!("send email from {} to {}: [{}] {}", sender, email, subject, content);
+        Ok(())
+    }
+}
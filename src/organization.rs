@@ -0,0 +1,635 @@
+use std::sync::Arc;
+
+use axum::{async_trait, extract::{Path, State}, http::StatusCode, Json};
+use axum_extra::extract::TypedHeader;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    common::{UidHeader, R},
+    config::ConfigHandle,
+    enums::{AuthType, IdentityType},
+    errors::{AuthixError, AuthixResult},
+    i18n::Msg,
+    notify::NotifyProvider,
+    provider::login::LoginResponse,
+    state::AppState,
+    storage::{self, Db, FromRecord, Row},
+    tenant::CurrentTenant,
+    user::UserProvider,
+    utils::{jwt, regex::{is_valid_email, is_valid_phone}},
+};
+
+pub const ORGANIZATION_TABLE_NAME: &str = "i18n_organizations";
+pub const ORG_MEMBER_TABLE_NAME: &str = "i18n_org_members";
+pub const ORG_INVITATION_TABLE_NAME: &str = "i18n_org_invitations";
+
+pub const ORG_ROLE_OWNER: &str = "owner";
+pub const ORG_ROLE_ADMIN: &str = "admin";
+pub const ORG_ROLE_MEMBER: &str = "member";
+
+const INVITATION_STATUS_PENDING: u8 = 0;
+const INVITATION_STATUS_ACCEPTED: u8 = 1;
+/// 邀请有效时长（秒）
+const INVITATION_SEC_TTL: u64 = 60 * 60 * 24 * 7;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Organization {
+    pub id: u64,
+    pub tenant_id: u64,
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(skip)]
+    pub crt_by: Option<u64>,
+}
+
+impl FromRecord for Organization {
+    fn from_record(row: &Row) -> Result<Self, sqlx::Error> {
+        Ok(Organization {
+            id: row.get("id")?,
+            tenant_id: row.get("tenant_id")?,
+            name: row.get("name")?,
+            description: row.get("description")?,
+            crt_by: None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Membership {
+    pub org_id: u64,
+    pub user_id: u64,
+    pub role: String,
+}
+
+impl FromRecord for Membership {
+    fn from_record(row: &Row) -> Result<Self, sqlx::Error> {
+        Ok(Membership { org_id: row.get("org_id")?, user_id: row.get("user_id")?, role: row.get("role")? })
+    }
+}
+
+/// 用户所属组织及其角色
+#[derive(Debug, Clone, Serialize)]
+pub struct UserOrganization {
+    pub id: u64,
+    pub name: String,
+    pub description: Option<String>,
+    pub role: String,
+}
+
+impl FromRecord for UserOrganization {
+    fn from_record(row: &Row) -> Result<Self, sqlx::Error> {
+        Ok(UserOrganization {
+            id: row.get("id")?,
+            name: row.get("name")?,
+            description: row.get("description")?,
+            role: row.get("role")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Invitation {
+    pub id: u64,
+    pub tenant_id: u64,
+    pub org_id: u64,
+    pub invite_type: String,
+    pub identifier: String,
+    pub role: String,
+    #[serde(skip)]
+    pub token: String,
+    pub status: u8,
+    pub invited_by: u64,
+    pub expires_at: DateTime<Local>,
+}
+
+impl FromRecord for Invitation {
+    fn from_record(row: &Row) -> Result<Self, sqlx::Error> {
+        Ok(Invitation {
+            id: row.get("id")?,
+            tenant_id: row.get("tenant_id")?,
+            org_id: row.get("org_id")?,
+            invite_type: row.get("invite_type")?,
+            identifier: row.get("identifier")?,
+            role: row.get("role")?,
+            token: row.get("token")?,
+            status: row.get("status")?,
+            invited_by: row.get("invited_by")?,
+            expires_at: row.get("expires_at")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OrganizationRequest {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MemberRequest {
+    pub user_id: u64,
+    #[serde(default)]
+    pub role: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct InviteRequest {
+    pub invite_type: AuthType,  // 邀请方式 sms、email
+    pub identifier: String,     // 手机号/邮箱
+    pub role: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AcceptInvitationRequest {
+    pub token: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SwitchOrganizationRequest {
+    pub org_id: u64,
+}
+
+pub(crate) fn is_valid_role(role: &str) -> bool {
+    [ORG_ROLE_OWNER, ORG_ROLE_ADMIN, ORG_ROLE_MEMBER].contains(&role)
+}
+
+fn can_manage(role: &str) -> bool {
+    role == ORG_ROLE_OWNER || role == ORG_ROLE_ADMIN
+}
+
+#[async_trait]
+pub trait OrganizationProvider: Send + Sync {
+    /// 创建组织，创建者自动成为 owner
+    async fn create_organization(&self, org: Organization) -> AuthixResult<Organization>;
+    async fn get_organization(&self, tenant_id: u64, id: u64) -> AuthixResult<Option<Organization>>;
+    async fn update_organization(&self, org: &Organization) -> AuthixResult<()>;
+    /// 删除组织及其成员、邀请
+    async fn delete_organization(&self, id: u64) -> AuthixResult<()>;
+    async fn list_user_organizations(&self, tenant_id: u64, user_id: u64) -> AuthixResult<Vec<UserOrganization>>;
+    async fn get_membership(&self, org_id: u64, user_id: u64) -> AuthixResult<Option<Membership>>;
+    async fn list_members(&self, org_id: u64) -> AuthixResult<Vec<Membership>>;
+    async fn save_membership(&self, org_id: u64, user_id: u64, role: &str) -> AuthixResult<()>;
+    async fn remove_membership(&self, org_id: u64, user_id: u64) -> AuthixResult<()>;
+    async fn create_invitation(&self, invitation: Invitation) -> AuthixResult<Invitation>;
+    /// 获取未接受且未过期的邀请
+    async fn get_pending_invitation(&self, token: String) -> AuthixResult<Option<Invitation>>;
+    /// 接受邀请：加入组织并标记邀请已接受
+    async fn accept_invitation(&self, invitation: &Invitation, user_id: u64) -> AuthixResult<()>;
+}
+
+pub struct OrganizationService {
+    db: Db,
+}
+
+impl OrganizationService {
+    pub fn new(db: Db) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl OrganizationProvider for OrganizationService {
+    async fn create_organization(&self, org: Organization) -> AuthixResult<Organization> {
+        let pool = &self.db;
+        let mut tx = pool.begin().await?;
+        let org_id = storage::query(format!("INSERT INTO {} (tenant_id, name, description, crt_by) VALUES (?, ?, ?, ?)", ORGANIZATION_TABLE_NAME))
+            .bind(org.tenant_id)
+            .bind(&org.name)
+            .bind(&org.description)
+            .bind(org.crt_by)
+            .insert(&mut tx)
+            .await?;
+        if let Some(owner) = org.crt_by {
+            storage::query(format!("INSERT INTO {} (org_id, user_id, role) VALUES (?, ?, ?)", ORG_MEMBER_TABLE_NAME))
+                .bind(org_id)
+                .bind(owner)
+                .bind(ORG_ROLE_OWNER)
+                .execute(&mut tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(Organization { id: org_id, crt_by: None, ..org })
+    }
+
+    async fn get_organization(&self, tenant_id: u64, id: u64) -> AuthixResult<Option<Organization>> {
+        let pool = &self.db;
+        let org = storage::query(format!("SELECT id, tenant_id, name, description FROM {} WHERE tenant_id = ? AND id = ?", ORGANIZATION_TABLE_NAME))
+            .bind(tenant_id)
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(org)
+    }
+
+    async fn update_organization(&self, org: &Organization) -> AuthixResult<()> {
+        let pool = &self.db;
+        storage::query(format!("UPDATE {} SET name = ?, description = ? WHERE id = ?", ORGANIZATION_TABLE_NAME))
+            .bind(&org.name)
+            .bind(&org.description)
+            .bind(org.id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_organization(&self, id: u64) -> AuthixResult<()> {
+        let pool = &self.db;
+        let mut tx = pool.begin().await?;
+        for (table, column) in [(ORG_INVITATION_TABLE_NAME, "org_id"), (ORG_MEMBER_TABLE_NAME, "org_id"), (ORGANIZATION_TABLE_NAME, "id")] {
+            storage::query(format!("DELETE FROM {} WHERE {} = ?", table, column))
+                .bind(id)
+                .execute(&mut tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn list_user_organizations(&self, tenant_id: u64, user_id: u64) -> AuthixResult<Vec<UserOrganization>> {
+        let pool = &self.db;
+        let list = storage::query(format!(
+            "SELECT o.id, o.name, o.description, m.role FROM {} o JOIN {} m ON m.org_id = o.id WHERE o.tenant_id = ? AND m.user_id = ? ORDER BY o.id",
+            ORGANIZATION_TABLE_NAME, ORG_MEMBER_TABLE_NAME
+        ))
+            .bind(tenant_id)
+            .bind(user_id)
+            .fetch_all(pool)
+            .await?;
+        Ok(list)
+    }
+
+    async fn get_membership(&self, org_id: u64, user_id: u64) -> AuthixResult<Option<Membership>> {
+        let pool = &self.db;
+        let member = storage::query(format!("SELECT org_id, user_id, role FROM {} WHERE org_id = ? AND user_id = ?", ORG_MEMBER_TABLE_NAME))
+            .bind(org_id)
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(member)
+    }
+
+    async fn list_members(&self, org_id: u64) -> AuthixResult<Vec<Membership>> {
+        let pool = &self.db;
+        let list = storage::query(format!("SELECT org_id, user_id, role FROM {} WHERE org_id = ? ORDER BY user_id", ORG_MEMBER_TABLE_NAME))
+            .bind(org_id)
+            .fetch_all(pool)
+            .await?;
+        Ok(list)
+    }
+
+    async fn save_membership(&self, org_id: u64, user_id: u64, role: &str) -> AuthixResult<()> {
+        let pool = &self.db;
+        storage::query(format!(
+            "INSERT INTO {} (org_id, user_id, role) VALUES (?, ?, ?){}",
+            ORG_MEMBER_TABLE_NAME,
+            self.db.backend().upsert(&["org_id", "user_id"], &["role"])
+        ))
+            .bind(org_id)
+            .bind(user_id)
+            .bind(role)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn remove_membership(&self, org_id: u64, user_id: u64) -> AuthixResult<()> {
+        let pool = &self.db;
+        storage::query(format!("DELETE FROM {} WHERE org_id = ? AND user_id = ?", ORG_MEMBER_TABLE_NAME))
+            .bind(org_id)
+            .bind(user_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn create_invitation(&self, invitation: Invitation) -> AuthixResult<Invitation> {
+        let pool = &self.db;
+        let id = storage::query(format!(
+            "INSERT INTO {} (tenant_id, org_id, invite_type, identifier, role, token, status, invited_by, expires_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            ORG_INVITATION_TABLE_NAME
+        ))
+            .bind(invitation.tenant_id)
+            .bind(invitation.org_id)
+            .bind(&invitation.invite_type)
+            .bind(&invitation.identifier)
+            .bind(&invitation.role)
+            .bind(&invitation.token)
+            .bind(invitation.status)
+            .bind(invitation.invited_by)
+            .bind(invitation.expires_at)
+            .insert(pool)
+            .await?;
+        Ok(Invitation { id, ..invitation })
+    }
+
+    async fn get_pending_invitation(&self, token: String) -> AuthixResult<Option<Invitation>> {
+        let pool = &self.db;
+        let invitation = storage::query(format!(
+            "SELECT id, tenant_id, org_id, invite_type, identifier, role, token, status, invited_by, expires_at FROM {} WHERE token = ? AND status = ? AND expires_at > CURRENT_TIMESTAMP",
+            ORG_INVITATION_TABLE_NAME
+        ))
+            .bind(token)
+            .bind(INVITATION_STATUS_PENDING)
+            .fetch_optional(pool)
+            .await?;
+        Ok(invitation)
+    }
+
+    async fn accept_invitation(&self, invitation: &Invitation, user_id: u64) -> AuthixResult<()> {
+        let pool = &self.db;
+        let mut tx = pool.begin().await?;
+        let rows = storage::query(format!("UPDATE {} SET status = ?, accepted_by = ? WHERE id = ? AND status = ?", ORG_INVITATION_TABLE_NAME))
+            .bind(INVITATION_STATUS_ACCEPTED)
+            .bind(user_id)
+            .bind(invitation.id)
+            .bind(INVITATION_STATUS_PENDING)
+            .execute(&mut tx)
+            .await?;
+        // 并发接受同一邀请时只有一方成功
+        if rows == 0 {
+            return Err(AuthixError::Conflict("org.invitation_used".into()));
+        }
+        storage::query(format!(
+            "INSERT INTO {} (org_id, user_id, role) VALUES (?, ?, ?){}",
+            ORG_MEMBER_TABLE_NAME,
+            self.db.backend().upsert(&["org_id", "user_id"], &["role"])
+        ))
+            .bind(invitation.org_id)
+            .bind(user_id)
+            .bind(&invitation.role)
+            .execute(&mut tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+/// 校验调用者在组织内的角色，返回组织信息与调用者 ID、角色
+async fn load_org_for_member(
+    org_provider: &Arc<dyn OrganizationProvider>,
+    tenant_id: u64,
+    org_id: u64,
+    uid: &UidHeader,
+) -> AuthixResult<(Organization, u64, String)> {
+    let user_id = parse_uid(uid)?;
+    let org = org_provider
+        .get_organization(tenant_id, org_id)
+        .await?
+        .ok_or_else(|| AuthixError::NotFound("org.not_found".into()))?;
+    let membership = org_provider
+        .get_membership(org_id, user_id)
+        .await?
+        .ok_or_else(|| AuthixError::Forbidden("org.not_member".into()))?;
+    Ok((org, user_id, membership.role))
+}
+
+fn parse_uid(uid: &UidHeader) -> AuthixResult<u64> {
+    uid.0.parse().map_err(|_| AuthixError::InvalidParameter("param.invalid_uid".into()))
+}
+
+fn permission_denied() -> AuthixError {
+    AuthixError::Forbidden("org.permission_denied".into())
+}
+
+/// 移除或降级 owner 前确认组织还有其他 owner
+async fn ensure_other_owner(org_provider: &Arc<dyn OrganizationProvider>, org_id: u64) -> AuthixResult<()> {
+    let owners = org_provider.list_members(org_id).await?.iter().filter(|m| m.role == ORG_ROLE_OWNER).count();
+    if owners <= 1 {
+        return Err(AuthixError::Conflict("org.last_owner".into()));
+    }
+    Ok(())
+}
+
+pub async fn create_organization(
+    CurrentTenant(ctx): CurrentTenant,
+    State(org_provider): State<Arc<dyn OrganizationProvider>>,
+    TypedHeader(uid): TypedHeader<UidHeader>,
+    Json(payload): Json<OrganizationRequest>,
+) -> AuthixResult<(StatusCode, Json<R<Organization>>)> {
+    let user_id = parse_uid(&uid)?;
+    if payload.name.trim().is_empty() {
+        return Err(AuthixError::InvalidParameter("org.name_required".into()));
+    }
+    let org = Organization {
+        id: 0,
+        tenant_id: ctx.tenant.id,
+        name: payload.name,
+        description: payload.description,
+        crt_by: Some(user_id),
+    };
+    let org = org_provider.create_organization(org).await?;
+    Ok((StatusCode::OK, Json(R::ok_data(org))))
+}
+
+pub async fn list_organizations(
+    CurrentTenant(ctx): CurrentTenant,
+    State(org_provider): State<Arc<dyn OrganizationProvider>>,
+    TypedHeader(uid): TypedHeader<UidHeader>,
+) -> AuthixResult<(StatusCode, Json<R<Vec<UserOrganization>>>)> {
+    let user_id = parse_uid(&uid)?;
+    let list = org_provider.list_user_organizations(ctx.tenant.id, user_id).await?;
+    Ok((StatusCode::OK, Json(R::ok_data(list))))
+}
+
+pub async fn get_organization(
+    CurrentTenant(ctx): CurrentTenant,
+    State(org_provider): State<Arc<dyn OrganizationProvider>>,
+    TypedHeader(uid): TypedHeader<UidHeader>,
+    Path(org_id): Path<u64>,
+) -> AuthixResult<(StatusCode, Json<R<Organization>>)> {
+    let (org, _, _) = load_org_for_member(&org_provider, ctx.tenant.id, org_id, &uid).await?;
+    Ok((StatusCode::OK, Json(R::ok_data(org))))
+}
+
+pub async fn update_organization(
+    CurrentTenant(ctx): CurrentTenant,
+    State(org_provider): State<Arc<dyn OrganizationProvider>>,
+    TypedHeader(uid): TypedHeader<UidHeader>,
+    Path(org_id): Path<u64>,
+    Json(payload): Json<OrganizationRequest>,
+) -> AuthixResult<(StatusCode, Json<R<Organization>>)> {
+    let (mut org, _, role) = load_org_for_member(&org_provider, ctx.tenant.id, org_id, &uid).await?;
+    if !can_manage(&role) {
+        return Err(permission_denied());
+    }
+    if payload.name.trim().is_empty() {
+        return Err(AuthixError::InvalidParameter("org.name_required".into()));
+    }
+    org.name = payload.name;
+    org.description = payload.description;
+    org_provider.update_organization(&org).await?;
+    Ok((StatusCode::OK, Json(R::ok_data(org))))
+}
+
+pub async fn delete_organization(
+    CurrentTenant(ctx): CurrentTenant,
+    State(org_provider): State<Arc<dyn OrganizationProvider>>,
+    TypedHeader(uid): TypedHeader<UidHeader>,
+    Path(org_id): Path<u64>,
+) -> AuthixResult<(StatusCode, Json<R<String>>)> {
+    let (_, _, role) = load_org_for_member(&org_provider, ctx.tenant.id, org_id, &uid).await?;
+    if role != ORG_ROLE_OWNER {
+        return Err(permission_denied());
+    }
+    org_provider.delete_organization(org_id).await?;
+    Ok((StatusCode::OK, Json(R::ok())))
+}
+
+pub async fn list_members(
+    CurrentTenant(ctx): CurrentTenant,
+    State(org_provider): State<Arc<dyn OrganizationProvider>>,
+    TypedHeader(uid): TypedHeader<UidHeader>,
+    Path(org_id): Path<u64>,
+) -> AuthixResult<(StatusCode, Json<R<Vec<Membership>>>)> {
+    load_org_for_member(&org_provider, ctx.tenant.id, org_id, &uid).await?;
+    let list = org_provider.list_members(org_id).await?;
+    Ok((StatusCode::OK, Json(R::ok_data(list))))
+}
+
+pub async fn update_member(
+    CurrentTenant(ctx): CurrentTenant,
+    State(org_provider): State<Arc<dyn OrganizationProvider>>,
+    TypedHeader(uid): TypedHeader<UidHeader>,
+    Path(org_id): Path<u64>,
+    Json(payload): Json<MemberRequest>,
+) -> AuthixResult<(StatusCode, Json<R<String>>)> {
+    let (_, _, role) = load_org_for_member(&org_provider, ctx.tenant.id, org_id, &uid).await?;
+    let new_role = payload.role.unwrap_or_else(|| ORG_ROLE_MEMBER.to_owned());
+    if !is_valid_role(&new_role) {
+        return Err(AuthixError::InvalidParameter(Msg::new("org.invalid_role").arg("role", &new_role)));
+    }
+    let member = org_provider
+        .get_membership(org_id, payload.user_id)
+        .await?
+        .ok_or_else(|| AuthixError::NotFound("org.member_not_found".into()))?;
+    // owner 可调整任意成员，admin 只能调整普通成员且不能授予 owner
+    if !can_manage(&role) || (role != ORG_ROLE_OWNER && (member.role != ORG_ROLE_MEMBER || new_role == ORG_ROLE_OWNER)) {
+        return Err(permission_denied());
+    }
+    if member.role == ORG_ROLE_OWNER && new_role != ORG_ROLE_OWNER {
+        ensure_other_owner(&org_provider, org_id).await?;
+    }
+    org_provider.save_membership(org_id, payload.user_id, &new_role).await?;
+    Ok((StatusCode::OK, Json(R::ok())))
+}
+
+pub async fn remove_member(
+    CurrentTenant(ctx): CurrentTenant,
+    State(org_provider): State<Arc<dyn OrganizationProvider>>,
+    TypedHeader(uid): TypedHeader<UidHeader>,
+    Path(org_id): Path<u64>,
+    Json(payload): Json<MemberRequest>,
+) -> AuthixResult<(StatusCode, Json<R<String>>)> {
+    let (_, user_id, role) = load_org_for_member(&org_provider, ctx.tenant.id, org_id, &uid).await?;
+    // 成员可自行退出，管理员可移除他人
+    if payload.user_id != user_id && !can_manage(&role) {
+        return Err(permission_denied());
+    }
+    let member = org_provider
+        .get_membership(org_id, payload.user_id)
+        .await?
+        .ok_or_else(|| AuthixError::NotFound("org.member_not_found".into()))?;
+    if member.role == ORG_ROLE_OWNER {
+        if role != ORG_ROLE_OWNER {
+            return Err(permission_denied());
+        }
+        ensure_other_owner(&org_provider, org_id).await?;
+    }
+    org_provider.remove_membership(org_id, payload.user_id).await?;
+    Ok((StatusCode::OK, Json(R::ok())))
+}
+
+pub async fn invite_member(
+    CurrentTenant(ctx): CurrentTenant,
+    State(org_provider): State<Arc<dyn OrganizationProvider>>,
+    State(notify_service): State<Arc<dyn NotifyProvider>>,
+    State(config): State<ConfigHandle>,
+    TypedHeader(uid): TypedHeader<UidHeader>,
+    Path(org_id): Path<u64>,
+    Json(payload): Json<InviteRequest>,
+) -> AuthixResult<(StatusCode, Json<R<Invitation>>)> {
+    let (org, user_id, role) = load_org_for_member(&org_provider, ctx.tenant.id, org_id, &uid).await?;
+    let invite_role = payload.role.unwrap_or_else(|| ORG_ROLE_MEMBER.to_owned());
+    if !can_manage(&role) || (invite_role == ORG_ROLE_OWNER && role != ORG_ROLE_OWNER) {
+        return Err(permission_denied());
+    }
+    if !is_valid_role(&invite_role) {
+        return Err(AuthixError::InvalidParameter(Msg::new("org.invalid_role").arg("role", &invite_role)));
+    }
+    let invite_type = match payload.invite_type {
+        AuthType::Sms if is_valid_phone(&payload.identifier) => "sms",
+        AuthType::Email if is_valid_email(&payload.identifier) => "email",
+        AuthType::Sms => return Err(AuthixError::InvalidParameter("param.phone_invalid".into())),
+        AuthType::Email => return Err(AuthixError::InvalidParameter("param.email_invalid".into())),
+        _ => return Err(AuthixError::InvalidParameter("org.unsupported_invite_type".into())),
+    };
+
+    let invitation = Invitation {
+        id: 0,
+        tenant_id: ctx.tenant.id,
+        org_id,
+        invite_type: invite_type.to_owned(),
+        identifier: payload.identifier,
+        role: invite_role,
+        token: Uuid::new_v4().to_string().replace("-", ""),
+        status: INVITATION_STATUS_PENDING,
+        invited_by: user_id,
+        expires_at: Local::now() + chrono::Duration::seconds(INVITATION_SEC_TTL as i64),
+    };
+    let invitation = org_provider.create_invitation(invitation).await?;
+
+    // 复用验证码通知通道发送邀请
+    let branding = &ctx.settings.branding;
+    let content = branding.invitation_message(&config.get().templates, &org.name, &invitation.token, INVITATION_SEC_TTL);
+    match payload.invite_type {
+        AuthType::Sms => notify_service.send_sms(branding, &invitation.identifier, &content).await?,
+        _ => notify_service.send_email(branding, &invitation.identifier, &Msg::new("notify.invitation_subject").localize(), &content).await?,
+    };
+    Ok((StatusCode::OK, Json(R::ok_data(invitation))))
+}
+
+/// 已注册用户接受邀请
+pub async fn accept_invitation(
+    CurrentTenant(ctx): CurrentTenant,
+    State(org_provider): State<Arc<dyn OrganizationProvider>>,
+    State(user_provider): State<Arc<dyn UserProvider>>,
+    TypedHeader(uid): TypedHeader<UidHeader>,
+    Json(payload): Json<AcceptInvitationRequest>,
+) -> AuthixResult<(StatusCode, Json<R<String>>)> {
+    let user_id = parse_uid(&uid)?;
+    let invitation = org_provider
+        .get_pending_invitation(payload.token)
+        .await?
+        .filter(|inv| inv.tenant_id == ctx.tenant.id)
+        .ok_or_else(|| AuthixError::InvalidParameter("org.invitation_not_found".into()))?;
+    // 邀请只能由关联了被邀请手机号/邮箱的用户接受，含后续关联的登录标识
+    let identity_type = if invitation.invite_type == "sms" { IdentityType::Phone } else { IdentityType::Email };
+    let invitee = user_provider.get_user_by_identity(ctx.tenant.id, identity_type, "", &invitation.identifier).await?;
+    if invitee.map(|u| u.id) != Some(user_id) {
+        return Err(AuthixError::Forbidden("org.invitation_mismatch".into()));
+    }
+    org_provider.accept_invitation(&invitation, user_id).await?;
+    Ok((StatusCode::OK, Json(R::ok())))
+}
+
+/// 切换当前组织，重新签发携带 org_id 的令牌
+pub async fn switch_organization(
+    CurrentTenant(ctx): CurrentTenant,
+    State(state): State<AppState>,
+    State(org_provider): State<Arc<dyn OrganizationProvider>>,
+    TypedHeader(uid): TypedHeader<UidHeader>,
+    Json(payload): Json<SwitchOrganizationRequest>,
+) -> AuthixResult<(StatusCode, Json<R<LoginResponse>>)> {
+    let (_, user_id, _) = load_org_for_member(&org_provider, ctx.tenant.id, payload.org_id, &uid).await?;
+    let resp = jwt::create_token(
+        &state,
+        user_id.to_string(),
+        ctx.tenant.id.to_string(),
+        Some(payload.org_id),
+        ctx.settings.access_token_exp,
+        ctx.settings.refresh_token_exp,
+    ).await?;
+    Ok((StatusCode::OK, Json(R::ok_data(resp))))
+}
@@ -0,0 +1,435 @@
+use std::{collections::HashMap, sync::{Arc, Mutex}, time::Duration};
+
+use axum::async_trait;
+use ldap3::{ldap_escape, Ldap, LdapConnAsync, LdapConnSettings, LdapError, Scope, SearchEntry};
+use tracing::warn;
+
+use crate::{
+    common::R,
+    config::{LdapConfig, LdapGroupRole},
+    enums::{IdentityType, UserStatus},
+    errors::{AuthixError, AuthixResult},
+    i18n::Msg,
+    organization::{OrganizationProvider, ORG_ROLE_OWNER},
+    provider::{login::{LoginProvider, LoginRequest, LoginResponse}, unusable_password},
+    state::AppState,
+    tenant::TenantContext,
+    user::{User, UserIdentity},
+    utils::jwt,
+};
+
+/// LDAP 结果码：凭据错误
+const RC_INVALID_CREDENTIALS: u32 = 49;
+
+/// 目录中的用户条目
+#[derive(Debug, Clone, Default)]
+pub struct LdapEntry {
+    pub dn: String,
+    pub attrs: HashMap<String, Vec<String>>,
+}
+
+impl LdapEntry {
+    /// 属性取值，属性名不区分大小写
+    pub fn values(&self, name: &str) -> &[String] {
+        self.attrs.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_slice()).unwrap_or_default()
+    }
+
+    pub fn first(&self, name: &str) -> Option<&str> {
+        self.values(name).first().map(String::as_str).filter(|v| !v.is_empty())
+    }
+}
+
+/// 目录访问，默认实现为 `Ldap3Directory`，测试时可替换为 `InMemoryDirectory`
+#[async_trait]
+pub trait LdapDirectory: Send + Sync {
+    /// 以服务账号查找登录标识对应的用户条目，未找到或匹配多条时返回 `None`
+    async fn search_user(&self, config: &LdapConfig, username: &str) -> AuthixResult<Option<LdapEntry>>;
+    /// 以用户 DN 与密码绑定，凭据错误时返回 `false`
+    async fn bind(&self, config: &LdapConfig, dn: &str, password: &str) -> AuthixResult<bool>;
+}
+
+fn unavailable(e: LdapError) -> AuthixError {
+    AuthixError::UpstreamUnavailable(format!("ldap: {}", e))
+}
+
+/// 基于 `ldap3` 的目录访问，每次操作使用独立连接
+pub struct Ldap3Directory;
+
+impl Ldap3Directory {
+    async fn connect(config: &LdapConfig) -> AuthixResult<Ldap> {
+        let settings = LdapConnSettings::new()
+            .set_conn_timeout(Duration::from_secs(config.timeout_secs))
+            .set_starttls(config.starttls)
+            .set_no_tls_verify(config.tls_skip_verify);
+        let (conn, ldap) = LdapConnAsync::with_settings(settings, &config.url).await.map_err(unavailable)?;
+        ldap3::drive!(conn);
+        Ok(ldap)
+    }
+}
+
+#[async_trait]
+impl LdapDirectory for Ldap3Directory {
+    async fn search_user(&self, config: &LdapConfig, username: &str) -> AuthixResult<Option<LdapEntry>> {
+        let timeout = Duration::from_secs(config.timeout_secs);
+        let mut ldap = Self::connect(config).await?;
+        if !config.bind_dn.is_empty() {
+            ldap.with_timeout(timeout).simple_bind(&config.bind_dn, &config.bind_password).await
+                .and_then(|r| r.success())
+                .map_err(unavailable)?;
+        }
+        let filter = config.user_filter.replace("{username}", &ldap_escape(username));
+        let attrs = [&config.attributes.username, &config.attributes.email, &config.attributes.phone, &config.attributes.groups];
+        let (entries, _) = ldap.with_timeout(timeout).search(&config.base_dn, Scope::Subtree, &filter, attrs).await
+            .and_then(|r| r.success())
+            .map_err(unavailable)?;
+        let _ = ldap.unbind().await;
+        if entries.len() != 1 {
+            return Ok(None);
+        }
+        let entry = SearchEntry::construct(entries.into_iter().next().expect("one entry"));
+        Ok(Some(LdapEntry { dn: entry.dn, attrs: entry.attrs }))
+    }
+
+    async fn bind(&self, config: &LdapConfig, dn: &str, password: &str) -> AuthixResult<bool> {
+        let mut ldap = Self::connect(config).await?;
+        let result = ldap.with_timeout(Duration::from_secs(config.timeout_secs)).simple_bind(dn, password).await.map_err(unavailable)?;
+        let _ = ldap.unbind().await;
+        match result.rc {
+            0 => Ok(true),
+            RC_INVALID_CREDENTIALS => Ok(false),
+            _ => Err(unavailable(LdapError::LdapResult { result })),
+        }
+    }
+}
+
+/// 进程内目录，代替 LDAP 服务器用于本地开发与测试。
+/// 查找时只按 `attributes.username` 匹配，不解析 `user_filter`
+#[derive(Default)]
+pub struct InMemoryDirectory {
+    entries: Mutex<Vec<(LdapEntry, String)>>,
+}
+
+impl InMemoryDirectory {
+    pub fn add_entry(&self, entry: LdapEntry, password: &str) {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).push((entry, password.to_string()));
+    }
+}
+
+#[async_trait]
+impl LdapDirectory for InMemoryDirectory {
+    async fn search_user(&self, config: &LdapConfig, username: &str) -> AuthixResult<Option<LdapEntry>> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let mut found = entries.iter()
+            .filter(|(e, _)| e.values(&config.attributes.username).iter().any(|v| v.eq_ignore_ascii_case(username)));
+        match (found.next(), found.next()) {
+            (Some((entry, _)), None) => Ok(Some(entry.clone())),
+            _ => Ok(None),
+        }
+    }
+
+    async fn bind(&self, _config: &LdapConfig, dn: &str, password: &str) -> AuthixResult<bool> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(entries.iter().any(|(e, p)| e.dn.eq_ignore_ascii_case(dn) && p == password))
+    }
+}
+
+/// LDAP / AD 登录：查找用户条目后以用户 DN 绑定校验密码，首次登录时创建本地用户
+pub struct LdapLoginProvider {
+    directory: Arc<dyn LdapDirectory>,
+}
+
+impl LdapLoginProvider {
+    pub fn new(directory: Arc<dyn LdapDirectory>) -> Self {
+        Self { directory }
+    }
+
+    /// 查找用户条目并以用户 DN 绑定校验密码，返回条目及目录中的用户名
+    async fn authenticate(&self, ldap: &LdapConfig, req: &LoginRequest) -> AuthixResult<(LdapEntry, String)> {
+        if !ldap.enabled() {
+            return Err(AuthixError::LoginTypeDisabled(Msg::new("auth.login_type_disabled").arg("type", &req.login_type)));
+        }
+        // 空密码在 LDAP 中为匿名绑定，会被视为成功
+        if req.credential.is_empty() {
+            return Err(AuthixError::InvalidCredentials("auth.invalid_credentials".into()));
+        }
+        let Some(entry) = self.directory.search_user(ldap, &req.identifier).await? else {
+            return Err(AuthixError::InvalidCredentials("auth.invalid_credentials".into()));
+        };
+        if !self.directory.bind(ldap, &entry.dn, &req.credential).await? {
+            return Err(AuthixError::InvalidCredentials("auth.invalid_credentials".into()));
+        }
+        let username = entry.first(&ldap.attributes.username).unwrap_or(&req.identifier).to_string();
+        Ok((entry, username))
+    }
+}
+
+impl Default for LdapLoginProvider {
+    fn default() -> Self {
+        Self::new(Arc::new(Ldap3Directory))
+    }
+}
+
+#[async_trait]
+impl LoginProvider for LdapLoginProvider {
+    async fn login(&self, ctx: &TenantContext, req: &LoginRequest, state: &AppState) -> AuthixResult<R<LoginResponse>> {
+        let config = state.config.get();
+        let ldap = &config.ldap;
+        let (entry, username) = self.authenticate(ldap, req).await?;
+
+        let user = resolve_user(state, ctx, ldap, &req.login_type, &entry, &username).await?;
+        user.ensure_can_login()?;
+
+        if !ldap.group_roles.is_empty() {
+            let groups = entry.values(&ldap.attributes.groups);
+            if let Err(e) = sync_group_roles(&*state.orgs, ctx.tenant.id, user.id, &ldap.group_roles, groups).await {
+                warn!("sync ldap groups for user {} error: {}", user.id, e);
+            }
+        }
+
+        let resp = jwt::create_token(state, user.id.to_string(), user.tenant_id.to_string(), None, ctx.settings.access_token_exp, ctx.settings.refresh_token_exp).await?;
+        let _ = state.users.update_last_login_time(user.id).await?;
+        Ok(R::ok_data(resp))
+    }
+
+    async fn identify(&self, _ctx: &TenantContext, req: &LoginRequest, state: &AppState) -> AuthixResult<Option<String>> {
+        let (_, username) = self.authenticate(&state.config.get().ldap, req).await?;
+        Ok(Some(username))
+    }
+}
+
+/// 优先按已关联的目录账号查找，其次以目录中的用户名匹配本地用户，均未找到时按配置创建。
+/// 匹配或新建的用户关联该目录账号
+async fn resolve_user(
+    state: &AppState,
+    ctx: &TenantContext,
+    ldap: &LdapConfig,
+    login_type: &str,
+    entry: &LdapEntry,
+    username: &str,
+) -> AuthixResult<User> {
+    if let Some(user) = state.users.get_user_by_identity(ctx.tenant.id, IdentityType::External, login_type, username).await? {
+        return Ok(user);
+    }
+    // 同名本地用户仅在开启 `match_local_username` 时关联，否则拒绝登录，避免接管本地账号
+    let user = match state.users.get_user_by_username(ctx.tenant.id, username.to_string()).await? {
+        Some(u) if ldap.match_local_username => u,
+        Some(_) => return Err(AuthixError::Conflict("auth.ldap_username_conflict".into())),
+        None if ldap.auto_create_user => {
+            let new_user = User {
+                id: 0,
+                tenant_id: ctx.tenant.id,
+                username: Some(username.to_string()),
+                phone: entry.first(&ldap.attributes.phone).map(str::to_string),
+                email: entry.first(&ldap.attributes.email).map(str::to_string),
+                password: unusable_password()?,
+                status: UserStatus::Active,
+                crt_by: Some(format!("ldap:{}", entry.dn)),
+            };
+            state.users.create_user(new_user).await?
+        }
+        None => return Err(AuthixError::InvalidCredentials("auth.account_not_found".into())),
+    };
+    state.users.link_identity(UserIdentity::new(&user, IdentityType::External, login_type, username).verified()).await?;
+    Ok(user)
+}
+
+/// 按组映射同步组织角色：命中时授予角色，不再命中时移出组织；owner 不受同步影响
+async fn sync_group_roles(
+    orgs: &dyn OrganizationProvider,
+    tenant_id: u64,
+    user_id: u64,
+    rules: &[LdapGroupRole],
+    groups: &[String],
+) -> AuthixResult<()> {
+    let mut synced = Vec::new();
+    for rule in rules {
+        if synced.contains(&rule.org_id) {
+            continue;
+        }
+        synced.push(rule.org_id);
+        if orgs.get_organization(tenant_id, rule.org_id).await?.is_none() {
+            continue;
+        }
+        let target = rules.iter()
+            .filter(|r| r.org_id == rule.org_id)
+            .find(|r| groups.iter().any(|g| g.eq_ignore_ascii_case(&r.group)))
+            .map(|r| r.role.as_str());
+        let current = orgs.get_membership(rule.org_id, user_id).await?;
+        match (target, current) {
+            (_, Some(m)) if m.role == ORG_ROLE_OWNER => {}
+            (Some(role), Some(m)) if m.role == role => {}
+            (Some(role), _) => orgs.save_membership(rule.org_id, user_id, role).await?,
+            (None, Some(_)) => orgs.remove_membership(rule.org_id, user_id).await?,
+            (None, None) => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::AuthixConfig,
+        organization::{Organization, ORG_ROLE_ADMIN, ORG_ROLE_MEMBER},
+        state::test_state,
+        tenant::TenantSettings,
+    };
+
+    const ALICE_DN: &str = "CN=Alice,OU=People,DC=example,DC=com";
+    const ADMINS: &str = "CN=Admins,OU=Groups,DC=example,DC=com";
+    const STAFF: &str = "CN=Staff,OU=Groups,DC=example,DC=com";
+
+    struct Fixture {
+        state: AppState,
+        ctx: TenantContext,
+        provider: LdapLoginProvider,
+    }
+
+    impl Fixture {
+        async fn new(configure: impl FnOnce(&mut LdapConfig)) -> Self {
+            let mut config = AuthixConfig::default();
+            config.ldap.url = "ldap://directory.test".into();
+            configure(&mut config.ldap);
+            let settings = TenantSettings::defaults(&config);
+            let state = test_state(config).await;
+            let tenant = state.tenants.get_tenant_by_code("default".into()).await.unwrap().expect("default tenant");
+
+            let directory = InMemoryDirectory::default();
+            let attrs = [
+                ("sAMAccountName", vec!["alice"]),
+                ("mail", vec!["alice@example.com"]),
+                ("memberOf", vec![ADMINS, STAFF]),
+            ];
+            let attrs = attrs.into_iter().map(|(k, v)| (k.to_string(), v.into_iter().map(str::to_string).collect())).collect();
+            directory.add_entry(LdapEntry { dn: ALICE_DN.into(), attrs }, "correct horse");
+            Fixture { state, ctx: TenantContext { tenant, settings }, provider: LdapLoginProvider::new(Arc::new(directory)) }
+        }
+
+        async fn login(&self, identifier: &str, credential: &str) -> AuthixResult<R<LoginResponse>> {
+            let req = LoginRequest {
+                login_type: "ldap".into(),
+                identifier: identifier.into(),
+                credential: credential.into(),
+                mfa_code: None,
+                extra: HashMap::new(),
+            };
+            self.provider.login(&self.ctx, &req, &self.state).await
+        }
+
+        async fn local_user(&self, username: &str) -> Option<User> {
+            self.state.users.get_user_by_username(self.ctx.tenant.id, username.into()).await.unwrap()
+        }
+
+        async fn create_local_user(&self, username: &str) -> User {
+            let user = User {
+                id: 0,
+                tenant_id: self.ctx.tenant.id,
+                username: Some(username.into()),
+                phone: None,
+                email: None,
+                password: unusable_password().unwrap(),
+                status: UserStatus::Active,
+                crt_by: None,
+            };
+            self.state.users.create_user(user).await.unwrap()
+        }
+    }
+
+    fn is_error(result: AuthixResult<impl std::fmt::Debug>, key: &str) -> bool {
+        match result {
+            Err(AuthixError::InvalidCredentials(m) | AuthixError::Conflict(m)) => m.key() == key,
+            _ => false,
+        }
+    }
+
+    #[tokio::test]
+    async fn searches_then_binds_with_user_dn() {
+        let fx = Fixture::new(|_| {}).await;
+        assert!(fx.login("ALICE", "correct horse").await.is_ok());
+        assert!(is_error(fx.login("alice", "wrong").await, "auth.invalid_credentials"));
+        assert!(is_error(fx.login("alice", "").await, "auth.invalid_credentials"));
+        assert!(is_error(fx.login("bob", "correct horse").await, "auth.invalid_credentials"));
+    }
+
+    #[tokio::test]
+    async fn provisions_user_on_first_login() {
+        let fx = Fixture::new(|_| {}).await;
+        fx.login("alice", "correct horse").await.unwrap();
+        let user = fx.local_user("alice").await.expect("provisioned user");
+        assert_eq!(user.email.as_deref(), Some("alice@example.com"));
+
+        // 再次登录按已关联的目录账号找到同一用户
+        fx.login("alice", "correct horse").await.unwrap();
+        let linked = fx.state.users.get_user_by_identity(fx.ctx.tenant.id, IdentityType::External, "ldap", "alice").await.unwrap();
+        assert_eq!(linked.map(|u| u.id), Some(user.id));
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_user_without_auto_create() {
+        let fx = Fixture::new(|ldap| ldap.auto_create_user = false).await;
+        assert!(is_error(fx.login("alice", "correct horse").await, "auth.account_not_found"));
+        assert!(fx.local_user("alice").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn does_not_take_over_same_named_local_user_by_default() {
+        let fx = Fixture::new(|_| {}).await;
+        fx.create_local_user("alice").await;
+        assert!(is_error(fx.login("alice", "correct horse").await, "auth.ldap_username_conflict"));
+    }
+
+    #[tokio::test]
+    async fn links_same_named_local_user_when_enabled() {
+        let fx = Fixture::new(|ldap| ldap.match_local_username = true).await;
+        let local = fx.create_local_user("alice").await;
+        fx.login("alice", "correct horse").await.unwrap();
+        let linked = fx.state.users.get_user_by_identity(fx.ctx.tenant.id, IdentityType::External, "ldap", "alice").await.unwrap();
+        assert_eq!(linked.map(|u| u.id), Some(local.id));
+    }
+
+    #[tokio::test]
+    async fn syncs_group_roles() {
+        let fx = Fixture::new(|_| {}).await;
+        let orgs = &*fx.state.orgs;
+        let tenant_id = fx.ctx.tenant.id;
+        let org = Organization { id: 0, tenant_id, name: "Engineering".into(), description: None, crt_by: None };
+        let org = orgs.create_organization(org).await.unwrap();
+        let rules = [
+            LdapGroupRole { group: ADMINS.into(), org_id: org.id, role: ORG_ROLE_ADMIN.into() },
+            LdapGroupRole { group: STAFF.into(), org_id: org.id, role: ORG_ROLE_MEMBER.into() },
+            LdapGroupRole { group: STAFF.into(), org_id: org.id + 100, role: ORG_ROLE_MEMBER.into() },
+        ];
+        let user = fx.create_local_user("alice").await;
+        let role = || async { orgs.get_membership(org.id, user.id).await.unwrap().map(|m| m.role) };
+
+        // 同一组织命中多条时取第一条，不存在的组织跳过
+        sync_group_roles(orgs, tenant_id, user.id, &rules, &[STAFF.to_lowercase(), ADMINS.into()]).await.unwrap();
+        assert_eq!(role().await.as_deref(), Some(ORG_ROLE_ADMIN));
+
+        sync_group_roles(orgs, tenant_id, user.id, &rules, &[STAFF.into()]).await.unwrap();
+        assert_eq!(role().await.as_deref(), Some(ORG_ROLE_MEMBER));
+
+        sync_group_roles(orgs, tenant_id, user.id, &rules, &[]).await.unwrap();
+        assert_eq!(role().await, None);
+
+        orgs.save_membership(org.id, user.id, ORG_ROLE_OWNER).await.unwrap();
+        sync_group_roles(orgs, tenant_id, user.id, &rules, &[]).await.unwrap();
+        assert_eq!(role().await.as_deref(), Some(ORG_ROLE_OWNER));
+    }
+
+    #[tokio::test]
+    async fn login_applies_group_roles() {
+        let fx = Fixture::new(|_| {}).await;
+        let org = Organization { id: 0, tenant_id: fx.ctx.tenant.id, name: "Engineering".into(), description: None, crt_by: None };
+        let org = fx.state.orgs.create_organization(org).await.unwrap();
+        let mut config = (*fx.state.config.get()).clone();
+        config.ldap.group_roles = vec![LdapGroupRole { group: ADMINS.into(), org_id: org.id, role: ORG_ROLE_ADMIN.into() }];
+        fx.state.config.replace(config);
+
+        fx.login("alice", "correct horse").await.unwrap();
+        let user = fx.local_user("alice").await.unwrap();
+        let membership = fx.state.orgs.get_membership(org.id, user.id).await.unwrap();
+        assert_eq!(membership.map(|m| m.role).as_deref(), Some(ORG_ROLE_ADMIN));
+    }
+}
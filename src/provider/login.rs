@@ -1,19 +1,40 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::{BTreeMap, HashMap}, sync::Arc};
 
 use axum::async_trait;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
-use crate::{common::R, enums::AuthType, errors::{AuthixError, AuthixResult}, provider::{EmailLoginProvider, PasswordLoginProvider, SmsLoginProvider}, user::UserProvider};
+use crate::{common::R, event::{emit, DomainEvent, EventPublisher}, errors::{AuthixError, AuthixResult}, i18n::Msg, provider::{schema::{has_value, FieldKind, RequestSchema}, social::{GitHub, Oidc, SocialLoginProvider, WeChat}, EmailLoginProvider, LdapLoginProvider, PasswordLoginProvider, SmsLoginProvider}, state::AppState, tenant::TenantContext};
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct LoginRequest {
-    pub login_type: String,    // "password" | "sms" | "email"
-    pub identifier: String,    // 用户名/手机号/邮箱
+    pub login_type: String,    // 已注册的登录方式，内置 "password" | "sms" | "email" | "ldap" 及第三方登录
+    #[serde(default)]
+    pub identifier: String,    // 用户名/手机号/邮箱，第三方登录不需要
+    #[serde(default)]
     pub credential: String,    // 密码/验证码
+    #[serde(default)]
+    pub mfa_code: Option<String>, // 租户开启 MFA 时密码登录需携带的短信/邮箱验证码
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>, // 自定义登录方式声明的其他字段
+}
+
+impl LoginRequest {
+    /// 字段是否已填写，用于按 `RequestSchema` 校验请求
+    pub fn has_field(&self, name: &str) -> bool {
+        match name {
+            "identifier" => !self.identifier.is_empty(),
+            "credential" => !self.credential.is_empty(),
+            "mfa_code" => self.mfa_code.as_deref().is_some_and(|c| !c.is_empty()),
+            _ => has_value(self.extra.get(name)),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
 pub struct LoginResponse {
+    #[serde(skip)]
+    pub uid: u64,               // 登录用户 id，仅用于审计，不返回给客户端
     pub access_token: String,
     pub refresh_token: String,
     pub exp: usize,
@@ -22,31 +43,200 @@ pub struct LoginResponse {
 
 #[async_trait]
 pub trait LoginProvider: Send + Sync {
-    async fn login(&self, req: &LoginRequest, user_service: Arc<dyn UserProvider>) -> AuthixResult<R<LoginResponse>>;
+    async fn login(&self, ctx: &TenantContext, req: &LoginRequest, state: &AppState) -> AuthixResult<R<LoginResponse>>;
+
+    /// 请求字段声明，默认为必填的 `identifier` 与 `credential`
+    fn schema(&self) -> RequestSchema {
+        RequestSchema::new().required("identifier", FieldKind::Text).required("credential", FieldKind::Secret)
+    }
+
+    /// 需要先跳转外部身份提供方的登录方式返回授权地址，默认不支持跳转
+    async fn authorize(&self, _ctx: &TenantContext, _login_type: &str, _redirect_uri: &str, _state: &AppState) -> AuthixResult<Option<String>> {
+        Ok(None)
+    }
+
+    /// 校验凭据并返回外部身份标识，用于已登录用户关联第三方身份，默认不支持关联
+    async fn identify(&self, _ctx: &TenantContext, _req: &LoginRequest, _state: &AppState) -> AuthixResult<Option<String>> {
+        Ok(None)
+    }
 }
 
-/// 登录服务，负责调度不同 Provider
+/// 登录服务，按登录方式标识调度已注册的 Provider，并发布登录事件
 pub struct LoginService {
-    providers: HashMap<AuthType, Box<dyn LoginProvider>>,
+    providers: BTreeMap<String, Arc<dyn LoginProvider>>,
+    publisher: Arc<dyn EventPublisher>,
+}
+
+/// `LoginService` 构建器，`default()` 已注册内置的 password、sms、email、ldap
+/// 及第三方登录 github、google、wechat、oidc
+pub struct LoginServiceBuilder {
+    providers: BTreeMap<String, Arc<dyn LoginProvider>>,
 }
 
-impl Default for LoginService {
+impl Default for LoginServiceBuilder {
     fn default() -> Self {
-        let mut providers: HashMap<AuthType, Box<dyn LoginProvider>> = HashMap::new();
-        providers.insert(AuthType::Password, Box::new(PasswordLoginProvider));
-        providers.insert(AuthType::Sms, Box::new(SmsLoginProvider));
-        providers.insert(AuthType::Email, Box::new(EmailLoginProvider));
-        Self { providers }
+        Self::empty()
+            .provider("password", Arc::new(PasswordLoginProvider))
+            .provider("sms", Arc::new(SmsLoginProvider))
+            .provider("email", Arc::new(EmailLoginProvider))
+            .provider("ldap", Arc::new(LdapLoginProvider::default()))
+            .provider("github", Arc::new(SocialLoginProvider::new(Arc::new(GitHub))))
+            .provider("google", Arc::new(SocialLoginProvider::new(Arc::new(Oidc::google()))))
+            .provider("wechat", Arc::new(SocialLoginProvider::new(Arc::new(WeChat))))
+            .provider("oidc", Arc::new(SocialLoginProvider::new(Arc::new(Oidc::generic()))))
+    }
+}
+
+impl LoginServiceBuilder {
+    /// 不含任何内置登录方式
+    pub fn empty() -> Self {
+        Self { providers: BTreeMap::new() }
+    }
+
+    /// 注册登录方式，同名时替换已有的 Provider
+    pub fn provider(mut self, login_type: impl Into<String>, provider: Arc<dyn LoginProvider>) -> Self {
+        self.providers.insert(login_type.into(), provider);
+        self
+    }
+
+    pub fn build(self, publisher: Arc<dyn EventPublisher>) -> LoginService {
+        LoginService { providers: self.providers, publisher }
+    }
+}
+
+impl LoginService {
+    pub fn builder() -> LoginServiceBuilder {
+        LoginServiceBuilder::default()
+    }
+
+    pub fn new(publisher: Arc<dyn EventPublisher>) -> Self {
+        Self::builder().build(publisher)
+    }
+
+    pub fn supports(&self, login_type: &str) -> bool {
+        self.providers.contains_key(login_type)
+    }
+
+    /// 获取第三方登录的授权地址，登录方式不支持跳转时返回参数错误
+    pub async fn authorize_url(&self, ctx: &TenantContext, login_type: &str, redirect_uri: &str, state: &AppState) -> AuthixResult<String> {
+        let provider = self.enabled_provider(ctx, login_type, state)?;
+        match provider.authorize(ctx, login_type, redirect_uri, state).await? {
+            Some(url) => Ok(url),
+            None => Err(AuthixError::InvalidParameter(Msg::new("auth.authorize_unsupported").arg("type", login_type))),
+        }
+    }
+
+    /// 校验第三方凭据并返回外部身份标识，登录方式不支持关联时返回参数错误
+    pub async fn identify(&self, ctx: &TenantContext, req: &LoginRequest, state: &AppState) -> AuthixResult<String> {
+        let provider = self.checked_provider(ctx, req, state)?;
+        match provider.identify(ctx, req, state).await? {
+            Some(subject) => Ok(subject),
+            None => Err(AuthixError::InvalidParameter(Msg::new("user.identity_link_unsupported").arg("type", &req.login_type))),
+        }
+    }
+
+    /// 已启用且请求已填写全部必填字段的 Provider
+    fn checked_provider(&self, ctx: &TenantContext, req: &LoginRequest, state: &AppState) -> AuthixResult<&Arc<dyn LoginProvider>> {
+        let provider = self.enabled_provider(ctx, &req.login_type, state)?;
+        if let Some(field) = provider.schema().missing(|name| req.has_field(name)) {
+            return Err(AuthixError::InvalidParameter(Msg::new("param.field_required").arg("field", field)));
+        }
+        Ok(provider)
+    }
+
+    /// 已注册且在全局、租户配置中均已启用的 Provider
+    fn enabled_provider(&self, ctx: &TenantContext, login_type: &str, state: &AppState) -> AuthixResult<&Arc<dyn LoginProvider>> {
+        let Some(provider) = self.providers.get(login_type) else {
+            return Err(AuthixError::UnknowLoginType(Msg::new("auth.unknown_login_type").arg("type", login_type)));
+        };
+        if !state.config.get().providers.login_type_enabled(login_type) {
+            return Err(AuthixError::LoginTypeDisabled(Msg::new("auth.login_type_disabled").arg("type", login_type)));
+        }
+        if !ctx.settings.login_type_enabled(login_type) {
+            return Err(AuthixError::LoginTypeDisabled(Msg::new("auth.tenant_login_type_disabled").arg("type", login_type)));
+        }
+        Ok(provider)
+    }
+
+    /// 已注册的登录方式及其请求字段，按标识排序
+    pub fn schemas(&self) -> impl Iterator<Item = (&str, RequestSchema)> {
+        self.providers.iter().map(|(t, p)| (t.as_str(), p.schema()))
     }
 }
 
 #[async_trait]
 impl LoginProvider for LoginService {
-    async fn login(&self, req: &LoginRequest, user_service: Arc<dyn UserProvider>) -> AuthixResult<R<LoginResponse>> {
-        if let Some(provider) = self.providers.get(&AuthType::from(req.login_type.clone())) {
-            provider.login(&req, user_service).await
-        } else {
-            Err(AuthixError::UnknowLoginType(format!("未知的登录方式: {}", req.login_type.clone())))
+    async fn login(&self, ctx: &TenantContext, req: &LoginRequest, state: &AppState) -> AuthixResult<R<LoginResponse>> {
+        let provider = self.checked_provider(ctx, req, state)?;
+        let result = provider.login(ctx, req, state).await;
+        let event = match &result {
+            Ok(resp) => resp.data.as_ref().map(|r| DomainEvent::UserLoggedIn { user_id: r.uid, login_type: req.login_type.clone() }),
+            Err(e) => Some(DomainEvent::UserLoginFailed { login_type: req.login_type.clone(), reason: e.to_string() }),
+        };
+        if let Some(event) = event {
+            emit(&self.publisher, ctx.tenant.id, event).await;
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{config::AuthixConfig, event::InMemoryEventPublisher, state::test_state};
+
+    /// 工牌登录：声明自有字段 `badge_id`，`badge_id` 为 "lost" 时失败
+    struct BadgeLoginProvider;
+
+    #[async_trait]
+    impl LoginProvider for BadgeLoginProvider {
+        async fn login(&self, _ctx: &TenantContext, req: &LoginRequest, _state: &AppState) -> AuthixResult<R<LoginResponse>> {
+            if req.extra.get("badge_id") == Some(&json!("lost")) {
+                return Err(AuthixError::InvalidCredentials("auth.invalid_credentials".into()));
+            }
+            Ok(R::ok_data(LoginResponse { uid: 42, access_token: "a".into(), refresh_token: "r".into(), exp: 0, iat: 0 }))
+        }
+
+        fn schema(&self) -> RequestSchema {
+            RequestSchema::new().required("badge_id", FieldKind::Text)
         }
     }
-}
\ No newline at end of file
+
+    fn request(value: serde_json::Value) -> LoginRequest {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[tokio::test]
+    async fn custom_provider_is_dispatched_by_name() {
+        let mut config = AuthixConfig::default();
+        config.providers.login_types.push("badge".into());
+        let state = test_state(config).await;
+        let ctx = TenantContext {
+            tenant: state.tenants.get_tenant_by_id(1).await.unwrap().unwrap(),
+            settings: state.tenants.get_tenant_settings(1).await.unwrap(),
+        };
+        let events = Arc::new(InMemoryEventPublisher::default());
+        let service = LoginService::builder().provider("badge", Arc::new(BadgeLoginProvider)).build(events.clone());
+        assert!(service.supports("badge") && service.supports("password"));
+        assert!(service.schemas().any(|(t, s)| t == "badge" && s.fields[0].name == "badge_id"));
+
+        let resp = service.login(&ctx, &request(json!({ "login_type": "badge", "badge_id": "b-1" })), &state).await.unwrap();
+        assert_eq!(resp.data.unwrap().uid, 42);
+        assert!(service.login(&ctx, &request(json!({ "login_type": "badge", "badge_id": "lost" })), &state).await.is_err());
+        let types: Vec<_> = events.events().iter().map(|e| e.event.event_type()).collect();
+        assert_eq!(types, ["user.logged_in", "user.login_failed"]);
+
+        // 缺少声明的必填字段、未注册或未启用的登录方式均被拒绝，不发布事件
+        let err = service.login(&ctx, &request(json!({ "login_type": "badge", "badge_id": "" })), &state).await.unwrap_err();
+        assert!(matches!(err, AuthixError::InvalidParameter(_)));
+        let err = service.login(&ctx, &request(json!({ "login_type": "palm", "identifier": "a", "credential": "b" })), &state).await.unwrap_err();
+        assert!(matches!(err, AuthixError::UnknowLoginType(_)));
+        let disabled = LoginServiceBuilder::empty().provider("palm", Arc::new(BadgeLoginProvider)).build(events.clone());
+        let err = disabled.login(&ctx, &request(json!({ "login_type": "palm", "badge_id": "b-1" })), &state).await.unwrap_err();
+        assert!(matches!(err, AuthixError::LoginTypeDisabled(_)));
+        assert!(!disabled.supports("password"));
+        assert_eq!(events.events().len(), 2);
+    }
+}
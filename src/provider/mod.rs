@@ -1,9 +1,27 @@
+use argon2::{password_hash::{rand_core::OsRng, PasswordHasher, SaltString}, Argon2};
+
+use crate::errors::{AuthixError, AuthixResult};
+
 mod email;
+mod ldap;
 mod password;
 mod sms;
 pub mod login;
 pub mod register;
+pub mod schema;
+pub mod social;
 
 pub use email::{ EmailLoginProvider, EmailRegisterProvider };
+pub use ldap::{InMemoryDirectory, Ldap3Directory, LdapDirectory, LdapEntry, LdapLoginProvider};
 pub use password::{PasswordLoginProvider, PasswordRegisterProvider};
-pub use sms::{SmsLoginProvider, SmsRegisterProvider};
\ No newline at end of file
+pub use sms::{SmsLoginProvider, SmsRegisterProvider};
+pub use schema::{FieldKind, RequestSchema, SchemaField};
+
+/// 目录、第三方登录用户的本地密码，随机生成且不对外返回，使密码登录无法使用
+pub(crate) fn unusable_password() -> AuthixResult<String> {
+    let secret: [u8; 32] = rand::random();
+    Argon2::default()
+        .hash_password(&secret, &SaltString::generate(&mut OsRng))
+        .map(|h| h.to_string())
+        .map_err(|e| AuthixError::Internal(format!("hash password error: {}", e)))
+}
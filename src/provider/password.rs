@@ -1,75 +1,88 @@
-use std::sync::Arc;
-
 use axum::async_trait;
 use argon2::{password_hash::{rand_core::OsRng, PasswordHasher, SaltString}, Argon2, PasswordHash, PasswordVerifier};
 
-use crate::{common::R, errors::{AuthixError, AuthixResult}, provider::{login::{LoginProvider, LoginRequest, LoginResponse}, register::{RegisterProvider, RegisterRequest}}, user::{User, UserProvider}, utils::{jwt, regex::{is_valid_password, is_valid_username}}};
+use crate::{common::R, enums::{AuthEnum, UserStatus}, errors::{AuthixError, AuthixResult}, provider::{login::{LoginProvider, LoginRequest, LoginResponse}, register::{RegisterProvider, RegisterRequest}, schema::{FieldKind, RequestSchema}}, state::AppState, tenant::TenantContext, user::User, utils::{jwt, regex::is_valid_username}};
 
 pub struct PasswordLoginProvider;
 
 #[async_trait]
 impl LoginProvider for PasswordLoginProvider {
-    async fn login(&self, req: &LoginRequest, user_service: Arc<dyn UserProvider>) -> AuthixResult<R<LoginResponse>> {
+    async fn login(&self, ctx: &TenantContext, req: &LoginRequest, state: &AppState) -> AuthixResult<R<LoginResponse>> {
         // 通过用户名加载用户
-        let user = match user_service
-            .get_user_by_username(req.identifier.clone())
+        let user = match state.users
+            .get_user_by_username(ctx.tenant.id, req.identifier.clone())
             .await? {
                 Some(u) => u,
-                None => return Err(AuthixError::InvalidCredentials("用户名或密码错误".into())),
+                None => return Err(AuthixError::InvalidCredentials("auth.invalid_credentials".into())),
             };
 
         // 使用 argon2 校验密码（user.password 应存储为 PHC 字符串）
         let parsed_hash = PasswordHash::new(&user.password)
-            .map_err(|e| AuthixError::InvalidCredentials(format!("密码加密失败, {}", e)))?;
+            .map_err(|e| AuthixError::Internal(format!("invalid password hash for user {}: {}", user.id, e)))?;
         let argon2 = Argon2::default();
         if argon2.verify_password(req.credential.as_bytes(), &parsed_hash).is_err() {
-            return Err(AuthixError::InvalidCredentials("用户名或密码错误".into()));
+            return Err(AuthixError::InvalidCredentials("auth.invalid_credentials".into()));
+        }
+        user.ensure_can_login()?;
+
+        // 租户要求 MFA 时校验绑定手机号/邮箱的验证码
+        if ctx.settings.mfa_required {
+            let target = match user.phone.as_deref().or(user.email.as_deref()) {
+                Some(t) => t,
+                None => return Err(AuthixError::MfaRequired("auth.mfa_unbound".into())),
+            };
+            let code = match req.mfa_code.as_deref() {
+                Some(c) if !c.is_empty() => c,
+                _ => return Err(AuthixError::MfaRequired("auth.mfa_code_required".into())),
+            };
+            crate::cache::verify_code(&*state.codes, ctx.tenant.id, target, code, AuthEnum::Login, state.config.get().verify_code.ttl_secs).await?;
         }
 
         // 使用用户 id 作为 sub 生成 token
-        let resp = jwt::create_token(user.id.to_string(), "0".to_string()).await?;
+        let resp = jwt::create_token(state, user.id.to_string(), user.tenant_id.to_string(), None, ctx.settings.access_token_exp, ctx.settings.refresh_token_exp).await?;
 
         // 更新最后登录时间
-        let _ = user_service.update_last_login_time(user.id).await?;
+        let _ = state.users.update_last_login_time(user.id).await?;
 
         Ok(R::ok_data(resp))
     }
+
+    fn schema(&self) -> RequestSchema {
+        RequestSchema::new().required("identifier", FieldKind::Text).required("credential", FieldKind::Secret).optional("mfa_code", FieldKind::Code)
+    }
 }
 
 pub struct PasswordRegisterProvider;
 #[async_trait]
 impl RegisterProvider for PasswordRegisterProvider {
-    async fn register(&self, req: &RegisterRequest, user_service: Arc<dyn UserProvider>) -> AuthixResult<R<u64>> {
+    async fn register(&self, ctx: &TenantContext, req: &RegisterRequest, state: &AppState) -> AuthixResult<R<u64>> {
         // 参数校验
         if !is_valid_username(&req.identifier) {
-            return Ok(R::error(400, "用户名不合法".into()));
-        }
-        if !is_valid_password(&req.credential) {
-            return Ok(R::error(400, "密码不合法(8-32位，支持字母数字常见符号)".into()));
+            return Err(AuthixError::InvalidParameter("param.username_invalid".into()));
         }
+        ctx.settings.password_policy.check(&req.credential).map_err(AuthixError::InvalidParameter)?;
         // 唯一性校验
-        if let Some(_) = user_service.get_user_by_username(req.identifier.clone()).await? {
-            return Ok(R::error(409, "username already exists".into()))
+        if state.users.get_user_by_username(ctx.tenant.id, req.identifier.clone()).await?.is_some() {
+            return Err(AuthixError::UserAlreadyExists("user.username_exists".into()));
         }
         // hash 密码
         let argon2 = Argon2::default();
         let password_hash = argon2
             .hash_password(req.credential.as_bytes(), &SaltString::generate(&mut OsRng))
             .map(|h| h.to_string())
-            .map_err(|e| AuthixError::InvalidCredentials(format!("hash password error: {}", e)))?;
+            .map_err(|e| AuthixError::Internal(format!("hash password error: {}", e)))?;
 
         let new_user = User {
             id: 0,
-            tenant_id: 0,
+            tenant_id: ctx.tenant.id,
             username: Some(req.identifier.clone()),
             phone: None,
             email: None,
             password: password_hash,
+            status: UserStatus::Active,
             crt_by: Some(req.identifier.clone()),
         };
-        match user_service.create_user(new_user).await {
-            Ok(u) => Ok(R::ok_data(u.id)),
-            Err(e) => Ok(R::error(500, e)),
-        }
+        let user = state.users.create_user(new_user).await?;
+        Ok(R::ok_data(user.id))
     }
 }
\ No newline at end of file
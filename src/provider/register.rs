@@ -1,43 +1,125 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::{BTreeMap, HashMap}, sync::Arc};
 
 use axum::async_trait;
 use serde::Deserialize;
+use serde_json::Value;
 
-use crate::{common::R, enums::AuthType, errors::{AuthixError, AuthixResult}, provider::{EmailRegisterProvider, PasswordRegisterProvider, SmsRegisterProvider}, user::UserProvider};
+use crate::{common::R, event::{emit, DomainEvent, EventPublisher}, errors::{AuthixError, AuthixResult}, i18n::Msg, provider::{schema::{has_value, FieldKind, RequestSchema}, EmailRegisterProvider, PasswordRegisterProvider, SmsRegisterProvider}, state::AppState, tenant::TenantContext};
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct RegisterRequest {
-    pub register_type: String,  // "password" | "sms" | "email"
+    pub register_type: String,  // 已注册的注册方式，内置 "password" | "sms" | "email"
     pub identifier: String,     // 用户名/手机号/邮箱
     pub credential: String,     // 密码
+    #[serde(default)]
+    pub invitation_token: Option<String>, // 组织邀请码
+    #[serde(skip)]
+    pub invited: bool,          // 邀请已校验，跳过验证码注册标识与自助注册开关
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>, // 自定义注册方式声明的其他字段
+}
+
+impl RegisterRequest {
+    /// 字段是否已填写，用于按 `RequestSchema` 校验请求
+    pub fn has_field(&self, name: &str) -> bool {
+        match name {
+            "identifier" => !self.identifier.is_empty(),
+            "credential" => !self.credential.is_empty(),
+            "invitation_token" => self.invitation_token.as_deref().is_some_and(|t| !t.is_empty()),
+            _ => has_value(self.extra.get(name)),
+        }
+    }
 }
 
 #[async_trait]
 pub trait RegisterProvider: Send + Sync {
-    async fn register(&self, req: &RegisterRequest, user_service: Arc<dyn UserProvider>) -> AuthixResult<R<u64>>;
+    async fn register(&self, ctx: &TenantContext, req: &RegisterRequest, state: &AppState) -> AuthixResult<R<u64>>;
+
+    /// 请求字段声明，默认为必填的 `identifier` 与 `credential`
+    fn schema(&self) -> RequestSchema {
+        RequestSchema::new().required("identifier", FieldKind::Text).required("credential", FieldKind::Secret)
+    }
 }
 
+/// 注册服务，按注册方式标识调度已注册的 Provider，并发布注册事件
 pub struct RegisterService {
-    providers: HashMap<AuthType, Box<dyn RegisterProvider>>,
+    providers: BTreeMap<String, Arc<dyn RegisterProvider>>,
+    publisher: Arc<dyn EventPublisher>,
+}
+
+/// `RegisterService` 构建器，`default()` 已注册内置的 password、sms、email
+pub struct RegisterServiceBuilder {
+    providers: BTreeMap<String, Arc<dyn RegisterProvider>>,
 }
 
-impl Default for RegisterService {
+impl Default for RegisterServiceBuilder {
     fn default() -> Self {
-        let mut providers: HashMap<AuthType, Box<dyn RegisterProvider>> = HashMap::new();
-        providers.insert(AuthType::Password, Box::new(PasswordRegisterProvider));
-        providers.insert(AuthType::Sms, Box::new(SmsRegisterProvider));
-        providers.insert(AuthType::Email, Box::new(EmailRegisterProvider));
-        Self { providers }
+        Self::empty()
+            .provider("password", Arc::new(PasswordRegisterProvider))
+            .provider("sms", Arc::new(SmsRegisterProvider))
+            .provider("email", Arc::new(EmailRegisterProvider))
+    }
+}
+
+impl RegisterServiceBuilder {
+    /// 不含任何内置注册方式
+    pub fn empty() -> Self {
+        Self { providers: BTreeMap::new() }
+    }
+
+    /// 注册注册方式，同名时替换已有的 Provider
+    pub fn provider(mut self, register_type: impl Into<String>, provider: Arc<dyn RegisterProvider>) -> Self {
+        self.providers.insert(register_type.into(), provider);
+        self
+    }
+
+    pub fn build(self, publisher: Arc<dyn EventPublisher>) -> RegisterService {
+        RegisterService { providers: self.providers, publisher }
+    }
+}
+
+impl RegisterService {
+    pub fn builder() -> RegisterServiceBuilder {
+        RegisterServiceBuilder::default()
+    }
+
+    pub fn new(publisher: Arc<dyn EventPublisher>) -> Self {
+        Self::builder().build(publisher)
+    }
+
+    pub fn supports(&self, register_type: &str) -> bool {
+        self.providers.contains_key(register_type)
+    }
+
+    /// 已注册的注册方式及其请求字段，按标识排序
+    pub fn schemas(&self) -> impl Iterator<Item = (&str, RequestSchema)> {
+        self.providers.iter().map(|(t, p)| (t.as_str(), p.schema()))
     }
 }
 
 #[async_trait]
 impl RegisterProvider for RegisterService {
-    async fn register(&self, req: &RegisterRequest, user_service: Arc<dyn UserProvider>) -> AuthixResult<R<u64>> {
-        if let Some(provider) = self.providers.get(&AuthType::from(req.register_type.clone())) {
-            provider.register(&req, user_service).await
-        } else {
-            Err(AuthixError::UnknowRegisterType(format!("未知的注册方式: {}", req.register_type.clone())))
+    async fn register(&self, ctx: &TenantContext, req: &RegisterRequest, state: &AppState) -> AuthixResult<R<u64>> {
+        let Some(provider) = self.providers.get(&req.register_type) else {
+            return Err(AuthixError::UnknowRegisterType(Msg::new("auth.unknown_register_type").arg("type", &req.register_type)));
+        };
+        if !ctx.settings.allow_register && !req.invited {
+            return Err(AuthixError::RegisterDisabled("auth.register_closed".into()));
+        }
+        if !state.config.get().providers.register_type_enabled(&req.register_type) {
+            return Err(AuthixError::RegisterDisabled(Msg::new("auth.register_type_disabled").arg("type", &req.register_type)));
+        }
+        if !ctx.settings.register_type_enabled(&req.register_type) {
+            return Err(AuthixError::RegisterDisabled(Msg::new("auth.tenant_register_type_disabled").arg("type", &req.register_type)));
         }
+        if let Some(field) = provider.schema().missing(|name| req.has_field(name)) {
+            return Err(AuthixError::InvalidParameter(Msg::new("param.field_required").arg("field", field)));
+        }
+        let resp = provider.register(ctx, req, state).await?;
+        if let Some(user_id) = resp.data {
+            let event = DomainEvent::UserRegistered { user_id, register_type: req.register_type.clone(), invited: req.invited };
+            emit(&self.publisher, ctx.tenant.id, event).await;
+        }
+        Ok(resp)
     }
-}
\ No newline at end of file
+}
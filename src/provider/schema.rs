@@ -0,0 +1,60 @@
+use serde::Serialize;
+use serde_json::Value;
+
+/// 字段类型，供客户端选择输入控件
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldKind {
+    Text,
+    Secret,
+    Phone,
+    Email,
+    Code,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SchemaField {
+    pub name: String,
+    pub kind: FieldKind,
+    pub required: bool,
+}
+
+/// 登录/注册请求的字段声明。`identifier`、`credential` 之外的字段直接放在请求体顶层，
+/// 由 Provider 通过 `extra` 读取
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RequestSchema {
+    pub fields: Vec<SchemaField>,
+}
+
+impl RequestSchema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn required(self, name: &str, kind: FieldKind) -> Self {
+        self.field(name, kind, true)
+    }
+
+    pub fn optional(self, name: &str, kind: FieldKind) -> Self {
+        self.field(name, kind, false)
+    }
+
+    fn field(mut self, name: &str, kind: FieldKind, required: bool) -> Self {
+        self.fields.push(SchemaField { name: name.to_string(), kind, required });
+        self
+    }
+
+    /// 返回第一个缺失的必填字段
+    pub fn missing(&self, present: impl Fn(&str) -> bool) -> Option<&str> {
+        self.fields.iter().find(|f| f.required && !present(&f.name)).map(|f| f.name.as_str())
+    }
+}
+
+/// 扩展字段是否有值，空字符串视为未填写
+pub(crate) fn has_value(value: Option<&Value>) -> bool {
+    match value {
+        None | Some(Value::Null) => false,
+        Some(Value::String(s)) => !s.is_empty(),
+        Some(_) => true,
+    }
+}
@@ -0,0 +1,58 @@
+use axum::async_trait;
+use reqwest::{Client, Url};
+use serde::Deserialize;
+
+use crate::{errors::{AuthixError, AuthixResult}, tenant::settings::SocialProviderSettings};
+
+use super::{exchange_code, rejected, upstream, AuthorizeState, ExternalIdentity, IdentityProvider};
+
+const AUTHORIZE_URL: &str = "https://github.com/login/oauth/authorize";
+const TOKEN_URL: &str = "https://github.com/login/oauth/access_token";
+/// 用户信息接口，邮箱列表为 `{userinfo_url}/emails`
+const USER_URL: &str = "https://api.github.com/user";
+const DEFAULT_SCOPES: [&str; 2] = ["read:user", "user:email"];
+
+#[derive(Debug, Deserialize)]
+struct GitHubUser {
+    id: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubEmail {
+    email: String,
+    primary: bool,
+    verified: bool,
+}
+
+/// GitHub OAuth App，用户标识为数字 id，邮箱取已验证的主邮箱
+pub struct GitHub;
+
+#[async_trait]
+impl IdentityProvider for GitHub {
+    async fn authorize_url(&self, _http: &Client, settings: &SocialProviderSettings, auth: &AuthorizeState, state: &str) -> AuthixResult<String> {
+        let endpoint = settings.authorize_url.as_deref().unwrap_or(AUTHORIZE_URL);
+        let scope = if settings.scopes.is_empty() { DEFAULT_SCOPES.join(" ") } else { settings.scopes.join(" ") };
+        let url = Url::parse_with_params(endpoint, [
+            ("client_id", settings.client_id.as_str()),
+            ("redirect_uri", auth.redirect_uri.as_str()),
+            ("scope", scope.as_str()),
+            ("state", state),
+        ]).map_err(|e| AuthixError::Internal(format!("invalid authorize endpoint {}: {}", endpoint, e)))?;
+        Ok(url.into())
+    }
+
+    async fn exchange(&self, http: &Client, settings: &SocialProviderSettings, auth: &AuthorizeState, code: &str) -> AuthixResult<ExternalIdentity> {
+        let token = exchange_code(http, settings.token_url.as_deref().unwrap_or(TOKEN_URL), settings, auth, code, false).await?;
+        let access_token = token.access_token.unwrap_or_default();
+        let user_url = settings.userinfo_url.as_deref().unwrap_or(USER_URL);
+        let user: GitHubUser = http.get(user_url).bearer_auth(&access_token).send().await.and_then(|r| r.error_for_status()).map_err(upstream)?
+            .json().await.map_err(rejected)?;
+        // 未授权 user:email 时邮箱列表不可读，按无邮箱处理
+        let emails: Vec<GitHubEmail> = match http.get(format!("{}/emails", user_url)).bearer_auth(&access_token).send().await.map_err(upstream)? {
+            resp if resp.status().is_success() => resp.json().await.unwrap_or_default(),
+            _ => Vec::new(),
+        };
+        let email = emails.into_iter().find(|e| e.primary && e.verified).map(|e| e.email);
+        Ok(ExternalIdentity { subject: user.id.to_string(), email_verified: email.is_some(), email })
+    }
+}
@@ -0,0 +1,237 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{
+    common::R,
+    enums::{IdentityType, UserStatus},
+    errors::{AuthixError, AuthixResult},
+    i18n::Msg,
+    provider::{login::{LoginProvider, LoginRequest, LoginResponse}, schema::{FieldKind, RequestSchema}, unusable_password},
+    state::AppState,
+    tenant::{settings::SocialProviderSettings, TenantContext},
+    user::{User, UserIdentity},
+    utils::jwt,
+};
+
+mod github;
+mod oidc;
+mod wechat;
+
+pub use github::GitHub;
+pub use oidc::Oidc;
+pub use wechat::WeChat;
+
+const SOCIAL_STATE_KEY: &str = "social_state";
+/// 授权状态有效期（秒），需在此时间内完成第三方授权并回调登录
+const SOCIAL_STATE_SEC_TTL: u64 = 600;
+const REQUEST_TIMEOUT_SECS: u64 = 10;
+
+/// 外部身份提供方返回的用户信息
+#[derive(Debug, Clone, Default)]
+pub struct ExternalIdentity {
+    /// 身份提供方内的唯一标识
+    pub subject: String,
+    pub email: Option<String>,
+    pub email_verified: bool,
+}
+
+/// 发起授权时保存的状态，回调登录时一次性取出
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthorizeState {
+    pub tenant_id: u64,
+    pub login_type: String,
+    pub redirect_uri: String,
+    pub nonce: String,
+    /// PKCE 校验码，支持 PKCE 的身份提供方使用
+    pub code_verifier: String,
+}
+
+/// OAuth2 / OIDC 身份提供方
+#[async_trait]
+pub trait IdentityProvider: Send + Sync {
+    /// 拼接跳转身份提供方的授权地址，`state` 为回调时携带的授权状态标识
+    async fn authorize_url(&self, http: &Client, settings: &SocialProviderSettings, auth: &AuthorizeState, state: &str) -> AuthixResult<String>;
+    /// 用授权码换取用户信息
+    async fn exchange(&self, http: &Client, settings: &SocialProviderSettings, auth: &AuthorizeState, code: &str) -> AuthixResult<ExternalIdentity>;
+}
+
+pub(crate) fn upstream(e: reqwest::Error) -> AuthixError {
+    AuthixError::UpstreamUnavailable(format!("identity provider: {}", e))
+}
+
+/// 身份提供方拒绝授权码或返回的凭据校验失败
+pub(crate) fn rejected(reason: impl std::fmt::Display) -> AuthixError {
+    warn!("social login rejected: {}", reason);
+    AuthixError::InvalidCredentials("auth.social_rejected".into())
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct TokenResponse {
+    pub access_token: Option<String>,
+    pub id_token: Option<String>,
+    pub error: Option<String>,
+}
+
+/// 标准 OAuth2 授权码换取令牌，`pkce` 为真时携带 `code_verifier`
+pub(crate) async fn exchange_code(
+    http: &Client,
+    token_url: &str,
+    settings: &SocialProviderSettings,
+    auth: &AuthorizeState,
+    code: &str,
+    pkce: bool,
+) -> AuthixResult<TokenResponse> {
+    let mut form = vec![
+        ("grant_type", "authorization_code"),
+        ("code", code),
+        ("redirect_uri", &auth.redirect_uri),
+        ("client_id", &settings.client_id),
+        ("client_secret", &settings.client_secret),
+    ];
+    if pkce {
+        form.push(("code_verifier", &auth.code_verifier));
+    }
+    let resp = http.post(token_url).header(reqwest::header::ACCEPT, "application/json").form(&form).send().await.map_err(upstream)?;
+    if resp.status().is_server_error() {
+        return Err(AuthixError::UpstreamUnavailable(format!("identity provider token endpoint returned {}", resp.status())));
+    }
+    let status = resp.status();
+    let token: TokenResponse = resp.json().await.map_err(rejected)?;
+    match (&token.error, &token.access_token) {
+        (None, Some(_)) if status.is_success() => Ok(token),
+        _ => Err(rejected(format!("token endpoint returned {} {:?}", status, token.error))),
+    }
+}
+
+/// 第三方登录：先通过 `authorize` 跳转身份提供方，回调后以授权码和授权状态登录
+pub struct SocialLoginProvider {
+    idp: Arc<dyn IdentityProvider>,
+    http: Client,
+}
+
+impl SocialLoginProvider {
+    pub fn new(idp: Arc<dyn IdentityProvider>) -> Self {
+        let http = Client::builder()
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .user_agent("authix")
+            .build()
+            .unwrap_or_default();
+        Self { idp, http }
+    }
+
+    fn settings<'a>(ctx: &'a TenantContext, login_type: &str) -> AuthixResult<&'a SocialProviderSettings> {
+        ctx.settings.social.get(login_type)
+            .ok_or_else(|| AuthixError::LoginTypeDisabled(Msg::new("auth.social_not_configured").arg("type", login_type)))
+    }
+
+    /// 取出授权状态并以授权码换取第三方身份
+    async fn external_identity(&self, ctx: &TenantContext, req: &LoginRequest, state: &AppState) -> AuthixResult<ExternalIdentity> {
+        let settings = Self::settings(ctx, &req.login_type)?;
+        let field = |name: &str| req.extra.get(name).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+        let (code, key) = (field("code"), format!("{}:{}", SOCIAL_STATE_KEY, field("state")));
+
+        // 授权状态只能使用一次
+        let stored = state.codes.get(&key).await?;
+        state.codes.delete(&key).await?;
+        let auth = stored
+            .and_then(|json| serde_json::from_str::<AuthorizeState>(&json).ok())
+            .filter(|a| a.tenant_id == ctx.tenant.id && a.login_type == req.login_type)
+            .ok_or_else(|| AuthixError::InvalidCredentials("auth.social_state_invalid".into()))?;
+
+        let identity = self.idp.exchange(&self.http, settings, &auth, &code).await?;
+        if identity.subject.is_empty() {
+            return Err(rejected("identity provider returned empty subject"));
+        }
+        Ok(identity)
+    }
+}
+
+#[async_trait]
+impl LoginProvider for SocialLoginProvider {
+    async fn login(&self, ctx: &TenantContext, req: &LoginRequest, state: &AppState) -> AuthixResult<R<LoginResponse>> {
+        let settings = Self::settings(ctx, &req.login_type)?;
+        let identity = self.external_identity(ctx, req, state).await?;
+        let user = resolve_user(state, ctx, &req.login_type, settings, &identity).await?;
+        user.ensure_can_login()?;
+
+        let resp = jwt::create_token(state, user.id.to_string(), user.tenant_id.to_string(), None, ctx.settings.access_token_exp, ctx.settings.refresh_token_exp).await?;
+        let _ = state.users.update_last_login_time(user.id).await?;
+        Ok(R::ok_data(resp))
+    }
+
+    fn schema(&self) -> RequestSchema {
+        RequestSchema::new().required("code", FieldKind::Code).required("state", FieldKind::Text)
+    }
+
+    async fn authorize(&self, ctx: &TenantContext, login_type: &str, redirect_uri: &str, state: &AppState) -> AuthixResult<Option<String>> {
+        let settings = Self::settings(ctx, login_type)?;
+        if !settings.redirect_uris.iter().any(|u| u == redirect_uri) {
+            return Err(AuthixError::InvalidParameter("auth.redirect_uri_not_allowed".into()));
+        }
+        let auth = AuthorizeState {
+            tenant_id: ctx.tenant.id,
+            login_type: login_type.to_string(),
+            redirect_uri: redirect_uri.to_string(),
+            nonce: Uuid::new_v4().simple().to_string(),
+            code_verifier: format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple()),
+        };
+        let id = Uuid::new_v4().simple().to_string();
+        let url = self.idp.authorize_url(&self.http, settings, &auth, &id).await?;
+        let json = serde_json::to_string(&auth).map_err(|e| AuthixError::Internal(format!("serialize authorize state error: {}", e)))?;
+        state.codes.set(&format!("{}:{}", SOCIAL_STATE_KEY, id), &json, SOCIAL_STATE_SEC_TTL).await?;
+        Ok(Some(url))
+    }
+
+    async fn identify(&self, ctx: &TenantContext, req: &LoginRequest, state: &AppState) -> AuthixResult<Option<String>> {
+        self.external_identity(ctx, req, state).await.map(|identity| Some(identity.subject))
+    }
+}
+
+/// 按已关联的第三方身份、已验证邮箱依次匹配本地用户，均未匹配时按配置创建。
+/// 通过邮箱匹配或新建的用户关联该第三方身份
+async fn resolve_user(
+    state: &AppState,
+    ctx: &TenantContext,
+    login_type: &str,
+    settings: &SocialProviderSettings,
+    identity: &ExternalIdentity,
+) -> AuthixResult<User> {
+    let tenant_id = ctx.tenant.id;
+    if let Some(user) = state.users.get_user_by_identity(tenant_id, IdentityType::External, login_type, &identity.subject).await? {
+        return Ok(user);
+    }
+    let email = identity.email.clone().filter(|_| identity.email_verified);
+    let matched = match &email {
+        Some(email) if settings.match_verified_email => state.users.get_user_by_email(tenant_id, email.clone()).await?,
+        _ => None,
+    };
+    let user = match matched {
+        Some(user) => user,
+        None if !settings.auto_create_user => return Err(AuthixError::InvalidCredentials("auth.account_not_found".into())),
+        None => {
+            // 邮箱已被其他账号使用时不写入，避免唯一约束冲突
+            let email = match email {
+                Some(e) if state.users.get_user_by_email(tenant_id, e.clone()).await?.is_none() => Some(e),
+                _ => None,
+            };
+            let new_user = User {
+                id: 0,
+                tenant_id,
+                username: None,
+                phone: None,
+                email,
+                password: unusable_password()?,
+                status: UserStatus::Active,
+                crt_by: Some(login_type.to_string()),
+            };
+            state.users.create_user(new_user).await?
+        }
+    };
+    state.users.link_identity(UserIdentity::new(&user, IdentityType::External, login_type, &identity.subject).verified()).await?;
+    Ok(user)
+}
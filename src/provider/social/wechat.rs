@@ -0,0 +1,79 @@
+use axum::async_trait;
+use reqwest::{Client, Url};
+use serde::Deserialize;
+
+use crate::{errors::{AuthixError, AuthixResult}, tenant::settings::SocialProviderSettings};
+
+use super::{rejected, upstream, AuthorizeState, ExternalIdentity, IdentityProvider};
+
+/// 网站应用扫码登录
+const AUTHORIZE_URL: &str = "https://open.weixin.qq.com/connect/qrconnect";
+const TOKEN_URL: &str = "https://api.weixin.qq.com/sns/oauth2/access_token";
+const USERINFO_URL: &str = "https://api.weixin.qq.com/sns/userinfo";
+const DEFAULT_SCOPE: &str = "snsapi_login";
+
+#[derive(Debug, Deserialize)]
+struct WeChatToken {
+    access_token: Option<String>,
+    openid: Option<String>,
+    unionid: Option<String>,
+    errcode: Option<i64>,
+    errmsg: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WeChatUser {
+    unionid: Option<String>,
+    errcode: Option<i64>,
+}
+
+/// 微信开放平台登录，`client_id`、`client_secret` 分别为 AppID、AppSecret。
+/// 用户标识优先取 unionid，同一开放平台下的多个应用可对应到同一本地用户
+pub struct WeChat;
+
+#[async_trait]
+impl IdentityProvider for WeChat {
+    async fn authorize_url(&self, _http: &Client, settings: &SocialProviderSettings, auth: &AuthorizeState, state: &str) -> AuthixResult<String> {
+        let endpoint = settings.authorize_url.as_deref().unwrap_or(AUTHORIZE_URL);
+        let scope = if settings.scopes.is_empty() { DEFAULT_SCOPE.to_string() } else { settings.scopes.join(",") };
+        let mut url = Url::parse_with_params(endpoint, [
+            ("appid", settings.client_id.as_str()),
+            ("redirect_uri", auth.redirect_uri.as_str()),
+            ("response_type", "code"),
+            ("scope", scope.as_str()),
+            ("state", state),
+        ]).map_err(|e| AuthixError::Internal(format!("invalid authorize endpoint {}: {}", endpoint, e)))?;
+        url.set_fragment(Some("wechat_redirect"));
+        Ok(url.into())
+    }
+
+    async fn exchange(&self, http: &Client, settings: &SocialProviderSettings, _auth: &AuthorizeState, code: &str) -> AuthixResult<ExternalIdentity> {
+        let token_url = settings.token_url.as_deref().unwrap_or(TOKEN_URL);
+        let query = [
+            ("appid", settings.client_id.as_str()),
+            ("secret", settings.client_secret.as_str()),
+            ("code", code),
+            ("grant_type", "authorization_code"),
+        ];
+        // 微信接口出错时仍返回 200，以 errcode 区分
+        let token: WeChatToken = http.get(token_url).query(&query).send().await.and_then(|r| r.error_for_status()).map_err(upstream)?
+            .json().await.map_err(rejected)?;
+        let (Some(access_token), Some(openid)) = (token.access_token, token.openid) else {
+            return Err(rejected(format!("wechat token errcode {:?} {:?}", token.errcode, token.errmsg)));
+        };
+        let unionid = match token.unionid {
+            Some(id) => Some(id),
+            None => {
+                let userinfo_url = settings.userinfo_url.as_deref().unwrap_or(USERINFO_URL);
+                let user: WeChatUser = http.get(userinfo_url).query(&[("access_token", access_token.as_str()), ("openid", openid.as_str())])
+                    .send().await.and_then(|r| r.error_for_status()).map_err(upstream)?
+                    .json().await.map_err(rejected)?;
+                if let Some(code) = user.errcode.filter(|c| *c != 0) {
+                    return Err(rejected(format!("wechat userinfo errcode {}", code)));
+                }
+                user.unionid
+            }
+        };
+        Ok(ExternalIdentity { subject: unionid.unwrap_or(openid), email: None, email_verified: false })
+    }
+}
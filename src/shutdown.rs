@@ -0,0 +1,79 @@
+use std::time::Duration;
+
+use tokio::{sync::watch, task::JoinHandle};
+use tracing::{info, warn};
+
+use crate::state::AppState;
+
+/// 后台任务收尾的最长等待时间
+const BACKGROUND_FLUSH_TIMEOUT_SECS: u64 = 10;
+
+/// 停机时需要收尾的后台任务，审计服务与连接池取自 `state`
+pub struct Background {
+    pub state: AppState,
+    pub tasks: Vec<JoinHandle<()>>,
+}
+
+/// 等待 SIGINT / SIGTERM
+pub async fn wait_for_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            warn!("listen ctrl_c error: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut s) => {
+                s.recv().await;
+            }
+            Err(e) => {
+                warn!("listen SIGTERM error: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+    tokio::select! {
+        _ = ctrl_c => info!("received SIGINT"),
+        _ = terminate => info!("received SIGTERM"),
+    }
+}
+
+/// 停机开始：先置为未就绪，等待流量摘除后通知服务停止接收新连接及后台任务退出
+pub async fn begin(state: &AppState, shutdown: &watch::Sender<bool>) {
+    state.mark_shutting_down();
+    let delay = Duration::from_secs(state.config.get().shutdown.readiness_delay_secs);
+    info!("readiness set to failing, stop accepting connections in {}s", delay.as_secs());
+    tokio::time::sleep(delay).await;
+    let _ = shutdown.send(true);
+}
+
+/// 等待处理中的请求完成，超过 `shutdown.drain_timeout_secs` 后放弃
+pub async fn drain(state: &AppState, server: JoinHandle<()>) {
+    let deadline = Duration::from_secs(state.config.get().shutdown.drain_timeout_secs);
+    match tokio::time::timeout(deadline, server).await {
+        Ok(_) => info!("in-flight requests drained"),
+        Err(_) => warn!("drain deadline {}s exceeded, dropping remaining connections", deadline.as_secs()),
+    }
+}
+
+/// 落库待写入的审计事件，等待后台任务退出，最后关闭连接池
+pub async fn finish(background: Background) {
+    let flush = async {
+        background.state.audit.flush().await;
+        for task in background.tasks {
+            let _ = task.await;
+        }
+    };
+    if tokio::time::timeout(Duration::from_secs(BACKGROUND_FLUSH_TIMEOUT_SECS), flush).await.is_err() {
+        warn!("background tasks did not finish within {}s", BACKGROUND_FLUSH_TIMEOUT_SECS);
+    }
+    background.state.db.close().await;
+    if let Some(redis) = &background.state.redis {
+        redis.close();
+    }
+    info!("shutdown complete");
+}
@@ -0,0 +1,270 @@
+use std::sync::{atomic::{AtomicBool, Ordering}, Arc};
+
+use axum::extract::FromRef;
+use deadpool_redis::Pool;
+
+use crate::{
+    audit::{AuditProvider, AuditService},
+    cache::{CodeStore, InMemoryCodeStore, InMemorySessionStore, RedisCodeStore, RedisSessionStore, SessionStore},
+    config::{CacheBackendKind, ConfigHandle, EventPublisherKind, UserStoreKind},
+    event::{EventPublisher, InMemoryEventPublisher, RedisStreamPublisher},
+    login_history::{LoginHistoryProvider, LoginHistoryService},
+    notify::{LogNotifyService, NotifyProvider},
+    organization::{OrganizationProvider, OrganizationService},
+    provider::{login::{LoginProvider, LoginService, LoginServiceBuilder}, register::{RegisterProvider, RegisterService, RegisterServiceBuilder}},
+    storage::Db,
+    tenant::{TenantProvider, TenantService},
+    user::{InMemoryUserService, UserProvider, UserService},
+    utils::{self, jwt::KeyRing},
+    webhook::{WebhookProvider, WebhookService},
+};
+
+/// 应用状态：配置、连接池、令牌密钥、存储与各服务，通过 Axum `State` 注入处理函数。
+/// 克隆开销很小，各字段共享同一份实例
+#[derive(Clone)]
+pub struct AppState {
+    pub config: ConfigHandle,
+    pub db: Db,
+    /// 缓存或事件发布使用 Redis 时才创建
+    pub redis: Option<Pool>,
+    pub keys: KeyRing,
+    pub sessions: Arc<dyn SessionStore>,
+    pub codes: Arc<dyn CodeStore>,
+    pub users: Arc<dyn UserProvider>,
+    pub tenants: Arc<dyn TenantProvider>,
+    pub orgs: Arc<dyn OrganizationProvider>,
+    pub audit: Arc<dyn AuditProvider>,
+    pub login_history: Arc<dyn LoginHistoryProvider>,
+    pub webhooks: Arc<dyn WebhookProvider>,
+    pub notify: Arc<dyn NotifyProvider>,
+    pub events: Arc<dyn EventPublisher>,
+    pub login: Arc<LoginService>,
+    pub register: Arc<RegisterService>,
+    shutting_down: Arc<AtomicBool>,
+}
+
+impl AppState {
+    /// 停机中标记，置位后就绪探针返回 503，负载均衡摘除流量
+    pub fn mark_shutting_down(&self) {
+        self.shutting_down.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::SeqCst)
+    }
+}
+
+macro_rules! from_state {
+    ($($field:ident: $t:ty),* $(,)?) => {$(
+        impl FromRef<AppState> for $t {
+            fn from_ref(state: &AppState) -> Self {
+                state.$field.clone()
+            }
+        }
+    )*};
+}
+
+from_state!(
+    config: ConfigHandle,
+    db: Db,
+    keys: KeyRing,
+    sessions: Arc<dyn SessionStore>,
+    codes: Arc<dyn CodeStore>,
+    users: Arc<dyn UserProvider>,
+    tenants: Arc<dyn TenantProvider>,
+    orgs: Arc<dyn OrganizationProvider>,
+    audit: Arc<dyn AuditProvider>,
+    login_history: Arc<dyn LoginHistoryProvider>,
+    webhooks: Arc<dyn WebhookProvider>,
+    notify: Arc<dyn NotifyProvider>,
+    events: Arc<dyn EventPublisher>,
+    login: Arc<LoginService>,
+    register: Arc<RegisterService>,
+);
+
+/// `AppState` 构建器，未指定的组件按配置创建默认实现。
+/// 嵌入其他应用时可传入自有的连接池、存储或服务
+pub struct AppStateBuilder {
+    config: ConfigHandle,
+    db: Option<Db>,
+    redis: Option<Pool>,
+    keys: Option<KeyRing>,
+    sessions: Option<Arc<dyn SessionStore>>,
+    codes: Option<Arc<dyn CodeStore>>,
+    users: Option<Arc<dyn UserProvider>>,
+    tenants: Option<Arc<dyn TenantProvider>>,
+    orgs: Option<Arc<dyn OrganizationProvider>>,
+    audit: Option<Arc<dyn AuditProvider>>,
+    login_history: Option<Arc<dyn LoginHistoryProvider>>,
+    webhooks: Option<Arc<dyn WebhookProvider>>,
+    notify: Option<Arc<dyn NotifyProvider>>,
+    events: Option<Arc<dyn EventPublisher>>,
+    login: LoginServiceBuilder,
+    register: RegisterServiceBuilder,
+}
+
+macro_rules! setters {
+    ($($field:ident: $t:ty),* $(,)?) => {$(
+        pub fn $field(mut self, $field: $t) -> Self {
+            self.$field = Some($field);
+            self
+        }
+    )*};
+}
+
+impl AppStateBuilder {
+    pub fn new(config: ConfigHandle) -> Self {
+        Self {
+            config,
+            db: None,
+            redis: None,
+            keys: None,
+            sessions: None,
+            codes: None,
+            users: None,
+            tenants: None,
+            orgs: None,
+            audit: None,
+            login_history: None,
+            webhooks: None,
+            notify: None,
+            events: None,
+            login: LoginServiceBuilder::default(),
+            register: RegisterServiceBuilder::default(),
+        }
+    }
+
+    setters!(
+        db: Db,
+        redis: Pool,
+        keys: KeyRing,
+        sessions: Arc<dyn SessionStore>,
+        codes: Arc<dyn CodeStore>,
+        users: Arc<dyn UserProvider>,
+        tenants: Arc<dyn TenantProvider>,
+        orgs: Arc<dyn OrganizationProvider>,
+        audit: Arc<dyn AuditProvider>,
+        login_history: Arc<dyn LoginHistoryProvider>,
+        webhooks: Arc<dyn WebhookProvider>,
+        notify: Arc<dyn NotifyProvider>,
+        events: Arc<dyn EventPublisher>,
+    );
+
+    /// 按标识注册登录方式，可覆盖内置的 password、sms、email、ldap
+    pub fn login_provider(mut self, login_type: impl Into<String>, provider: Arc<dyn LoginProvider>) -> Self {
+        self.login = self.login.provider(login_type, provider);
+        self
+    }
+
+    /// 按标识注册注册方式，可覆盖内置的 password、sms、email
+    pub fn register_provider(mut self, register_type: impl Into<String>, provider: Arc<dyn RegisterProvider>) -> Self {
+        self.register = self.register.provider(register_type, provider);
+        self
+    }
+
+    /// 创建 `AppState`，连接池均为懒连接；审计服务会启动后台写入任务，需在 Tokio 运行时内调用
+    pub fn build(self) -> AppState {
+        let config = self.config.get();
+        let db = self.db.unwrap_or_else(|| utils::database::connect(&config.database));
+        let redis = self.redis.or_else(|| config.uses_redis().then(|| utils::redis::connect(&config.redis)));
+        let redis_pool = || redis.clone().expect("redis pool is created when cache or events use redis");
+
+        let keys = self.keys.unwrap_or_else(|| KeyRing::from_secret(&config.token.secret));
+        let sessions = self.sessions.unwrap_or_else(|| match config.cache.backend {
+            CacheBackendKind::Memory => Arc::new(InMemorySessionStore::default()),
+            CacheBackendKind::Redis => Arc::new(RedisSessionStore::new(redis_pool())),
+        });
+        let codes = self.codes.unwrap_or_else(|| match config.cache.backend {
+            CacheBackendKind::Memory => Arc::new(InMemoryCodeStore::default()),
+            CacheBackendKind::Redis => Arc::new(RedisCodeStore::new(redis_pool())),
+        });
+        let events = self.events.unwrap_or_else(|| match config.events.publisher {
+            EventPublisherKind::Memory => Arc::new(InMemoryEventPublisher::default()),
+            EventPublisherKind::Redis => Arc::new(RedisStreamPublisher::new(redis_pool(), &config.events.stream_key)),
+        });
+        let users = self.users.unwrap_or_else(|| match config.user.store {
+            UserStoreKind::Memory => Arc::new(InMemoryUserService::default()),
+            UserStoreKind::Database => Arc::new(UserService::new(db.clone())),
+        });
+
+        AppState {
+            tenants: self.tenants.unwrap_or_else(|| Arc::new(TenantService::new(db.clone(), codes.clone(), self.config.clone()))),
+            orgs: self.orgs.unwrap_or_else(|| Arc::new(OrganizationService::new(db.clone()))),
+            audit: self.audit.unwrap_or_else(|| Arc::new(AuditService::new(db.clone()))),
+            login_history: self.login_history.unwrap_or_else(|| Arc::new(LoginHistoryService::new(db.clone()))),
+            webhooks: self.webhooks.unwrap_or_else(|| Arc::new(WebhookService::new(db.clone()))),
+            notify: self.notify.unwrap_or_else(|| Arc::new(LogNotifyService)),
+            login: Arc::new(self.login.build(events.clone())),
+            register: Arc::new(self.register.build(events.clone())),
+            config: self.config,
+            db,
+            redis,
+            keys,
+            sessions,
+            codes,
+            users,
+            events,
+            shutting_down: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+/// 测试用的 `AppState`：SQLite 临时数据库，缓存与事件发布使用内存实现
+#[cfg(test)]
+pub(crate) async fn test_state(mut config: crate::config::AuthixConfig) -> AppState {
+    config.cache.backend = CacheBackendKind::Memory;
+    config.events.publisher = EventPublisherKind::Memory;
+    AppStateBuilder::new(ConfigHandle::new(config)).db(utils::database::test_db().await).build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::AuthixConfig, enums::UserStatus, user::User};
+
+    fn new_user(username: &str) -> User {
+        User {
+            id: 0,
+            tenant_id: 1,
+            username: Some(username.into()),
+            phone: None,
+            email: None,
+            password: "hash".into(),
+            status: UserStatus::Active,
+            crt_by: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn states_do_not_share_components() {
+        let first = test_state(AuthixConfig::default()).await;
+        let second = test_state(AuthixConfig::default()).await;
+        let user = first.users.create_user(new_user("alice")).await.unwrap();
+        assert!(first.users.get_user_by_id(user.id).await.unwrap().is_some());
+        assert!(second.users.get_user_by_username(1, "alice".into()).await.unwrap().is_none());
+
+        first.sessions.save_session(1, user.id, "token", 60).await.unwrap();
+        assert_eq!(first.sessions.online_count(None).await.unwrap(), 1);
+        assert_eq!(second.sessions.online_count(None).await.unwrap(), 0);
+
+        // 克隆与 FromRef 共享同一份实例
+        let cloned = first.clone();
+        assert!(Arc::ptr_eq(&cloned.sessions, &first.sessions));
+        assert!(Arc::ptr_eq(&<Arc<dyn UserProvider>>::from_ref(&first), &first.users));
+    }
+
+    #[tokio::test]
+    async fn builder_uses_supplied_components() {
+        let users: Arc<dyn UserProvider> = Arc::new(InMemoryUserService::default());
+        let codes: Arc<dyn CodeStore> = Arc::new(InMemoryCodeStore::default());
+        let state = AppStateBuilder::new(ConfigHandle::new(AuthixConfig::default()))
+            .db(utils::database::test_db().await)
+            .users(users.clone())
+            .codes(codes.clone())
+            .build();
+        assert!(Arc::ptr_eq(&state.users, &users));
+        assert!(Arc::ptr_eq(&state.codes, &codes));
+        state.users.create_user(new_user("alice")).await.unwrap();
+        assert!(users.get_user_by_username(1, "alice".into()).await.unwrap().is_some());
+    }
+}
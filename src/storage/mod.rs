@@ -0,0 +1,209 @@
+use std::str::FromStr;
+
+use sqlx::{
+    mysql::MySqlPoolOptions,
+    postgres::PgPoolOptions,
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+    MySql, MySqlPool, PgPool, Postgres, Sqlite, SqlitePool, Transaction,
+};
+
+mod query;
+mod row;
+
+pub use query::{query, Value};
+pub use row::{decode_error, Column, ColumnIndex, FromRecord, Row};
+
+/// 存储后端，由数据库连接串协议决定
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    MySql,
+    Postgres,
+    Sqlite,
+}
+
+impl Backend {
+    /// `mysql://`、`postgres://`（`postgresql://`）、`sqlite:`
+    pub fn from_url(url: &str) -> Option<Backend> {
+        let scheme = url.split(':').next().unwrap_or_default().to_ascii_lowercase();
+        match scheme.as_str() {
+            "mysql" => Some(Backend::MySql),
+            "postgres" | "postgresql" => Some(Backend::Postgres),
+            "sqlite" => Some(Backend::Sqlite),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Backend::MySql => "mysql",
+            Backend::Postgres => "postgres",
+            Backend::Sqlite => "sqlite",
+        }
+    }
+
+    /// 转换为 64 位整数
+    pub fn cast_int(self, expr: &str) -> String {
+        match self {
+            Backend::MySql => format!("CAST({} AS SIGNED)", expr),
+            Backend::Postgres => format!("CAST({} AS BIGINT)", expr),
+            Backend::Sqlite => format!("CAST({} AS INTEGER)", expr),
+        }
+    }
+
+    /// 时间列转换为 Unix 时间戳（秒）
+    pub fn epoch_seconds(self, column: &str) -> String {
+        match self {
+            Backend::MySql => format!("UNIX_TIMESTAMP({})", column),
+            Backend::Postgres => format!("CAST(EXTRACT(EPOCH FROM {}) AS BIGINT)", column),
+            Backend::Sqlite => format!("CAST(strftime('%s', {}) AS INTEGER)", column),
+        }
+    }
+
+    /// 事务内锁定查询到的行，SQLite 写事务本身串行执行，无需加锁
+    pub fn for_update(self) -> &'static str {
+        match self {
+            Backend::Sqlite => "",
+            _ => " FOR UPDATE",
+        }
+    }
+
+    /// 唯一键冲突时改为更新 `columns`，拼接在 `INSERT ... VALUES (...)` 之后
+    pub fn upsert(self, keys: &[&str], columns: &[&str]) -> String {
+        match self {
+            Backend::MySql => {
+                let sets: Vec<String> = columns.iter().map(|c| format!("{c} = VALUES({c})")).collect();
+                format!(" ON DUPLICATE KEY UPDATE {}", sets.join(", "))
+            }
+            Backend::Postgres | Backend::Sqlite => {
+                let sets: Vec<String> = columns.iter().map(|c| format!("{c} = excluded.{c}")).collect();
+                format!(" ON CONFLICT ({}) DO UPDATE SET {}", keys.join(", "), sets.join(", "))
+            }
+        }
+    }
+}
+
+/// 数据库连接池，按连接串选择 MySQL、PostgreSQL 或 SQLite
+#[derive(Debug, Clone)]
+pub enum Db {
+    MySql(MySqlPool),
+    Postgres(PgPool),
+    Sqlite(SqlitePool),
+}
+
+impl Db {
+    /// 创建连接池，首次使用时才建立连接；SQLite 数据库文件不存在时自动创建
+    pub fn connect_lazy(url: &str, max_connections: u32) -> Result<Db, sqlx::Error> {
+        let backend = Backend::from_url(url).ok_or_else(|| sqlx::Error::Configuration(format!("unsupported database url: {}", url).into()))?;
+        Ok(match backend {
+            Backend::MySql => Db::MySql(MySqlPoolOptions::new().max_connections(max_connections).connect_lazy(url)?),
+            Backend::Postgres => Db::Postgres(PgPoolOptions::new().max_connections(max_connections).connect_lazy(url)?),
+            Backend::Sqlite => {
+                let options = SqliteConnectOptions::from_str(url)?.create_if_missing(true);
+                Db::Sqlite(SqlitePoolOptions::new().max_connections(max_connections).connect_lazy_with(options))
+            }
+        })
+    }
+
+    pub fn backend(&self) -> Backend {
+        match self {
+            Db::MySql(_) => Backend::MySql,
+            Db::Postgres(_) => Backend::Postgres,
+            Db::Sqlite(_) => Backend::Sqlite,
+        }
+    }
+
+    pub async fn begin(&self) -> Result<Tx, sqlx::Error> {
+        Ok(match self {
+            Db::MySql(pool) => Tx::MySql(pool.begin().await?),
+            Db::Postgres(pool) => Tx::Postgres(pool.begin().await?),
+            Db::Sqlite(pool) => Tx::Sqlite(pool.begin().await?),
+        })
+    }
+
+    pub async fn close(&self) {
+        match self {
+            Db::MySql(pool) => pool.close().await,
+            Db::Postgres(pool) => pool.close().await,
+            Db::Sqlite(pool) => pool.close().await,
+        }
+    }
+
+    /// 当前连接数
+    pub fn size(&self) -> u32 {
+        match self {
+            Db::MySql(pool) => pool.size(),
+            Db::Postgres(pool) => pool.size(),
+            Db::Sqlite(pool) => pool.size(),
+        }
+    }
+
+    /// 空闲连接数
+    pub fn num_idle(&self) -> usize {
+        match self {
+            Db::MySql(pool) => pool.num_idle(),
+            Db::Postgres(pool) => pool.num_idle(),
+            Db::Sqlite(pool) => pool.num_idle(),
+        }
+    }
+
+    pub fn max_connections(&self) -> u32 {
+        match self {
+            Db::MySql(pool) => pool.options().get_max_connections(),
+            Db::Postgres(pool) => pool.options().get_max_connections(),
+            Db::Sqlite(pool) => pool.options().get_max_connections(),
+        }
+    }
+}
+
+/// 数据库事务，未提交即丢弃时自动回滚
+#[derive(Debug)]
+pub enum Tx {
+    MySql(Transaction<'static, MySql>),
+    Postgres(Transaction<'static, Postgres>),
+    Sqlite(Transaction<'static, Sqlite>),
+}
+
+impl Tx {
+    pub fn backend(&self) -> Backend {
+        match self {
+            Tx::MySql(_) => Backend::MySql,
+            Tx::Postgres(_) => Backend::Postgres,
+            Tx::Sqlite(_) => Backend::Sqlite,
+        }
+    }
+
+    pub async fn commit(self) -> Result<(), sqlx::Error> {
+        match self {
+            Tx::MySql(tx) => tx.commit().await,
+            Tx::Postgres(tx) => tx.commit().await,
+            Tx::Sqlite(tx) => tx.commit().await,
+        }
+    }
+}
+
+/// 执行查询的连接：连接池或事务
+pub enum Conn<'c> {
+    Pool(&'c Db),
+    Tx(&'c mut Tx),
+}
+
+impl Conn<'_> {
+    pub fn backend(&self) -> Backend {
+        match self {
+            Conn::Pool(db) => db.backend(),
+            Conn::Tx(tx) => tx.backend(),
+        }
+    }
+}
+
+impl<'c> From<&'c Db> for Conn<'c> {
+    fn from(db: &'c Db) -> Self {
+        Conn::Pool(db)
+    }
+}
+
+impl<'c> From<&'c mut Tx> for Conn<'c> {
+    fn from(tx: &'c mut Tx) -> Self {
+        Conn::Tx(tx)
+    }
+}
@@ -0,0 +1,381 @@
+use chrono::{DateTime, Local, NaiveDate};
+use sqlx::{
+    mysql::MySqlArguments, postgres::PgArguments, sqlite::SqliteArguments, MySql, Postgres, Sqlite,
+};
+
+use super::{Backend, Column, Conn, Db, FromRecord, Row, Tx};
+
+/// 查询参数，空值保留类型，PostgreSQL 按类型绑定 NULL
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Int(Option<i64>),
+    Bool(Option<bool>),
+    Text(Option<String>),
+    Date(Option<NaiveDate>),
+    DateTime(Option<DateTime<Local>>),
+}
+
+macro_rules! int_value {
+    ($($t:ty),*) => {$(
+        impl From<$t> for Value {
+            fn from(v: $t) -> Self {
+                Value::Int(Some(v as i64))
+            }
+        }
+
+        impl From<Option<$t>> for Value {
+            fn from(v: Option<$t>) -> Self {
+                Value::Int(v.map(|v| v as i64))
+            }
+        }
+    )*};
+}
+
+int_value!(u8, u16, u32, u64, i32, i64);
+
+impl From<bool> for Value {
+    fn from(v: bool) -> Self {
+        Value::Bool(Some(v))
+    }
+}
+
+impl From<String> for Value {
+    fn from(v: String) -> Self {
+        Value::Text(Some(v))
+    }
+}
+
+impl From<&String> for Value {
+    fn from(v: &String) -> Self {
+        Value::Text(Some(v.clone()))
+    }
+}
+
+impl From<&str> for Value {
+    fn from(v: &str) -> Self {
+        Value::Text(Some(v.to_owned()))
+    }
+}
+
+impl From<Option<String>> for Value {
+    fn from(v: Option<String>) -> Self {
+        Value::Text(v)
+    }
+}
+
+impl From<&Option<String>> for Value {
+    fn from(v: &Option<String>) -> Self {
+        Value::Text(v.clone())
+    }
+}
+
+impl From<Option<&str>> for Value {
+    fn from(v: Option<&str>) -> Self {
+        Value::Text(v.map(str::to_owned))
+    }
+}
+
+impl From<NaiveDate> for Value {
+    fn from(v: NaiveDate) -> Self {
+        Value::Date(Some(v))
+    }
+}
+
+impl From<Option<NaiveDate>> for Value {
+    fn from(v: Option<NaiveDate>) -> Self {
+        Value::Date(v)
+    }
+}
+
+impl From<DateTime<Local>> for Value {
+    fn from(v: DateTime<Local>) -> Self {
+        Value::DateTime(Some(v))
+    }
+}
+
+impl From<Option<DateTime<Local>>> for Value {
+    fn from(v: Option<DateTime<Local>>) -> Self {
+        Value::DateTime(v)
+    }
+}
+
+/// 跨后端的 SQL 查询，统一使用 `?` 占位符，PostgreSQL 执行前转换为 `$n`
+#[derive(Debug, Clone)]
+pub struct Query {
+    sql: String,
+    args: Vec<Value>,
+}
+
+pub fn query(sql: impl Into<String>) -> Query {
+    Query { sql: sql.into(), args: Vec::new() }
+}
+
+impl Query {
+    pub fn bind(mut self, value: impl Into<Value>) -> Self {
+        self.args.push(value.into());
+        self
+    }
+
+    /// 执行语句，返回影响行数
+    pub async fn execute<'c>(self, conn: impl Into<Conn<'c>>) -> Result<u64, sqlx::Error> {
+        let Query { sql, args } = self;
+        Ok(match conn.into() {
+            Conn::Pool(Db::MySql(pool)) => bind_mysql(&sql, args).execute(pool).await?.rows_affected(),
+            Conn::Tx(Tx::MySql(tx)) => bind_mysql(&sql, args).execute(&mut **tx).await?.rows_affected(),
+            Conn::Pool(Db::Postgres(pool)) => bind_pg(&numbered(&sql), args).execute(pool).await?.rows_affected(),
+            Conn::Tx(Tx::Postgres(tx)) => bind_pg(&numbered(&sql), args).execute(&mut **tx).await?.rows_affected(),
+            Conn::Pool(Db::Sqlite(pool)) => bind_sqlite(&sql, args).execute(pool).await?.rows_affected(),
+            Conn::Tx(Tx::Sqlite(tx)) => bind_sqlite(&sql, args).execute(&mut **tx).await?.rows_affected(),
+        })
+    }
+
+    /// 执行 INSERT，返回自增主键 `id`
+    pub async fn insert<'c>(self, conn: impl Into<Conn<'c>>) -> Result<u64, sqlx::Error> {
+        let conn = conn.into();
+        if conn.backend() == Backend::Postgres {
+            let sql = format!("{} RETURNING id", self.sql);
+            return Query { sql, args: self.args }.fetch_scalar(conn).await;
+        }
+        let Query { sql, args } = self;
+        let id = match conn {
+            Conn::Pool(Db::MySql(pool)) => bind_mysql(&sql, args).execute(pool).await?.last_insert_id(),
+            Conn::Tx(Tx::MySql(tx)) => bind_mysql(&sql, args).execute(&mut **tx).await?.last_insert_id(),
+            Conn::Pool(Db::Sqlite(pool)) => bind_sqlite(&sql, args).execute(pool).await?.last_insert_rowid() as u64,
+            Conn::Tx(Tx::Sqlite(tx)) => bind_sqlite(&sql, args).execute(&mut **tx).await?.last_insert_rowid() as u64,
+            Conn::Pool(Db::Postgres(_)) | Conn::Tx(Tx::Postgres(_)) => unreachable!(),
+        };
+        Ok(id)
+    }
+
+    /// 返回原始行，用于聚合等无对应结构体的查询
+    pub async fn fetch_rows<'c>(self, conn: impl Into<Conn<'c>>) -> Result<Vec<Row>, sqlx::Error> {
+        let Query { sql, args } = self;
+        Ok(match conn.into() {
+            Conn::Pool(Db::MySql(pool)) => bind_mysql(&sql, args).fetch_all(pool).await?.into_iter().map(Row::MySql).collect(),
+            Conn::Tx(Tx::MySql(tx)) => bind_mysql(&sql, args).fetch_all(&mut **tx).await?.into_iter().map(Row::MySql).collect(),
+            Conn::Pool(Db::Postgres(pool)) => bind_pg(&numbered(&sql), args).fetch_all(pool).await?.into_iter().map(Row::Postgres).collect(),
+            Conn::Tx(Tx::Postgres(tx)) => bind_pg(&numbered(&sql), args).fetch_all(&mut **tx).await?.into_iter().map(Row::Postgres).collect(),
+            Conn::Pool(Db::Sqlite(pool)) => bind_sqlite(&sql, args).fetch_all(pool).await?.into_iter().map(Row::Sqlite).collect(),
+            Conn::Tx(Tx::Sqlite(tx)) => bind_sqlite(&sql, args).fetch_all(&mut **tx).await?.into_iter().map(Row::Sqlite).collect(),
+        })
+    }
+
+    pub async fn fetch_all<'c, T: FromRecord>(self, conn: impl Into<Conn<'c>>) -> Result<Vec<T>, sqlx::Error> {
+        self.fetch_rows(conn).await?.iter().map(T::from_record).collect()
+    }
+
+    pub async fn fetch_optional<'c, T: FromRecord>(self, conn: impl Into<Conn<'c>>) -> Result<Option<T>, sqlx::Error> {
+        self.fetch_rows(conn).await?.first().map(T::from_record).transpose()
+    }
+
+    pub async fn fetch_one<'c, T: FromRecord>(self, conn: impl Into<Conn<'c>>) -> Result<T, sqlx::Error> {
+        self.fetch_optional(conn).await?.ok_or(sqlx::Error::RowNotFound)
+    }
+
+    /// 查询第一行第一列，如 `COUNT(*)`
+    pub async fn fetch_scalar<'c, T: Column>(self, conn: impl Into<Conn<'c>>) -> Result<T, sqlx::Error> {
+        self.fetch_optional_scalar(conn).await?.ok_or(sqlx::Error::RowNotFound)
+    }
+
+    pub async fn fetch_optional_scalar<'c, T: Column>(self, conn: impl Into<Conn<'c>>) -> Result<Option<T>, sqlx::Error> {
+        self.fetch_rows(conn).await?.first().map(|row| row.get(0)).transpose()
+    }
+}
+
+type MySqlQuery<'q> = sqlx::query::Query<'q, MySql, MySqlArguments>;
+type PgQuery<'q> = sqlx::query::Query<'q, Postgres, PgArguments>;
+type SqliteQuery<'q> = sqlx::query::Query<'q, Sqlite, SqliteArguments<'q>>;
+
+fn bind_mysql(sql: &str, args: Vec<Value>) -> MySqlQuery<'_> {
+    args.into_iter().fold(sqlx::query(sql), |q, arg| match arg {
+        Value::Int(v) => q.bind(v),
+        Value::Bool(v) => q.bind(v),
+        Value::Text(v) => q.bind(v),
+        Value::Date(v) => q.bind(v),
+        Value::DateTime(v) => q.bind(v),
+    })
+}
+
+fn bind_pg(sql: &str, args: Vec<Value>) -> PgQuery<'_> {
+    args.into_iter().fold(sqlx::query(sql), |q, arg| match arg {
+        Value::Int(v) => q.bind(v),
+        Value::Bool(v) => q.bind(v),
+        Value::Text(v) => q.bind(v),
+        Value::Date(v) => q.bind(v),
+        Value::DateTime(v) => q.bind(v),
+    })
+}
+
+/// SQLite 时间以 UTC 文本存储，与 `CURRENT_TIMESTAMP` 格式一致，保证按文本比较结果正确
+fn bind_sqlite(sql: &str, args: Vec<Value>) -> SqliteQuery<'_> {
+    args.into_iter().fold(sqlx::query(sql), |q, arg| match arg {
+        Value::Int(v) => q.bind(v),
+        Value::Bool(v) => q.bind(v),
+        Value::Text(v) => q.bind(v),
+        Value::Date(v) => q.bind(v),
+        Value::DateTime(v) => q.bind(v.map(|t| t.naive_utc().format("%Y-%m-%d %H:%M:%S").to_string())),
+    })
+}
+
+/// `?` 占位符转换为 PostgreSQL 的 `$1`、`$2`，忽略字符串字面量内的问号
+fn numbered(sql: &str) -> String {
+    let mut out = String::with_capacity(sql.len() + 8);
+    let mut n = 0;
+    let mut quoted = false;
+    for c in sql.chars() {
+        match c {
+            '\'' => {
+                quoted = !quoted;
+                out.push(c);
+            }
+            '?' if !quoted => {
+                n += 1;
+                out.push('$');
+                out.push_str(&n.to_string());
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::utils::database::empty_test_db;
+
+    #[test]
+    fn numbered_skips_quoted_placeholders() {
+        assert_eq!(numbered("SELECT ? , '?' , ?"), "SELECT $1 , '?' , $2");
+        assert_eq!(numbered("a = ? AND b LIKE 'x?''?' AND c = ?"), "a = $1 AND b LIKE 'x?''?' AND c = $2");
+        assert_eq!(numbered("SELECT 1"), "SELECT 1");
+    }
+
+    #[test]
+    fn upsert_by_backend() {
+        assert_eq!(
+            Backend::MySql.upsert(&["k"], &["a", "b"]),
+            " ON DUPLICATE KEY UPDATE a = VALUES(a), b = VALUES(b)"
+        );
+        assert_eq!(Backend::Postgres.upsert(&["k", "j"], &["a"]), " ON CONFLICT (k, j) DO UPDATE SET a = excluded.a");
+        assert_eq!(Backend::from_url("postgresql://localhost/db"), Some(Backend::Postgres));
+        assert_eq!(Backend::from_url("SQLITE::memory:"), Some(Backend::Sqlite));
+        assert_eq!(Backend::from_url("redis://localhost"), None);
+    }
+
+    /// 各后端共用的读写校验，`ddl` 建表 `t(id, small, flag, name, day, at)`
+    async fn round_trip(db: &Db, ddl: &str) {
+        query(ddl).execute(db).await.unwrap();
+        let at = Local.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+        let day = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+
+        let id = query("INSERT INTO t (small, flag, name, day, at) VALUES (?, ?, ?, ?, ?)")
+            .bind(7u8)
+            .bind(true)
+            .bind("a?b")
+            .bind(day)
+            .bind(at)
+            .insert(db)
+            .await
+            .unwrap();
+        let empty = query("INSERT INTO t (small, flag, name, day, at) VALUES (?, ?, ?, ?, ?)")
+            .bind(None::<u8>)
+            .bind(Value::Bool(None))
+            .bind(None::<String>)
+            .bind(None::<NaiveDate>)
+            .bind(None::<DateTime<Local>>)
+            .insert(db)
+            .await
+            .unwrap();
+        assert_eq!(empty, id + 1);
+
+        // 字符串字面量中的问号不参与编号
+        let rows = query("SELECT small, flag, name, day, at, '?' AS mark FROM t WHERE id = ? AND name <> ?")
+            .bind(id)
+            .bind("x")
+            .fetch_rows(db)
+            .await
+            .unwrap();
+        let row = &rows[0];
+        assert_eq!(row.get::<u8, _>("small").unwrap(), 7);
+        assert_eq!(row.get::<u64, _>(0).unwrap(), 7);
+        assert!(row.get::<bool, _>("flag").unwrap());
+        assert_eq!(row.get::<String, _>("name").unwrap(), "a?b");
+        assert_eq!(row.get::<NaiveDate, _>("day").unwrap(), day);
+        assert_eq!(row.get::<DateTime<Local>, _>("at").unwrap(), at);
+        assert_eq!(row.get::<String, _>("mark").unwrap(), "?");
+
+        let rows = query("SELECT small, flag, name, day, at FROM t WHERE id = ?").bind(empty).fetch_rows(db).await.unwrap();
+        let row = &rows[0];
+        assert_eq!(row.get::<Option<u8>, _>("small").unwrap(), None);
+        assert_eq!(row.get::<Option<bool>, _>("flag").unwrap(), None);
+        assert_eq!(row.get::<Option<String>, _>("name").unwrap(), None);
+        assert_eq!(row.get::<Option<NaiveDate>, _>("day").unwrap(), None);
+        assert_eq!(row.get::<Option<DateTime<Local>>, _>("at").unwrap(), None);
+
+        // 按时间参数比较
+        let count: i64 = query("SELECT COUNT(*) FROM t WHERE at < ?").bind(at + chrono::Duration::seconds(1)).fetch_scalar(db).await.unwrap();
+        assert_eq!(count, 1);
+        let missing: Option<u64> = query("SELECT id FROM t WHERE id = ?").bind(0u64).fetch_optional_scalar(db).await.unwrap();
+        assert_eq!(missing, None);
+
+        // 整数超出目标类型范围时返回解码错误
+        query("UPDATE t SET small = ? WHERE id = ?").bind(300).bind(id).execute(db).await.unwrap();
+        let err = query("SELECT small FROM t WHERE id = ?").bind(id).fetch_scalar::<u8>(db).await.unwrap_err();
+        assert!(matches!(err, sqlx::Error::ColumnDecode { .. }), "{err:?}");
+        assert_eq!(query("SELECT small FROM t WHERE id = ?").bind(id).fetch_scalar::<u16>(db).await.unwrap(), 300);
+        query("UPDATE t SET small = ? WHERE id = ?").bind(-1).bind(id).execute(db).await.unwrap();
+        let err = query("SELECT small FROM t WHERE id = ?").bind(id).fetch_scalar::<u64>(db).await.unwrap_err();
+        assert!(matches!(err, sqlx::Error::ColumnDecode { .. }), "{err:?}");
+
+        // 未提交的事务在丢弃时回滚
+        let mut tx = db.begin().await.unwrap();
+        query("DELETE FROM t").execute(&mut tx).await.unwrap();
+        let count: i64 = query("SELECT COUNT(*) FROM t").fetch_scalar(&mut tx).await.unwrap();
+        assert_eq!(count, 0);
+        drop(tx);
+        let count: i64 = query("SELECT COUNT(*) FROM t").fetch_scalar(db).await.unwrap();
+        assert_eq!(count, 2);
+
+        let sql = format!("INSERT INTO t (id, name) VALUES (?, ?){}", db.backend().upsert(&["id"], &["name"]));
+        query(&sql).bind(id).bind("upserted").execute(db).await.unwrap();
+        let name: String = query("SELECT name FROM t WHERE id = ?").bind(id).fetch_scalar(db).await.unwrap();
+        assert_eq!(name, "upserted");
+    }
+
+    #[tokio::test]
+    async fn sqlite_round_trip() {
+        let db = empty_test_db();
+        round_trip(
+            &db,
+            "CREATE TABLE t (id INTEGER PRIMARY KEY AUTOINCREMENT, small INTEGER, flag BOOLEAN, name TEXT, day DATE, at DATETIME)",
+        )
+        .await;
+        // 时间以 UTC 文本存储
+        let at = Local.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+        let stored: String = query("SELECT at FROM t WHERE at IS NOT NULL").fetch_scalar(&db).await.unwrap();
+        assert_eq!(stored, at.naive_utc().format("%Y-%m-%d %H:%M:%S").to_string());
+    }
+
+    /// 需要可用的 PostgreSQL，例如
+    /// `AUTHIX_TEST_POSTGRES_URL=postgres://postgres@127.0.0.1/postgres cargo test -- --ignored`
+    #[tokio::test]
+    #[ignore = "requires AUTHIX_TEST_POSTGRES_URL"]
+    async fn postgres_round_trip() {
+        let url = std::env::var("AUTHIX_TEST_POSTGRES_URL").expect("AUTHIX_TEST_POSTGRES_URL");
+        // 临时表仅对当前连接可见
+        let db = Db::connect_lazy(&url, 1).unwrap();
+        round_trip(
+            &db,
+            "CREATE TEMP TABLE t (id BIGSERIAL PRIMARY KEY, small SMALLINT, flag BOOLEAN, name TEXT, day DATE, at TIMESTAMPTZ)",
+        )
+        .await;
+        // INT4 与 INT8 列同样按实际宽度解码
+        let rows = query("SELECT CAST(? AS INTEGER) AS a, CAST(? AS BIGINT) AS b").bind(70000).bind(u32::MAX).fetch_rows(&db).await.unwrap();
+        assert_eq!(rows[0].get::<u32, _>("a").unwrap(), 70000);
+        assert_eq!(rows[0].get::<u32, _>("b").unwrap(), u32::MAX);
+        assert!(rows[0].get::<u16, _>("a").is_err());
+    }
+}
@@ -0,0 +1,101 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, Local, NaiveDate};
+use sqlx::{mysql::MySqlRow, postgres::PgRow, sqlite::SqliteRow, Row as _, TypeInfo, ValueRef};
+
+/// 查询结果行
+pub enum Row {
+    MySql(MySqlRow),
+    Postgres(PgRow),
+    Sqlite(SqliteRow),
+}
+
+impl Row {
+    pub fn get<T: Column, I: ColumnIndex>(&self, index: I) -> Result<T, sqlx::Error> {
+        T::decode(self, index)
+    }
+
+    fn is_null<I: ColumnIndex>(&self, index: I) -> Result<bool, sqlx::Error> {
+        Ok(match self {
+            Row::MySql(r) => r.try_get_raw(index)?.is_null(),
+            Row::Postgres(r) => r.try_get_raw(index)?.is_null(),
+            Row::Sqlite(r) => r.try_get_raw(index)?.is_null(),
+        })
+    }
+}
+
+/// 列名或列序号
+pub trait ColumnIndex:
+    sqlx::ColumnIndex<MySqlRow> + sqlx::ColumnIndex<PgRow> + sqlx::ColumnIndex<SqliteRow> + Display + Copy
+{
+}
+
+impl ColumnIndex for &str {}
+impl ColumnIndex for usize {}
+
+/// 从结果行构造结构体
+pub trait FromRecord: Sized {
+    fn from_record(row: &Row) -> Result<Self, sqlx::Error>;
+}
+
+/// 可从结果行读取的列类型。
+/// MySQL 按表结构中的无符号类型直接解码；PostgreSQL 与 SQLite 没有无符号整数，按有符号读取后转换
+pub trait Column: Sized {
+    fn decode<I: ColumnIndex>(row: &Row, index: I) -> Result<Self, sqlx::Error>;
+}
+
+pub fn decode_error<I: ColumnIndex>(index: I, source: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> sqlx::Error {
+    sqlx::Error::ColumnDecode { index: index.to_string(), source: source.into() }
+}
+
+impl<T: Column> Column for Option<T> {
+    fn decode<I: ColumnIndex>(row: &Row, index: I) -> Result<Self, sqlx::Error> {
+        if row.is_null(index)? {
+            return Ok(None);
+        }
+        T::decode(row, index).map(Some)
+    }
+}
+
+macro_rules! native_column {
+    ($($t:ty),*) => {$(
+        impl Column for $t {
+            fn decode<I: ColumnIndex>(row: &Row, index: I) -> Result<Self, sqlx::Error> {
+                match row {
+                    Row::MySql(r) => r.try_get(index),
+                    Row::Postgres(r) => r.try_get(index),
+                    Row::Sqlite(r) => r.try_get(index),
+                }
+            }
+        }
+    )*};
+}
+
+native_column!(String, bool, NaiveDate, DateTime<Local>);
+
+/// PostgreSQL 整数列按实际宽度读取
+fn pg_int<I: ColumnIndex>(row: &PgRow, index: I) -> Result<i64, sqlx::Error> {
+    let type_name = row.try_get_raw(index)?.type_info().name().to_owned();
+    match type_name.as_str() {
+        "INT2" => row.try_get::<i16, _>(index).map(i64::from),
+        "INT4" => row.try_get::<i32, _>(index).map(i64::from),
+        _ => row.try_get::<i64, _>(index),
+    }
+}
+
+macro_rules! int_column {
+    ($($t:ty),*) => {$(
+        impl Column for $t {
+            fn decode<I: ColumnIndex>(row: &Row, index: I) -> Result<Self, sqlx::Error> {
+                let value = match row {
+                    Row::MySql(r) => return r.try_get(index),
+                    Row::Postgres(r) => pg_int(r, index)?,
+                    Row::Sqlite(r) => r.try_get::<i64, _>(index)?,
+                };
+                <$t>::try_from(value).map_err(|e| decode_error(index, e))
+            }
+        }
+    )*};
+}
+
+int_column!(u8, u16, u32, u64, i64);
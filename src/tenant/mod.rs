@@ -0,0 +1,307 @@
+use std::sync::Arc;
+
+use axum::{async_trait, extract::{FromRef, FromRequestParts, Query, State}, http::{header::HOST, request::Parts, HeaderMap, StatusCode}, Json};
+use headers::Header;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::{cache::{self, CodeStore}, common::{PageQuery, PageResult, TenantIdHeader, R}, config::ConfigHandle, errors::{AuthixError, AuthixResult}, i18n::{self, Msg}, storage::{self, Db, FromRecord, Row}};
+
+pub mod settings;
+
+pub use settings::TenantSettings;
+use settings::TENANT_SETTINGS_TABLE_NAME;
+
+pub const TENANT_TABLE_NAME: &str = "i18n_tenants";
+/// 请求未携带任何租户信息时使用的租户编码
+pub const DEFAULT_TENANT_CODE: &str = "default";
+/// 客户端标识请求头
+const CLIENT_ID_HEADER: &str = "client_id";
+/// 租户状态：启用
+pub const TENANT_STATUS_ENABLED: u8 = 1;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Tenant {
+    pub id: u64,
+    pub code: String,
+    pub name: String,
+    pub domain: Option<String>,
+    pub client_id: Option<String>,
+    pub status: u8,
+}
+
+impl FromRecord for Tenant {
+    fn from_record(row: &Row) -> Result<Self, sqlx::Error> {
+        Ok(Tenant {
+            id: row.get("id")?,
+            code: row.get("code")?,
+            name: row.get("name")?,
+            domain: row.get("domain")?,
+            client_id: row.get("client_id")?,
+            status: row.get("status")?,
+        })
+    }
+}
+
+/// 当前请求的租户及其认证配置，传递给各 Provider
+#[derive(Debug, Clone)]
+pub struct TenantContext {
+    pub tenant: Tenant,
+    pub settings: TenantSettings,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateTenantRequest {
+    pub code: String,
+    pub name: String,
+    pub domain: Option<String>,
+    pub client_id: Option<String>,
+}
+
+#[async_trait]
+pub trait TenantProvider: Send + Sync {
+    async fn get_tenant_by_id(&self, id: u64) -> AuthixResult<Option<Tenant>>;
+    async fn get_tenant_by_code(&self, code: String) -> AuthixResult<Option<Tenant>>;
+    async fn get_tenant_by_domain(&self, domain: String) -> AuthixResult<Option<Tenant>>;
+    async fn get_tenant_by_client_id(&self, client_id: String) -> AuthixResult<Option<Tenant>>;
+    async fn create_tenant(&self, req: CreateTenantRequest) -> AuthixResult<Tenant>;
+    async fn list_tenants(&self, page: isize, page_size: isize) -> AuthixResult<PageResult<Tenant>>;
+    async fn get_tenant_settings(&self, tenant_id: u64) -> AuthixResult<TenantSettings>;
+    /// 保存租户覆盖的配置项，未覆盖的字段读取时取当前全局配置
+    async fn update_tenant_settings(&self, tenant_id: u64, overrides: serde_json::Map<String, serde_json::Value>) -> AuthixResult<()>;
+}
+
+/// 租户服务，租户配置经 `codes` 缓存，未配置的字段取 `config` 中的默认值
+pub struct TenantService {
+    db: Db,
+    codes: Arc<dyn CodeStore>,
+    config: ConfigHandle,
+}
+
+impl TenantService {
+    pub fn new(db: Db, codes: Arc<dyn CodeStore>, config: ConfigHandle) -> Self {
+        Self { db, codes, config }
+    }
+
+    async fn get_tenant_by(&self, column: &str, value: String) -> AuthixResult<Option<Tenant>> {
+        let pool = &self.db;
+        let tenant = storage::query(format!("SELECT id, code, name, domain, client_id, status FROM {} WHERE {} = ?", TENANT_TABLE_NAME, column))
+            .bind(value)
+            .fetch_optional(pool)
+            .await?;
+        Ok(tenant)
+    }
+
+    /// 租户覆盖的配置项（JSON），优先读取缓存，缓存异常时降级读库
+    async fn get_overrides(&self, tenant_id: u64) -> AuthixResult<String> {
+        match cache::get_tenant_settings(&*self.codes, tenant_id).await {
+            Ok(Some(json)) => return Ok(json),
+            Ok(None) => {}
+            Err(e) => warn!("read tenant settings cache error: {}", e),
+        }
+        let json: Option<String> = storage::query(format!("SELECT settings FROM {} WHERE tenant_id = ?", TENANT_SETTINGS_TABLE_NAME))
+            .bind(tenant_id)
+            .fetch_optional_scalar(&self.db)
+            .await?;
+        let json = json.unwrap_or_else(|| "{}".to_owned());
+        if let Err(e) = cache::save_tenant_settings(&*self.codes, tenant_id, &json).await {
+            warn!("save tenant settings cache error: {}", e);
+        }
+        Ok(json)
+    }
+}
+
+#[async_trait]
+impl TenantProvider for TenantService {
+    async fn get_tenant_by_id(&self, id: u64) -> AuthixResult<Option<Tenant>> {
+        let pool = &self.db;
+        let tenant = storage::query(format!("SELECT id, code, name, domain, client_id, status FROM {} WHERE id = ?", TENANT_TABLE_NAME))
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(tenant)
+    }
+
+    async fn get_tenant_by_code(&self, code: String) -> AuthixResult<Option<Tenant>> {
+        self.get_tenant_by("code", code).await
+    }
+
+    async fn get_tenant_by_domain(&self, domain: String) -> AuthixResult<Option<Tenant>> {
+        self.get_tenant_by("domain", domain).await
+    }
+
+    async fn get_tenant_by_client_id(&self, client_id: String) -> AuthixResult<Option<Tenant>> {
+        self.get_tenant_by("client_id", client_id).await
+    }
+
+    async fn create_tenant(&self, req: CreateTenantRequest) -> AuthixResult<Tenant> {
+        let pool = &self.db;
+        let id = storage::query(format!("INSERT INTO {} (code, name, domain, client_id, status) VALUES (?, ?, ?, ?, ?)", TENANT_TABLE_NAME))
+            .bind(&req.code)
+            .bind(&req.name)
+            .bind(&req.domain)
+            .bind(&req.client_id)
+            .bind(TENANT_STATUS_ENABLED)
+            .insert(pool)
+            .await?;
+        Ok(Tenant {
+            id,
+            code: req.code,
+            name: req.name,
+            domain: req.domain,
+            client_id: req.client_id,
+            status: TENANT_STATUS_ENABLED,
+        })
+    }
+
+    async fn list_tenants(&self, page: isize, page_size: isize) -> AuthixResult<PageResult<Tenant>> {
+        let pool = &self.db;
+        let total: i64 = storage::query(format!("SELECT COUNT(*) FROM {}", TENANT_TABLE_NAME))
+            .fetch_scalar(pool)
+            .await?;
+        let offset = (page.saturating_sub(1)) * page_size;
+        let records = storage::query(format!("SELECT id, code, name, domain, client_id, status FROM {} ORDER BY id LIMIT ? OFFSET ?", TENANT_TABLE_NAME))
+            .bind(page_size as i64)
+            .bind(offset as i64)
+            .fetch_all(pool)
+            .await?;
+        Ok(PageResult { total: total as u64, records })
+    }
+
+    async fn get_tenant_settings(&self, tenant_id: u64) -> AuthixResult<TenantSettings> {
+        let overrides = self.get_overrides(tenant_id).await?;
+        serde_json::from_str(&overrides)
+            .and_then(|value| TenantSettings::from_json(value, &self.config.get()))
+            .map_err(|e| AuthixError::DatabaseError(format!("invalid tenant settings: {}", e)))
+    }
+
+    async fn update_tenant_settings(&self, tenant_id: u64, overrides: serde_json::Map<String, serde_json::Value>) -> AuthixResult<()> {
+        let pool = &self.db;
+        let json = serde_json::to_string(&overrides).map_err(|e| AuthixError::DatabaseError(format!("invalid tenant settings: {}", e)))?;
+        storage::query(format!(
+            "INSERT INTO {} (tenant_id, settings) VALUES (?, ?){}",
+            TENANT_SETTINGS_TABLE_NAME,
+            pool.backend().upsert(&["tenant_id"], &["settings"])
+        ))
+            .bind(tenant_id)
+            .bind(&json)
+            .execute(pool)
+            .await?;
+        if let Err(e) = cache::delete_tenant_settings(&*self.codes, tenant_id).await {
+            warn!("delete tenant settings cache error: {}", e);
+        }
+        Ok(())
+    }
+}
+
+/// 解析当前请求所属租户，优先级：
+/// 1. `tenant_id` 请求头（租户 ID）
+/// 2. `client_id` 请求头（客户端标识）
+/// 3. Host 完整域名匹配，其次取子域名作为租户编码
+/// 4. 以上均未携带时使用默认租户
+pub async fn resolve_tenant(headers: &HeaderMap, tenant_provider: &Arc<dyn TenantProvider>) -> AuthixResult<Tenant> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).map(str::trim).filter(|v| !v.is_empty());
+
+    let tenant = if let Some(tenant_id) = header(TenantIdHeader::name().as_str()) {
+        let id: u64 = tenant_id.parse().map_err(|_| AuthixError::TenantNotFound("tenant.unresolved".into()))?;
+        tenant_provider.get_tenant_by_id(id).await?
+    } else if let Some(client_id) = header(CLIENT_ID_HEADER) {
+        tenant_provider.get_tenant_by_client_id(client_id.to_owned()).await?
+    } else {
+        let mut tenant = None;
+        if let Some(host) = header(HOST.as_str()) {
+            let domain = host.split(':').next().unwrap_or(host).to_ascii_lowercase();
+            tenant = tenant_provider.get_tenant_by_domain(domain.clone()).await?;
+            // 仅当存在子域名时才按子域名匹配租户编码，如 acme.auth.example.com
+            if tenant.is_none() && domain.split('.').count() >= 3 {
+                let sub = domain.split('.').next().unwrap_or_default().to_owned();
+                tenant = tenant_provider.get_tenant_by_code(sub).await?;
+            }
+        }
+        match tenant {
+            Some(t) => Some(t),
+            None => tenant_provider.get_tenant_by_code(DEFAULT_TENANT_CODE.to_owned()).await?,
+        }
+    };
+
+    match tenant {
+        Some(t) if t.status == TENANT_STATUS_ENABLED => Ok(t),
+        Some(t) => Err(AuthixError::TenantDisabled(Msg::new("tenant.disabled").arg("code", t.code))),
+        None => Err(AuthixError::TenantNotFound("tenant.unresolved".into())),
+    }
+}
+
+/// 当前请求所属租户及其配置，租户服务取自路由状态
+pub struct CurrentTenant(pub TenantContext);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for CurrentTenant
+where
+    S: Send + Sync,
+    Arc<dyn TenantProvider>: FromRef<S>,
+{
+    type Rejection = AuthixError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let tenant_provider = Arc::<dyn TenantProvider>::from_ref(state);
+        let tenant = resolve_tenant(&parts.headers, &tenant_provider).await?;
+        let settings = tenant_provider.get_tenant_settings(tenant.id).await?;
+        i18n::prefer(settings.locale.as_deref());
+        Ok(CurrentTenant(TenantContext { tenant, settings }))
+    }
+}
+
+pub async fn current_tenant(CurrentTenant(ctx): CurrentTenant) -> (StatusCode, Json<R<Tenant>>) {
+    (StatusCode::OK, Json(R::ok_data(ctx.tenant)))
+}
+
+pub async fn create_tenant(
+    State(tenant_provider): State<Arc<dyn TenantProvider>>,
+    Json(payload): Json<CreateTenantRequest>,
+) -> AuthixResult<(StatusCode, Json<R<Tenant>>)> {
+    if payload.code.trim().is_empty() || payload.name.trim().is_empty() {
+        return Err(AuthixError::InvalidParameter("tenant.code_name_required".into()));
+    }
+    if tenant_provider.get_tenant_by_code(payload.code.clone()).await?.is_some() {
+        return Err(AuthixError::Conflict("tenant.code_exists".into()));
+    }
+    let tenant = tenant_provider.create_tenant(payload).await?;
+    Ok((StatusCode::OK, Json(R::ok_data(tenant))))
+}
+
+pub async fn list_tenants(
+    State(tenant_provider): State<Arc<dyn TenantProvider>>,
+    Query(q): Query<PageQuery>,
+) -> AuthixResult<(StatusCode, Json<R<PageResult<Tenant>>>)> {
+    let page = q.page.unwrap_or(1).max(1);
+    let page_size = q.page_size.unwrap_or(20).clamp(1, 200);
+    let data = tenant_provider.list_tenants(page, page_size).await?;
+    Ok((StatusCode::OK, Json(R::ok_data(data))))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{config::AuthixConfig, state::test_state};
+
+    #[tokio::test]
+    async fn settings_merge_overrides_with_current_config() {
+        let state = test_state(AuthixConfig::default()).await;
+        let config = state.config.get();
+        let (settings, overrides) = TenantSettings::parse_overrides(json!({ "mfa_required": true, "unknown": 1 }), &config).unwrap();
+        assert!(settings.mfa_required);
+        assert_eq!(overrides.keys().collect::<Vec<_>>(), ["mfa_required"]);
+        state.tenants.update_tenant_settings(1, overrides).await.unwrap();
+
+        // 全局配置变更后，未覆盖的字段随之生效
+        let mut next = (*config).clone();
+        next.token.access_token_exp = config.token.access_token_exp * 2;
+        state.config.replace(next);
+        let settings = state.tenants.get_tenant_settings(1).await.unwrap();
+        assert!(settings.mfa_required);
+        assert_eq!(settings.access_token_exp, config.token.access_token_exp * 2);
+        assert_eq!(state.tenants.get_tenant_settings(2).await.unwrap().access_token_exp, config.token.access_token_exp * 2);
+    }
+}
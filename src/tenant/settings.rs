@@ -0,0 +1,281 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use axum::{extract::{Path, State}, http::StatusCode, Json};
+use serde::{Deserialize, Serialize};
+
+use crate::{common::R, config::{AuthixConfig, ConfigHandle, TemplatesConfig, MAX_REFRESH_TOKEN_EXP}, errors::{AuthixError, AuthixResult}, i18n::{Locale, Msg}, provider::{login::LoginService, register::RegisterService}, tenant::TenantProvider};
+
+pub const TENANT_SETTINGS_TABLE_NAME: &str = "i18n_tenant_settings";
+
+/// 租户认证配置，未配置的字段使用全局配置中的默认值，见 [`TenantSettings::from_json`]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TenantSettings {
+    /// 启用的登录方式
+    pub login_types: Vec<String>,
+    /// 启用的注册方式
+    pub register_types: Vec<String>,
+    /// 是否允许用户自助注册
+    pub allow_register: bool,
+    /// 租户默认语言，请求未携带 `Accept-Language` 时使用，未配置时使用全局默认语言
+    pub locale: Option<String>,
+    /// 密码登录是否需要额外校验短信/邮箱验证码
+    pub mfa_required: bool,
+    /// 访问令牌有效期（毫秒）
+    pub access_token_exp: usize,
+    /// 刷新令牌有效期（毫秒）
+    pub refresh_token_exp: usize,
+    pub password_policy: PasswordPolicy,
+    pub branding: Branding,
+    /// 第三方登录配置，键为登录方式（github、google、wechat、oidc）
+    pub social: BTreeMap<String, SocialProviderSettings>,
+}
+
+impl TenantSettings {
+    /// 租户未配置时的默认值，取自全局配置
+    pub fn defaults(config: &AuthixConfig) -> Self {
+        Self {
+            login_types: config.providers.login_types.clone(),
+            register_types: config.providers.register_types.clone(),
+            allow_register: config.providers.allow_register,
+            locale: None,
+            mfa_required: false,
+            access_token_exp: config.token.access_token_exp,
+            refresh_token_exp: config.token.refresh_token_exp,
+            password_policy: config.password.clone(),
+            branding: Branding::default(),
+            social: BTreeMap::new(),
+        }
+    }
+
+    /// 解析租户配置，缺失的字段使用全局配置中的默认值
+    pub fn from_json(value: serde_json::Value, config: &AuthixConfig) -> serde_json::Result<Self> {
+        let serde_json::Value::Object(fields) = value else {
+            return serde_json::from_value(value);
+        };
+        let mut merged = serde_json::to_value(Self::defaults(config))?;
+        if let Some(defaults) = merged.as_object_mut() {
+            defaults.extend(fields);
+        }
+        serde_json::from_value(merged)
+    }
+
+    /// 解析租户配置的覆盖项，返回合并全局配置后的结果与去掉未知字段后的覆盖项。
+    /// 只保存覆盖项，读取时再与当前全局配置合并，全局配置热加载后未覆盖的字段随之生效
+    pub fn parse_overrides(value: serde_json::Value, config: &AuthixConfig) -> serde_json::Result<(Self, serde_json::Map<String, serde_json::Value>)> {
+        let mut overrides: serde_json::Map<String, serde_json::Value> = serde_json::from_value(value)?;
+        let defaults = serde_json::to_value(Self::defaults(config))?;
+        overrides.retain(|key, _| defaults.get(key).is_some());
+        let settings = Self::from_json(serde_json::Value::Object(overrides.clone()), config)?;
+        Ok((settings, overrides))
+    }
+
+    pub fn login_type_enabled(&self, login_type: &str) -> bool {
+        self.login_types.iter().any(|t| t == login_type)
+    }
+
+    pub fn register_type_enabled(&self, register_type: &str) -> bool {
+        self.register_types.iter().any(|t| t == register_type)
+    }
+
+    /// 校验配置合法性，返回第一条错误信息
+    pub fn validate(&self, login: &LoginService, register: &RegisterService) -> Result<(), Msg> {
+        if let Some(t) = self.login_types.iter().find(|t| !login.supports(t))
+            .or_else(|| self.register_types.iter().find(|t| !register.supports(t)))
+        {
+            return Err(Msg::new("tenant.unknown_auth_type").arg("type", t));
+        }
+        if self.access_token_exp == 0 || self.refresh_token_exp == 0 {
+            return Err("tenant.token_exp_positive".into());
+        }
+        if self.access_token_exp > self.refresh_token_exp {
+            return Err("tenant.token_exp_order".into());
+        }
+        // 吊销标记过期后，超出其保留时长的刷新令牌会重新可用
+        if self.refresh_token_exp > MAX_REFRESH_TOKEN_EXP {
+            return Err(Msg::new("tenant.refresh_token_exp_max").arg("max", MAX_REFRESH_TOKEN_EXP));
+        }
+        if let Some(locale) = &self.locale
+            && Locale::parse(locale).is_none()
+        {
+            return Err(Msg::new("tenant.unsupported_locale").arg("locale", locale));
+        }
+        for (login_type, social) in &self.social {
+            if !login.supports(login_type) {
+                return Err(Msg::new("tenant.unknown_auth_type").arg("type", login_type));
+            }
+            social.validate().map_err(|m| m.arg("type", login_type))?;
+        }
+        self.password_policy.validate()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    pub max_length: usize,
+    pub require_lowercase: bool,
+    pub require_uppercase: bool,
+    pub require_digit: bool,
+    pub require_symbol: bool,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 8,
+            max_length: 32,
+            require_lowercase: false,
+            require_uppercase: false,
+            require_digit: false,
+            require_symbol: false,
+        }
+    }
+}
+
+impl PasswordPolicy {
+    pub fn validate(&self) -> Result<(), Msg> {
+        if self.min_length < 6 || self.min_length > self.max_length || self.max_length > 128 {
+            return Err("password.policy_length".into());
+        }
+        Ok(())
+    }
+
+    /// 按策略校验密码，字符集沿用 `is_valid_password` 的规则
+    pub fn check(&self, password: &str) -> Result<(), Msg> {
+        let len = password.chars().count();
+        let charset_ok = password.chars().all(|c| c.is_ascii_alphanumeric() || "_-.@#$%^&*".contains(c));
+        if len < self.min_length || len > self.max_length || !charset_ok {
+            return Err(Msg::new("password.invalid").arg("min", self.min_length).arg("max", self.max_length));
+        }
+        if self.require_lowercase && !password.chars().any(|c| c.is_ascii_lowercase()) {
+            return Err("password.require_lowercase".into());
+        }
+        if self.require_uppercase && !password.chars().any(|c| c.is_ascii_uppercase()) {
+            return Err("password.require_uppercase".into());
+        }
+        if self.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+            return Err("password.require_digit".into());
+        }
+        if self.require_symbol && !password.chars().any(|c| !c.is_ascii_alphanumeric()) {
+            return Err("password.require_symbol".into());
+        }
+        Ok(())
+    }
+}
+
+/// 第三方登录（OAuth2 / OIDC）配置
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SocialProviderSettings {
+    pub client_id: String,
+    pub client_secret: String,
+    /// OIDC 签发方，用于发现端点并校验 ID Token；google 默认为 `https://accounts.google.com`
+    pub issuer: Option<String>,
+    /// 为空时使用各登录方式的默认权限范围
+    pub scopes: Vec<String>,
+    /// 允许的回调地址，发起授权时传入的 `redirect_uri` 必须在列表中
+    pub redirect_uris: Vec<String>,
+    /// 授权、令牌、用户信息端点，覆盖默认值或发现结果
+    pub authorize_url: Option<String>,
+    pub token_url: Option<String>,
+    pub userinfo_url: Option<String>,
+    /// 以已验证的邮箱匹配已有本地用户
+    pub match_verified_email: bool,
+    /// 未匹配到本地用户时自动创建
+    pub auto_create_user: bool,
+}
+
+impl Default for SocialProviderSettings {
+    fn default() -> Self {
+        Self {
+            client_id: String::new(),
+            client_secret: String::new(),
+            issuer: None,
+            scopes: Vec::new(),
+            redirect_uris: Vec::new(),
+            authorize_url: None,
+            token_url: None,
+            userinfo_url: None,
+            match_verified_email: true,
+            auto_create_user: true,
+        }
+    }
+}
+
+impl SocialProviderSettings {
+    pub fn validate(&self) -> Result<(), Msg> {
+        if self.client_id.is_empty() || self.client_secret.is_empty() {
+            return Err("tenant.social_client_required".into());
+        }
+        if self.redirect_uris.is_empty() {
+            return Err("tenant.social_redirect_required".into());
+        }
+        Ok(())
+    }
+}
+
+/// 通知品牌信息，用于短信签名和邮件发件人
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Branding {
+    pub display_name: Option<String>,
+    pub logo_url: Option<String>,
+    pub sms_signature: Option<String>,
+    pub email_sender: Option<String>,
+}
+
+impl Branding {
+    /// 按模板生成通知内容，模板来自配置 `templates`
+    fn render(&self, template: &str, vars: &[(&str, &str)]) -> String {
+        let signature = self.sms_signature.as_deref().or(self.display_name.as_deref()).unwrap_or("Authix");
+        vars.iter()
+            .fold(template.replace("{signature}", signature), |text, (name, value)| text.replace(&format!("{{{}}}", name), value))
+    }
+
+    /// 生成验证码通知内容
+    pub fn verify_code_message(&self, templates: &TemplatesConfig, code: &str, ttl_secs: u64) -> String {
+        let minutes = (ttl_secs / 60).to_string();
+        self.render(&templates.verify_code, &[("code", code), ("minutes", &minutes)])
+    }
+
+    /// 生成组织邀请通知内容
+    pub fn invitation_message(&self, templates: &TemplatesConfig, org_name: &str, token: &str, ttl_secs: u64) -> String {
+        let days = (ttl_secs / 86400).to_string();
+        self.render(&templates.invitation, &[("org_name", org_name), ("token", token), ("days", &days)])
+    }
+
+    /// 生成新设备登录提醒内容
+    pub fn new_device_message(&self, templates: &TemplatesConfig, login_at: &str, ip: &str) -> String {
+        self.render(&templates.new_device, &[("login_at", login_at), ("ip", ip)])
+    }
+}
+
+pub async fn get_tenant_settings(
+    State(tenant_provider): State<Arc<dyn TenantProvider>>,
+    Path(tenant_id): Path<u64>,
+) -> AuthixResult<(StatusCode, Json<R<TenantSettings>>)> {
+    if tenant_provider.get_tenant_by_id(tenant_id).await?.is_none() {
+        return Err(AuthixError::TenantNotFound("tenant.not_found".into()));
+    }
+    let settings = tenant_provider.get_tenant_settings(tenant_id).await?;
+    Ok((StatusCode::OK, Json(R::ok_data(settings))))
+}
+
+pub async fn update_tenant_settings(
+    State(tenant_provider): State<Arc<dyn TenantProvider>>,
+    State(login): State<Arc<LoginService>>,
+    State(register): State<Arc<RegisterService>>,
+    State(config): State<ConfigHandle>,
+    Path(tenant_id): Path<u64>,
+    Json(payload): Json<serde_json::Value>,
+) -> AuthixResult<(StatusCode, Json<R<TenantSettings>>)> {
+    let (settings, overrides) = TenantSettings::parse_overrides(payload, &config.get())
+        .map_err(|e| AuthixError::InvalidParameter(Msg::new("tenant.settings_invalid").arg("reason", e)))?;
+    settings.validate(&login, &register).map_err(AuthixError::InvalidParameter)?;
+    if tenant_provider.get_tenant_by_id(tenant_id).await?.is_none() {
+        return Err(AuthixError::TenantNotFound("tenant.not_found".into()));
+    }
+    tenant_provider.update_tenant_settings(tenant_id, overrides).await?;
+    Ok((StatusCode::OK, Json(R::ok_data(settings))))
+}
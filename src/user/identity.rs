@@ -0,0 +1,222 @@
+use std::sync::Arc;
+
+use axum::{extract::{Path, State}, http::StatusCode, Json};
+use axum_extra::extract::TypedHeader;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    audit::{AuditEvent, AuditEventType, AuditOutcome, AuditProvider},
+    cache::CodeStore,
+    common::{ClientInfo, UidHeader, R},
+    config::ConfigHandle,
+    enums::{AuthEnum, IdentityType},
+    errors::{AuthixError, AuthixResult},
+    provider::login::LoginRequest,
+    state::AppState,
+    storage::{FromRecord, Row},
+    tenant::{CurrentTenant, TenantContext},
+    utils::regex::{is_valid_email, is_valid_phone, is_valid_username},
+};
+
+use super::{User, UserProvider};
+
+pub const USER_IDENTITY_TABLE_NAME: &str = "i18n_user_identities";
+/// 注销用户的登录标识，恢复注销时移回
+pub const DELETED_USER_IDENTITY_TABLE_NAME: &str = "i18n_deleted_user_identities";
+
+/// 用户登录标识，一个用户可关联多个
+#[derive(Debug, Clone, Serialize)]
+pub struct UserIdentity {
+    pub id: u64,
+    #[serde(skip)]
+    pub tenant_id: u64,
+    #[serde(skip)]
+    pub user_id: u64,
+    #[serde(rename = "type")]
+    pub identity_type: IdentityType,
+    /// 第三方身份的登录方式，本地标识为空
+    pub provider: String,
+    pub identifier: String,
+    /// 完成验证码或第三方授权的时间
+    pub verified_at: Option<DateTime<Local>>,
+    pub created_at: Option<DateTime<Local>>,
+}
+
+impl FromRecord for UserIdentity {
+    fn from_record(row: &Row) -> Result<Self, sqlx::Error> {
+        Ok(UserIdentity {
+            id: row.get("id")?,
+            tenant_id: row.get("tenant_id")?,
+            user_id: row.get("user_id")?,
+            identity_type: row.get("identity_type")?,
+            provider: row.get("provider")?,
+            identifier: row.get("identifier")?,
+            verified_at: row.get("verified_at")?,
+            created_at: row.get("created_at")?,
+        })
+    }
+}
+
+impl UserIdentity {
+    pub fn new(user: &User, identity_type: IdentityType, provider: &str, identifier: &str) -> Self {
+        Self {
+            id: 0,
+            tenant_id: user.tenant_id,
+            user_id: user.id,
+            identity_type,
+            provider: provider.to_string(),
+            identifier: identifier.to_string(),
+            verified_at: None,
+            created_at: None,
+        }
+    }
+
+    pub fn verified(self) -> Self {
+        Self { verified_at: Some(Local::now()), ..self }
+    }
+
+    /// 用户名/手机号/邮箱，创建用户时写入
+    pub(crate) fn local_identities(user: &User) -> Vec<UserIdentity> {
+        [(IdentityType::Username, &user.username), (IdentityType::Phone, &user.phone), (IdentityType::Email, &user.email)]
+            .into_iter()
+            .filter_map(|(t, v)| v.as_deref().map(|v| UserIdentity::new(user, t, "", v)))
+            .collect()
+    }
+}
+
+/// 标识已被其他用户使用
+pub(crate) fn identity_exists(identity_type: IdentityType) -> AuthixError {
+    let key = match identity_type {
+        IdentityType::Username => "user.username_exists",
+        IdentityType::Phone => "user.phone_exists",
+        IdentityType::Email => "user.email_exists",
+        IdentityType::External => "user.identity_exists",
+    };
+    AuthixError::UserAlreadyExists(key.into())
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LinkIdentityRequest {
+    #[serde(rename = "type")]
+    pub identity_type: IdentityType,    // username / phone / email
+    pub identifier: String,
+    #[serde(default)]
+    pub code: Option<String>,           // 手机号/邮箱的验证码
+}
+
+/// 当前租户下的登录用户
+pub(super) async fn current_user(users: &dyn UserProvider, ctx: &TenantContext, uid: &UidHeader) -> AuthixResult<User> {
+    let id: u64 = uid.0.parse().map_err(|_| AuthixError::InvalidParameter("param.invalid_uid".into()))?;
+    users
+        .get_user_by_id(id)
+        .await?
+        .filter(|u| u.tenant_id == ctx.tenant.id)
+        .ok_or_else(|| AuthixError::UserNotFound("user.not_found".into()))
+}
+
+pub async fn list_identities(
+    CurrentTenant(ctx): CurrentTenant,
+    State(users): State<Arc<dyn UserProvider>>,
+    TypedHeader(uid): TypedHeader<UidHeader>,
+) -> AuthixResult<(StatusCode, Json<R<Vec<UserIdentity>>>)> {
+    let user = current_user(&*users, &ctx, &uid).await?;
+    let list = users.list_identities(user.id).await?;
+    Ok((StatusCode::OK, Json(R::ok_data(list))))
+}
+
+/// 关联用户名/手机号/邮箱，手机号和邮箱需先通过 `/auth/code/send` 获取验证码
+#[allow(clippy::too_many_arguments)]
+pub async fn link_identity(
+    CurrentTenant(ctx): CurrentTenant,
+    client: ClientInfo,
+    State(users): State<Arc<dyn UserProvider>>,
+    State(codes): State<Arc<dyn CodeStore>>,
+    State(audit): State<Arc<dyn AuditProvider>>,
+    State(config): State<ConfigHandle>,
+    TypedHeader(uid): TypedHeader<UidHeader>,
+    Json(payload): Json<LinkIdentityRequest>,
+) -> AuthixResult<(StatusCode, Json<R<UserIdentity>>)> {
+    let user = current_user(&*users, &ctx, &uid).await?;
+    let (valid, invalid_key) = match payload.identity_type {
+        IdentityType::Username => (is_valid_username(&payload.identifier), "param.username_invalid"),
+        IdentityType::Phone => (is_valid_phone(&payload.identifier), "param.phone_invalid"),
+        IdentityType::Email => (is_valid_email(&payload.identifier), "param.email_invalid"),
+        IdentityType::External => return Err(AuthixError::InvalidParameter("user.identity_type_unsupported".into())),
+    };
+    if !valid {
+        return Err(AuthixError::InvalidParameter(invalid_key.into()));
+    }
+    let mut identity = UserIdentity::new(&user, payload.identity_type, "", &payload.identifier);
+    if payload.identity_type != IdentityType::Username {
+        let code = payload.code.as_deref().filter(|c| !c.is_empty())
+            .ok_or_else(|| AuthixError::InvalidParameter("user.identity_code_required".into()))?;
+        crate::cache::verify_code(&*codes, ctx.tenant.id, &payload.identifier, code, AuthEnum::Login, config.get().verify_code.ttl_secs).await?;
+        identity = identity.verified();
+    }
+    let event = AuditEvent::new(AuditEventType::IdentityLink, AuditOutcome::Success, &client)
+        .tenant(ctx.tenant.id)
+        .actor(user.id)
+        .subject_id(Some(user.id))
+        .subject(&payload.identifier);
+    record(&*audit, event, users.link_identity(identity).await).await
+}
+
+/// 关联第三方身份，请求体与对应登录方式的登录请求相同（如 `code`、`state`）
+pub async fn link_external_identity(
+    CurrentTenant(ctx): CurrentTenant,
+    client: ClientInfo,
+    State(state): State<AppState>,
+    State(audit): State<Arc<dyn AuditProvider>>,
+    TypedHeader(uid): TypedHeader<UidHeader>,
+    Json(payload): Json<LoginRequest>,
+) -> AuthixResult<(StatusCode, Json<R<UserIdentity>>)> {
+    let user = current_user(&*state.users, &ctx, &uid).await?;
+    let subject = state.login.identify(&ctx, &payload, &state).await?;
+    let identity = UserIdentity::new(&user, IdentityType::External, &payload.login_type, &subject).verified();
+    let event = AuditEvent::new(AuditEventType::IdentityLink, AuditOutcome::Success, &client)
+        .tenant(ctx.tenant.id)
+        .actor(user.id)
+        .subject_id(Some(user.id))
+        .subject(&format!("{}:{}", payload.login_type, subject));
+    record(&*audit, event, state.users.link_identity(identity).await).await
+}
+
+/// 解除关联，不能解除最后一个登录标识
+pub async fn unlink_identity(
+    CurrentTenant(ctx): CurrentTenant,
+    client: ClientInfo,
+    State(users): State<Arc<dyn UserProvider>>,
+    State(audit): State<Arc<dyn AuditProvider>>,
+    TypedHeader(uid): TypedHeader<UidHeader>,
+    Path(id): Path<u64>,
+) -> AuthixResult<(StatusCode, Json<R<String>>)> {
+    let user = current_user(&*users, &ctx, &uid).await?;
+    let identities = users.list_identities(user.id).await?;
+    let identity = identities.iter().find(|i| i.id == id)
+        .ok_or_else(|| AuthixError::NotFound("user.identity_not_found".into()))?;
+    if identities.len() <= 1 {
+        return Err(AuthixError::Conflict("user.identity_last".into()));
+    }
+    users.unlink_identity(user.id, id).await?;
+    let event = AuditEvent::new(AuditEventType::IdentityUnlink, AuditOutcome::Success, &client)
+        .tenant(ctx.tenant.id)
+        .actor(user.id)
+        .subject_id(Some(user.id))
+        .subject(&identity.identifier);
+    audit.record(event).await;
+    Ok((StatusCode::OK, Json(R::<String>::ok())))
+}
+
+async fn record(audit: &dyn AuditProvider, event: AuditEvent, result: AuthixResult<UserIdentity>) -> AuthixResult<(StatusCode, Json<R<UserIdentity>>)> {
+    match result {
+        Ok(identity) => {
+            audit.record(event).await;
+            Ok((StatusCode::OK, Json(R::ok_data(identity))))
+        }
+        Err(e) => {
+            audit.record(AuditEvent { outcome: AuditOutcome::Failure, ..event }.reason(e.to_string())).await;
+            Err(e)
+        }
+    }
+}
@@ -0,0 +1,390 @@
+use std::{collections::BTreeMap, sync::Mutex};
+
+use axum::async_trait;
+use chrono::{DateTime, Local, NaiveDate};
+
+use crate::{
+    common::CursorPage,
+    enums::{IdentityType, UserStatus},
+    errors::{AuthixError, AuthixResult},
+    i18n::Msg,
+};
+
+use super::{identity_exists, parse_cursor, ProfileInfo, UpdateUserRequest, User, UserIdentity, UserProvider, UserSearchQuery, UserSummary};
+
+/// 用户记录，对应用户表中 `User` 之外的列
+struct StoredUser {
+    user: User,
+    nickname: Option<String>,
+    avatar: Option<String>,
+    gender: Option<u8>,
+    birthday: Option<NaiveDate>,
+    last_login: Option<DateTime<Local>>,
+    created_at: DateTime<Local>,
+    deleted_at: Option<DateTime<Local>>,
+    /// 注销前的状态
+    prev_status: Option<UserStatus>,
+    /// 注销时移出的登录标识，及其是否为用户的用户名/手机号/邮箱
+    deleted_identities: Vec<(UserIdentity, bool)>,
+}
+
+impl StoredUser {
+    fn profile(&self) -> ProfileInfo {
+        ProfileInfo {
+            username: self.user.username.clone(),
+            nickname: self.nickname.clone(),
+            avatar: self.avatar.clone(),
+            gender: self.gender,
+            birthday: self.birthday,
+            last_login: self.last_login,
+        }
+    }
+
+    fn summary(&self) -> UserSummary {
+        UserSummary {
+            id: self.user.id,
+            tenant_id: self.user.tenant_id,
+            username: self.user.username.clone(),
+            phone: self.user.phone.clone(),
+            email: self.user.email.clone(),
+            nickname: self.nickname.clone(),
+            status: self.user.status,
+            last_login: self.last_login,
+            created_at: Some(self.created_at),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Users {
+    users: BTreeMap<u64, StoredUser>,
+    next_id: u64,
+    identities: BTreeMap<u64, UserIdentity>,
+    next_identity_id: u64,
+}
+
+impl Users {
+    fn find_identity(&self, tenant_id: u64, identity_type: IdentityType, provider: &str, identifier: &str) -> Option<&UserIdentity> {
+        self.identities.values().find(|i| {
+            i.tenant_id == tenant_id && i.identity_type == identity_type && i.provider == provider && i.identifier == identifier
+        })
+    }
+
+    /// 校验用户名/手机号/邮箱未被其他用户关联，与登录标识表唯一索引一致
+    fn check_unique(&self, id: u64, tenant_id: u64, username: Option<&String>, phone: Option<&String>, email: Option<&String>) -> AuthixResult<()> {
+        for (identity_type, value) in [(IdentityType::Username, username), (IdentityType::Phone, phone), (IdentityType::Email, email)] {
+            if let Some(value) = value
+                && self.find_identity(tenant_id, identity_type, "", value).is_some_and(|i| i.user_id != id)
+            {
+                return Err(identity_exists(identity_type));
+            }
+        }
+        Ok(())
+    }
+
+    fn insert_identity(&mut self, identity: UserIdentity) -> AuthixResult<UserIdentity> {
+        if self.find_identity(identity.tenant_id, identity.identity_type, &identity.provider, &identity.identifier).is_some() {
+            return Err(identity_exists(identity.identity_type));
+        }
+        self.next_identity_id += 1;
+        let identity = UserIdentity { id: self.next_identity_id, created_at: Some(Local::now()), ..identity };
+        self.identities.insert(identity.id, identity.clone());
+        Ok(identity)
+    }
+}
+
+/// 用户的用户名/手机号/邮箱列
+fn column_mut(user: &mut User, identity_type: IdentityType) -> Option<&mut Option<String>> {
+    match identity_type {
+        IdentityType::Username => Some(&mut user.username),
+        IdentityType::Phone => Some(&mut user.phone),
+        IdentityType::Email => Some(&mut user.email),
+        IdentityType::External => None,
+    }
+}
+
+/// 内存实现，用于测试及本地开发，重启后数据丢失。
+/// 不写入 Webhook 发件箱，也不清理组织成员关系
+#[derive(Default)]
+pub struct InMemoryUserService {
+    inner: Mutex<Users>,
+}
+
+impl InMemoryUserService {
+    fn lock(&self) -> std::sync::MutexGuard<'_, Users> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[async_trait]
+impl UserProvider for InMemoryUserService {
+    async fn get_user_profile(&self, id: u64) -> AuthixResult<ProfileInfo> {
+        let inner = self.lock();
+        inner
+            .users
+            .get(&id)
+            .filter(|s| s.user.status != UserStatus::Deleted)
+            .map(StoredUser::profile)
+            .ok_or_else(|| AuthixError::UserNotFound("user.not_found".into()))
+    }
+
+    async fn get_user_profiles(&self, ids: Vec<u64>) -> AuthixResult<Vec<ProfileInfo>> {
+        let inner = self.lock();
+        Ok(ids.iter().filter_map(|id| inner.users.get(id)).map(StoredUser::profile).collect())
+    }
+
+    async fn create_user(&self, user: User) -> AuthixResult<User> {
+        let mut inner = self.lock();
+        inner.check_unique(0, user.tenant_id, user.username.as_ref(), user.phone.as_ref(), user.email.as_ref())?;
+        inner.next_id += 1;
+        let user = User { id: inner.next_id, crt_by: None, ..user };
+        for identity in UserIdentity::local_identities(&user) {
+            inner.insert_identity(identity)?;
+        }
+        let stored = StoredUser {
+            user: user.clone(),
+            nickname: None,
+            avatar: None,
+            gender: None,
+            birthday: None,
+            last_login: None,
+            created_at: Local::now(),
+            deleted_at: None,
+            prev_status: None,
+            deleted_identities: Vec::new(),
+        };
+        inner.users.insert(user.id, stored);
+        Ok(user)
+    }
+
+    async fn delete_user(&self, id: u64) -> AuthixResult<()> {
+        let mut inner = self.lock();
+        let Users { users, identities, .. } = &mut *inner;
+        let Some(s) = users.get_mut(&id).filter(|s| s.user.status != UserStatus::Deleted) else { return Ok(()) };
+        // 登录标识移出，释放用户名/手机号/邮箱供重新注册
+        let ids: Vec<u64> = identities.values().filter(|i| i.user_id == id).map(|i| i.id).collect();
+        for identity_id in ids {
+            let Some(identity) = identities.remove(&identity_id) else { continue };
+            let is_primary = identity.provider.is_empty()
+                && column_mut(&mut s.user, identity.identity_type).is_some_and(|c| c.as_ref() == Some(&identity.identifier));
+            s.deleted_identities.push((identity, is_primary));
+        }
+        s.user.username = None;
+        s.user.phone = None;
+        s.user.email = None;
+        s.prev_status = Some(s.user.status);
+        s.user.status = UserStatus::Deleted;
+        s.deleted_at = Some(Local::now());
+        Ok(())
+    }
+
+    async fn restore_user(&self, id: u64) -> AuthixResult<bool> {
+        let mut inner = self.lock();
+        let Some(s) = inner.users.get(&id).filter(|s| s.user.status == UserStatus::Deleted) else { return Ok(false) };
+        // 注销期间已被其他用户使用时恢复失败
+        if let Some((identity, _)) = s.deleted_identities.iter().find(|(i, _)| {
+            inner.find_identity(i.tenant_id, i.identity_type, &i.provider, &i.identifier).is_some()
+        }) {
+            return Err(identity_exists(identity.identity_type));
+        }
+        let Users { users, identities, .. } = &mut *inner;
+        let Some(s) = users.get_mut(&id) else { return Ok(false) };
+        for (identity, is_primary) in std::mem::take(&mut s.deleted_identities) {
+            if is_primary && let Some(column) = column_mut(&mut s.user, identity.identity_type) {
+                *column = Some(identity.identifier.clone());
+            }
+            identities.insert(identity.id, identity);
+        }
+        s.user.status = s.prev_status.take().unwrap_or(UserStatus::Active);
+        s.deleted_at = None;
+        Ok(true)
+    }
+
+    async fn purge_deleted_users(&self, retention_days: u32) -> AuthixResult<u64> {
+        let cutoff = Local::now() - chrono::Duration::days(retention_days as i64);
+        let mut inner = self.lock();
+        let before = inner.users.len();
+        inner.users.retain(|_, s| !(s.user.status == UserStatus::Deleted && s.deleted_at.is_some_and(|t| t < cutoff)));
+        let Users { users, identities, .. } = &mut *inner;
+        identities.retain(|_, i| users.contains_key(&i.user_id));
+        Ok((before - inner.users.len()) as u64)
+    }
+
+    async fn get_user_by_id(&self, id: u64) -> AuthixResult<Option<User>> {
+        Ok(self.lock().users.get(&id).map(|s| s.user.clone()))
+    }
+
+    async fn get_user_by_identity(&self, tenant_id: u64, identity_type: IdentityType, provider: &str, identifier: &str) -> AuthixResult<Option<User>> {
+        let inner = self.lock();
+        let user_id = inner.find_identity(tenant_id, identity_type, provider, identifier).map(|i| i.user_id);
+        Ok(user_id.and_then(|id| inner.users.get(&id)).map(|s| s.user.clone()))
+    }
+
+    async fn get_user_by_username(&self, tenant_id: u64, username: String) -> AuthixResult<Option<User>> {
+        self.get_user_by_identity(tenant_id, IdentityType::Username, "", &username).await
+    }
+
+    async fn get_user_by_phone(&self, tenant_id: u64, phone: String) -> AuthixResult<Option<User>> {
+        self.get_user_by_identity(tenant_id, IdentityType::Phone, "", &phone).await
+    }
+
+    async fn get_user_by_email(&self, tenant_id: u64, email: String) -> AuthixResult<Option<User>> {
+        self.get_user_by_identity(tenant_id, IdentityType::Email, "", &email).await
+    }
+
+    async fn update_last_login_time(&self, id: u64) -> AuthixResult<User> {
+        let mut inner = self.lock();
+        let s = inner.users.get_mut(&id).ok_or_else(|| AuthixError::UserNotFound("user.not_found".into()))?;
+        s.last_login = Some(Local::now());
+        Ok(s.user.clone())
+    }
+
+    async fn search_users(&self, query: &UserSearchQuery) -> AuthixResult<CursorPage<UserSummary>> {
+        let limit = query.limit.unwrap_or(20).clamp(1, 200) as usize;
+        let desc = query.order.as_deref() != Some("asc");
+        // 与数据库实现一致：排序值统一为整数，last_login 为空时按 0 处理
+        let sort_value: fn(&StoredUser) -> i64 = match query.sort.as_deref().unwrap_or("id") {
+            "id" => |s| s.user.id as i64,
+            "created_at" => |s| s.created_at.timestamp(),
+            "last_login" => |s| s.last_login.map_or(0, |t| t.timestamp()),
+            other => return Err(AuthixError::InvalidParameter(Msg::new("param.unsupported_sort_field").arg("field", other))),
+        };
+        let cursor = parse_cursor(query.cursor.as_deref())?;
+        let prefix_match = |value: &Option<String>, prefix: &Option<String>| match prefix.as_deref().filter(|p| !p.is_empty()) {
+            Some(prefix) => value.as_deref().is_some_and(|v| v.starts_with(prefix)),
+            None => true,
+        };
+        let in_range = |value: Option<DateTime<Local>>, from: Option<DateTime<Local>>, to: Option<DateTime<Local>>| {
+            from.is_none_or(|from| value.is_some_and(|v| v >= from)) && to.is_none_or(|to| value.is_some_and(|v| v < to))
+        };
+
+        let inner = self.lock();
+        let mut matched: Vec<((i64, u64), &StoredUser)> = inner
+            .users
+            .values()
+            .filter(|s| query.tenant_id.is_none_or(|t| s.user.tenant_id == t))
+            .filter(|s| prefix_match(&s.user.username, &query.username) && prefix_match(&s.user.phone, &query.phone) && prefix_match(&s.user.email, &query.email))
+            .filter(|s| query.status.is_none_or(|status| s.user.status == status))
+            .filter(|s| in_range(Some(s.created_at), query.created_from, query.created_to))
+            .filter(|s| in_range(s.last_login, query.last_login_from, query.last_login_to))
+            .map(|s| ((sort_value(s), s.user.id), s))
+            .filter(|(key, _)| match cursor {
+                Some(c) if desc => *key < c,
+                Some(c) => *key > c,
+                None => true,
+            })
+            .collect();
+        matched.sort_unstable_by_key(|(key, _)| *key);
+        if desc {
+            matched.reverse();
+        }
+
+        let next_cursor = if matched.len() > limit { matched.get(limit - 1).map(|((v, id), _)| format!("{}_{}", v, id)) } else { None };
+        let records = matched.into_iter().take(limit).map(|(_, s)| s.summary()).collect();
+        Ok(CursorPage { records, next_cursor })
+    }
+
+    async fn get_user_summary(&self, id: u64) -> AuthixResult<Option<UserSummary>> {
+        Ok(self.lock().users.get(&id).map(StoredUser::summary))
+    }
+
+    async fn update_user(&self, id: u64, req: &UpdateUserRequest) -> AuthixResult<()> {
+        let mut inner = self.lock();
+        let tenant_id = inner.users.get(&id).map(|s| s.user.tenant_id).ok_or_else(|| AuthixError::UserNotFound("user.not_found".into()))?;
+        inner.check_unique(id, tenant_id, req.username.as_ref(), req.phone.as_ref(), req.email.as_ref())?;
+        // 修改用户名/手机号/邮箱时替换对应的登录标识
+        for (identity_type, new) in [(IdentityType::Username, &req.username), (IdentityType::Phone, &req.phone), (IdentityType::Email, &req.email)] {
+            let Some(new) = new else { continue };
+            let Some(s) = inner.users.get_mut(&id) else { return Ok(()) };
+            let Some(column) = column_mut(&mut s.user, identity_type) else { continue };
+            let old = column.replace(new.clone());
+            if old.as_ref() == Some(new) {
+                continue;
+            }
+            let user = s.user.clone();
+            if let Some(old) = old {
+                inner.identities.retain(|_, i| !(i.user_id == id && i.identity_type == identity_type && i.provider.is_empty() && i.identifier == old));
+            }
+            if inner.find_identity(tenant_id, identity_type, "", new).is_none() {
+                inner.insert_identity(UserIdentity::new(&user, identity_type, "", new))?;
+            }
+        }
+        let Some(s) = inner.users.get_mut(&id) else { return Ok(()) };
+        if req.nickname.is_some() {
+            s.nickname = req.nickname.clone();
+        }
+        if req.avatar.is_some() {
+            s.avatar = req.avatar.clone();
+        }
+        if req.gender.is_some() {
+            s.gender = req.gender;
+        }
+        if req.birthday.is_some() {
+            s.birthday = req.birthday;
+        }
+        Ok(())
+    }
+
+    async fn update_user_status(&self, id: u64, status: UserStatus) -> AuthixResult<()> {
+        if let Some(s) = self.lock().users.get_mut(&id) {
+            s.user.status = status;
+        }
+        Ok(())
+    }
+
+    async fn update_password(&self, id: u64, password: String) -> AuthixResult<()> {
+        let mut inner = self.lock();
+        let stored = inner
+            .users
+            .get_mut(&id)
+            .filter(|s| s.user.status != UserStatus::Deleted)
+            .ok_or_else(|| AuthixError::UserNotFound("user.not_found".into()))?;
+        stored.user.password = password;
+        Ok(())
+    }
+
+    async fn list_identities(&self, user_id: u64) -> AuthixResult<Vec<UserIdentity>> {
+        Ok(self.lock().identities.values().filter(|i| i.user_id == user_id).cloned().collect())
+    }
+
+    async fn link_identity(&self, identity: UserIdentity) -> AuthixResult<UserIdentity> {
+        let mut inner = self.lock();
+        let identity = inner.insert_identity(identity)?;
+        if let Some(column) = inner.users.get_mut(&identity.user_id).and_then(|s| column_mut(&mut s.user, identity.identity_type))
+            && column.is_none()
+        {
+            *column = Some(identity.identifier.clone());
+        }
+        Ok(identity)
+    }
+
+    async fn unlink_identity(&self, user_id: u64, identity_id: u64) -> AuthixResult<bool> {
+        let mut inner = self.lock();
+        let Some(identity) = inner.identities.remove(&identity_id) else { return Ok(false) };
+        if identity.user_id != user_id {
+            inner.identities.insert(identity.id, identity);
+            return Ok(false);
+        }
+        // 清空后以其余同类标识中最早关联的一个补上
+        let next = inner.identities.values()
+            .find(|i| i.user_id == user_id && i.identity_type == identity.identity_type && i.provider.is_empty())
+            .map(|i| i.identifier.clone());
+        if let Some(column) = inner.users.get_mut(&user_id).and_then(|s| column_mut(&mut s.user, identity.identity_type))
+            && column.as_ref() == Some(&identity.identifier)
+        {
+            *column = next;
+        }
+        Ok(true)
+    }
+
+    async fn verify_identity(&self, tenant_id: u64, identity_type: IdentityType, provider: &str, identifier: &str) -> AuthixResult<()> {
+        let mut inner = self.lock();
+        if let Some(i) = inner.identities.values_mut().find(|i| {
+            i.tenant_id == tenant_id && i.identity_type == identity_type && i.provider == provider && i.identifier == identifier
+        }) && i.verified_at.is_none()
+        {
+            i.verified_at = Some(Local::now());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,1073 @@
+use std::sync::Arc;
+
+use argon2::{password_hash::{rand_core::OsRng, PasswordHasher, SaltString}, Argon2, PasswordHash, PasswordVerifier};
+use axum::async_trait;
+use chrono::DateTime;
+use chrono::Local;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::audit::{AuditEvent, AuditEventType, AuditOutcome, AuditProvider};
+use crate::cache::SessionStore;
+use crate::event::{emit, DomainEvent, EventPublisher};
+use crate::common::PageQuery;
+use crate::config::ConfigHandle;
+use crate::common::PageResult;
+use crate::common::CursorPage;
+use crate::enums::{IdentityType, UserStatus};
+use crate::errors::{AuthixError, AuthixResult};
+use crate::i18n::Msg;
+use crate::login_history::LOGIN_HISTORY_TABLE_NAME;
+use crate::organization::ORG_MEMBER_TABLE_NAME;
+use crate::storage::{self, Db, FromRecord, Row, Tx, Value};
+use crate::tenant::CurrentTenant;
+use crate::webhook::{enqueue_event, EVENT_PASSWORD_CHANGED, EVENT_USER_DELETED, EVENT_USER_REGISTERED};
+use axum::http::StatusCode;
+use axum::Json;
+use axum_extra::extract::TypedHeader;
+use crate::common::{ClientInfo, UidHeader};
+use crate::common::R;
+use axum::extract::State;
+use axum::extract::Query;
+
+pub mod identity;
+mod memory;
+
+pub use identity::{UserIdentity, DELETED_USER_IDENTITY_TABLE_NAME, USER_IDENTITY_TABLE_NAME};
+pub use memory::InMemoryUserService;
+use identity::identity_exists;
+
+pub const USER_TABLE_NAME: &str = "i18n_users";
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct User {
+    pub id: u64,
+    pub tenant_id: u64,
+    pub username: Option<String>,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    pub password: String,
+    pub status: UserStatus,
+    #[serde(skip)]
+    pub crt_by: Option<String>,
+}
+
+impl FromRecord for User {
+    fn from_record(row: &Row) -> Result<Self, sqlx::Error> {
+        Ok(User {
+            id: row.get("id")?,
+            tenant_id: row.get("tenant_id")?,
+            username: row.get("username")?,
+            phone: row.get("phone")?,
+            email: row.get("email")?,
+            password: row.get("password")?,
+            status: row.get("status")?,
+            crt_by: None,
+        })
+    }
+}
+
+impl User {
+    /// 登录前校验账号状态，禁用/锁定/待验证返回对应错误，已注销视为账号不存在
+    pub fn ensure_can_login(&self) -> AuthixResult<()> {
+        match self.status {
+            UserStatus::Active => Ok(()),
+            UserStatus::Disabled => Err(AuthixError::AccountDisabled("account.disabled".into())),
+            UserStatus::Locked => Err(AuthixError::AccountLocked("account.locked".into())),
+            UserStatus::Pending => Err(AuthixError::AccountPending("account.pending".into())),
+            UserStatus::Deleted => Err(AuthixError::InvalidCredentials("auth.account_not_found".into())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ProfileInfo {
+    pub username: Option<String>,
+    pub nickname: Option<String>,
+    pub avatar: Option<String>,
+    pub gender: Option<u8>,
+    pub birthday: Option<NaiveDate>,
+    pub last_login: Option<DateTime<Local>>,
+}
+
+impl FromRecord for ProfileInfo {
+    fn from_record(row: &Row) -> Result<Self, sqlx::Error> {
+        Ok(ProfileInfo {
+            username: row.get("username")?,
+            nickname: row.get("nickname")?,
+            avatar: row.get("avatar")?,
+            gender: row.get("gender")?,
+            birthday: row.get("birthday")?,
+            last_login: row.get("last_login")?,
+        })
+    }
+}
+
+/// 管理端用户视图
+#[derive(Debug, Clone, Serialize)]
+pub struct UserSummary {
+    pub id: u64,
+    pub tenant_id: u64,
+    pub username: Option<String>,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    pub nickname: Option<String>,
+    pub status: UserStatus,
+    pub last_login: Option<DateTime<Local>>,
+    pub created_at: Option<DateTime<Local>>,
+}
+
+impl FromRecord for UserSummary {
+    fn from_record(row: &Row) -> Result<Self, sqlx::Error> {
+        Ok(UserSummary {
+            id: row.get("id")?,
+            tenant_id: row.get("tenant_id")?,
+            username: row.get("username")?,
+            phone: row.get("phone")?,
+            email: row.get("email")?,
+            nickname: row.get("nickname")?,
+            status: row.get("status")?,
+            last_login: row.get("last_login")?,
+            created_at: row.get("created_at")?,
+        })
+    }
+}
+
+/// 管理端用户查询条件
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UserSearchQuery {
+    pub tenant_id: Option<u64>,
+    pub username: Option<String>,           // 用户名前缀
+    pub phone: Option<String>,              // 手机号前缀
+    pub email: Option<String>,              // 邮箱前缀
+    pub status: Option<UserStatus>,
+    pub created_from: Option<DateTime<Local>>,
+    pub created_to: Option<DateTime<Local>>,
+    pub last_login_from: Option<DateTime<Local>>,
+    pub last_login_to: Option<DateTime<Local>>,
+    pub sort: Option<String>,               // "id" | "created_at" | "last_login"
+    pub order: Option<String>,              // "asc" | "desc"
+    pub cursor: Option<String>,
+    pub limit: Option<u32>,
+}
+
+/// 管理端修改用户，字段为空表示不修改
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UpdateUserRequest {
+    pub username: Option<String>,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    pub nickname: Option<String>,
+    pub avatar: Option<String>,
+    pub gender: Option<u8>,
+    pub birthday: Option<NaiveDate>,
+}
+
+/// LIKE 前缀匹配，以 `!` 转义通配符，配合 `ESCAPE '!'` 在各后端行为一致
+fn like_prefix(prefix: &str) -> String {
+    let escaped = prefix.replace('!', "!!").replace('%', "!%").replace('_', "!_");
+    format!("{}%", escaped)
+}
+
+#[async_trait]
+pub trait UserProvider: Send + Sync {
+    async fn get_user_profile(&self, id: u64) -> AuthixResult<ProfileInfo>;
+    async fn get_user_profiles(&self, ids: Vec<u64>) -> AuthixResult<Vec<ProfileInfo>>;
+    async fn create_user(&self, user: User) -> AuthixResult<User>;
+    /// 注销用户（软删除），保留期后由后台任务清理
+    async fn delete_user(&self, id: u64) -> AuthixResult<()>;
+    /// 恢复注销的用户，状态还原为注销前的状态
+    async fn restore_user(&self, id: u64) -> AuthixResult<bool>;
+    /// 清理注销超过 `retention_days` 天的用户，返回清理数量
+    async fn purge_deleted_users(&self, retention_days: u32) -> AuthixResult<u64>;
+    async fn get_user_by_id(&self, id: u64) -> AuthixResult<Option<User>>;
+    /// 按登录标识查找用户，用户名/手机号/邮箱的 `provider` 为空
+    async fn get_user_by_identity(&self, tenant_id: u64, identity_type: IdentityType, provider: &str, identifier: &str) -> AuthixResult<Option<User>>;
+    async fn get_user_by_username(&self, tenant_id: u64, username: String) -> AuthixResult<Option<User>>;
+    async fn get_user_by_phone(&self, tenant_id: u64, phone: String) -> AuthixResult<Option<User>>;
+    async fn get_user_by_email(&self, tenant_id: u64, email: String) -> AuthixResult<Option<User>>;
+    async fn update_last_login_time(&self, id: u64) -> AuthixResult<User>;
+    async fn search_users(&self, query: &UserSearchQuery) -> AuthixResult<CursorPage<UserSummary>>;
+    async fn get_user_summary(&self, id: u64) -> AuthixResult<Option<UserSummary>>;
+    async fn update_user(&self, id: u64, req: &UpdateUserRequest) -> AuthixResult<()>;
+    async fn update_user_status(&self, id: u64, status: UserStatus) -> AuthixResult<()>;
+    /// 修改密码（已 hash），同一事务写入 password.changed 事件
+    async fn update_password(&self, id: u64, password: String) -> AuthixResult<()>;
+    async fn list_identities(&self, user_id: u64) -> AuthixResult<Vec<UserIdentity>>;
+    /// 关联登录标识，已被使用时返回 `UserAlreadyExists`；用户的同类列为空时一并写入
+    async fn link_identity(&self, identity: UserIdentity) -> AuthixResult<UserIdentity>;
+    /// 解除关联，用户的同类列为该标识时改用其余同类标识，没有时清空
+    async fn unlink_identity(&self, user_id: u64, identity_id: u64) -> AuthixResult<bool>;
+    /// 标记登录标识已验证（验证码登录、第三方授权）
+    async fn verify_identity(&self, tenant_id: u64, identity_type: IdentityType, provider: &str, identifier: &str) -> AuthixResult<()>;
+}
+
+/// 登录标识对应的用户表列，第三方身份没有对应列
+fn identity_column(identity_type: IdentityType) -> Option<&'static str> {
+    match identity_type {
+        IdentityType::Username => Some("username"),
+        IdentityType::Phone => Some("phone"),
+        IdentityType::Email => Some("email"),
+        IdentityType::External => None,
+    }
+}
+
+/// 用户表中对应类型的用户名/手机号/邮箱
+fn identity_value(user: &User, identity_type: IdentityType) -> Option<&String> {
+    match identity_type {
+        IdentityType::Username => user.username.as_ref(),
+        IdentityType::Phone => user.phone.as_ref(),
+        IdentityType::Email => user.email.as_ref(),
+        IdentityType::External => None,
+    }
+}
+
+/// 解析用户查询游标，格式：{排序值}_{id}
+fn parse_cursor(cursor: Option<&str>) -> AuthixResult<Option<(i64, u64)>> {
+    let Some(cursor) = cursor.filter(|c| !c.is_empty()) else { return Ok(None) };
+    cursor
+        .split_once('_')
+        .and_then(|(v, id)| Some((v.parse::<i64>().ok()?, id.parse::<u64>().ok()?)))
+        .map(Some)
+        .ok_or_else(|| AuthixError::InvalidParameter("param.invalid_cursor".into()))
+}
+
+pub struct UserService {
+    db: Db,
+}
+
+impl UserService {
+    pub fn new(db: Db) -> Self {
+        Self { db }
+    }
+}
+
+const USER_COLUMNS: &str = "id, tenant_id, username, phone, email, password, status";
+const IDENTITY_COLUMNS: &str = "id, tenant_id, user_id, identity_type, provider, identifier, verified_at, created_at";
+
+/// 写入登录标识，标识已被使用时返回 `UserAlreadyExists`
+async fn insert_identity(tx: &mut Tx, identity: &UserIdentity) -> AuthixResult<u64> {
+    storage::query(format!("INSERT INTO {} (tenant_id, user_id, identity_type, provider, identifier, verified_at) VALUES (?, ?, ?, ?, ?, ?)", USER_IDENTITY_TABLE_NAME))
+        .bind(identity.tenant_id)
+        .bind(identity.user_id)
+        .bind(identity.identity_type)
+        .bind(&identity.provider)
+        .bind(&identity.identifier)
+        .bind(identity.verified_at)
+        .insert(tx)
+        .await
+        .map_err(|e| match &e {
+            sqlx::Error::Database(db) if db.is_unique_violation() => identity_exists(identity.identity_type),
+            _ => e.into(),
+        })
+}
+
+#[async_trait]
+impl UserProvider for UserService {
+    async fn get_user_profile(&self, id: u64) -> AuthixResult<ProfileInfo> {
+        let pool = &self.db;
+        let user = storage::query(format!("SELECT username, nickname, avatar, gender, birthday, last_login FROM {} WHERE id = ? AND status <> ?", USER_TABLE_NAME))
+            .bind(id)
+            .bind(UserStatus::Deleted)
+            .fetch_optional(pool)
+            .await?;
+        user.ok_or_else(|| AuthixError::UserNotFound("user.not_found".into()))
+    }
+
+    async fn get_user_profiles(&self, ids: Vec<u64>) -> AuthixResult<Vec<ProfileInfo>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let pool = &self.db;
+        // 动态占位符
+        let placeholders = std::iter::repeat_n("?", ids.len()).collect::<Vec<_>>().join(", ");
+        let sql = format!(
+            "SELECT username, nickname, avatar, gender, birthday, last_login FROM {} WHERE id IN ({})",
+            USER_TABLE_NAME, placeholders
+        );
+        let q = ids.into_iter().fold(storage::query(sql), |q, id| q.bind(id));
+        let list = q.fetch_all(pool).await?;
+        Ok(list)
+    }
+
+    async fn create_user(&self, user: User) -> AuthixResult<User> {
+        // 用户与 user.registered 事件同一事务写入，保证事件不丢失
+        let mut tx = self.db.begin().await?;
+        let id = storage::query(format!("INSERT INTO {} (tenant_id, username, phone, email, password, status, crt_by) VALUES (?, ?, ?, ?, ?, ?, ?)", USER_TABLE_NAME))
+            .bind(user.tenant_id)
+            .bind(&user.username)
+            .bind(&user.phone)
+            .bind(&user.email)
+            .bind(&user.password)
+            .bind(user.status)
+            .bind(&user.crt_by)
+            .insert(&mut tx)
+            .await?;
+        let created = User { id, ..user.clone() };
+        for identity in UserIdentity::local_identities(&created) {
+            insert_identity(&mut tx, &identity).await?;
+        }
+        let data = json!({
+            "user_id": id,
+            "username": user.username,
+            "phone": user.phone,
+            "email": user.email,
+            "status": user.status,
+        });
+        enqueue_event(&mut tx, user.tenant_id, EVENT_USER_REGISTERED, data).await?;
+        tx.commit().await?;
+
+        let new_user = User {
+            id,
+            tenant_id: user.tenant_id,
+            username: user.username,
+            phone: user.phone,
+            email: user.email,
+            password: user.password,
+            status: user.status,
+            crt_by: None,
+        };
+        Ok(new_user)
+    }
+
+    async fn delete_user(&self, id: u64) -> AuthixResult<()> {
+        let mut tx = self.db.begin().await?;
+        let user: Option<User> = storage::query(format!("SELECT {} FROM {} WHERE id = ? AND status <> ?{}", USER_COLUMNS, USER_TABLE_NAME, tx.backend().for_update()))
+            .bind(id)
+            .bind(UserStatus::Deleted)
+            .fetch_optional(&mut tx)
+            .await?;
+        let Some(user) = user else { return Ok(()) };
+        // 登录标识移入注销标识表，释放用户名/手机号/邮箱供重新注册
+        let identities: Vec<UserIdentity> = storage::query(format!("SELECT {} FROM {} WHERE user_id = ?", IDENTITY_COLUMNS, USER_IDENTITY_TABLE_NAME))
+            .bind(id)
+            .fetch_all(&mut tx)
+            .await?;
+        for identity in &identities {
+            let is_primary = identity.provider.is_empty() && identity_value(&user, identity.identity_type) == Some(&identity.identifier);
+            storage::query(format!(
+                "INSERT INTO {} (id, tenant_id, user_id, identity_type, provider, identifier, verified_at, created_at, is_primary) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                DELETED_USER_IDENTITY_TABLE_NAME
+            ))
+                .bind(identity.id)
+                .bind(identity.tenant_id)
+                .bind(identity.user_id)
+                .bind(identity.identity_type)
+                .bind(&identity.provider)
+                .bind(&identity.identifier)
+                .bind(identity.verified_at)
+                .bind(identity.created_at)
+                .bind(is_primary)
+                .execute(&mut tx)
+                .await?;
+        }
+        storage::query(format!("DELETE FROM {} WHERE user_id = ?", USER_IDENTITY_TABLE_NAME))
+            .bind(id)
+            .execute(&mut tx)
+            .await?;
+        // 先记录注销前的状态（MySQL 按顺序赋值）
+        storage::query(format!(
+            "UPDATE {} SET prev_status = status, status = ?, username = NULL, phone = NULL, email = NULL, deleted_at = CURRENT_TIMESTAMP WHERE id = ?",
+            USER_TABLE_NAME
+        ))
+            .bind(UserStatus::Deleted)
+            .bind(id)
+            .execute(&mut tx)
+            .await?;
+        enqueue_event(&mut tx, user.tenant_id, EVENT_USER_DELETED, json!({ "user_id": id })).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn restore_user(&self, id: u64) -> AuthixResult<bool> {
+        let mut tx = self.db.begin().await?;
+        let deleted: Option<u64> = storage::query(format!("SELECT id FROM {} WHERE id = ? AND status = ?{}", USER_TABLE_NAME, tx.backend().for_update()))
+            .bind(id)
+            .bind(UserStatus::Deleted)
+            .fetch_optional_scalar(&mut tx)
+            .await?;
+        if deleted.is_none() {
+            return Ok(false);
+        }
+        // 移回登录标识，注销期间已被其他用户使用时恢复失败
+        let rows = storage::query(format!("SELECT {}, is_primary FROM {} WHERE user_id = ? ORDER BY id", IDENTITY_COLUMNS, DELETED_USER_IDENTITY_TABLE_NAME))
+            .bind(id)
+            .fetch_rows(&mut tx)
+            .await?;
+        for row in &rows {
+            let identity = UserIdentity::from_record(row)?;
+            storage::query(format!(
+                "INSERT INTO {} (id, tenant_id, user_id, identity_type, provider, identifier, verified_at, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+                USER_IDENTITY_TABLE_NAME
+            ))
+                .bind(identity.id)
+                .bind(identity.tenant_id)
+                .bind(identity.user_id)
+                .bind(identity.identity_type)
+                .bind(&identity.provider)
+                .bind(&identity.identifier)
+                .bind(identity.verified_at)
+                .bind(identity.created_at)
+                .execute(&mut tx)
+                .await
+                .map_err(|e| match &e {
+                    sqlx::Error::Database(db) if db.is_unique_violation() => identity_exists(identity.identity_type),
+                    _ => e.into(),
+                })?;
+            if let Some(column) = identity_column(identity.identity_type)
+                && row.get::<bool, _>("is_primary")?
+            {
+                storage::query(format!("UPDATE {} SET {} = ? WHERE id = ?", USER_TABLE_NAME, column))
+                    .bind(&identity.identifier)
+                    .bind(id)
+                    .execute(&mut tx)
+                    .await?;
+            }
+        }
+        storage::query(format!("DELETE FROM {} WHERE user_id = ?", DELETED_USER_IDENTITY_TABLE_NAME))
+            .bind(id)
+            .execute(&mut tx)
+            .await?;
+        // 未记录注销前状态的用户恢复为正常
+        storage::query(format!("UPDATE {} SET status = COALESCE(prev_status, ?), prev_status = NULL, deleted_at = NULL WHERE id = ?", USER_TABLE_NAME))
+            .bind(UserStatus::Active)
+            .bind(id)
+            .execute(&mut tx)
+            .await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn purge_deleted_users(&self, retention_days: u32) -> AuthixResult<u64> {
+        let pool = &self.db;
+        let cutoff = Local::now() - chrono::Duration::days(retention_days as i64);
+        let mut tx = pool.begin().await?;
+        // 先清理组织成员关系、登录标识与登录历史，再删除用户
+        for table in [ORG_MEMBER_TABLE_NAME, USER_IDENTITY_TABLE_NAME, DELETED_USER_IDENTITY_TABLE_NAME, LOGIN_HISTORY_TABLE_NAME] {
+            storage::query(format!(
+                "DELETE FROM {} WHERE user_id IN (SELECT id FROM {} WHERE status = ? AND deleted_at < ?)",
+                table, USER_TABLE_NAME
+            ))
+                .bind(UserStatus::Deleted)
+                .bind(cutoff)
+                .execute(&mut tx)
+                .await?;
+        }
+        let rows = storage::query(format!("DELETE FROM {} WHERE status = ? AND deleted_at < ?", USER_TABLE_NAME))
+            .bind(UserStatus::Deleted)
+            .bind(cutoff)
+            .execute(&mut tx)
+            .await?;
+        tx.commit().await?;
+        Ok(rows)
+    }
+
+    async fn get_user_by_id(&self, id: u64) -> AuthixResult<Option<User>> {
+        let pool = &self.db;
+        let user = storage::query(format!("SELECT {} FROM {} WHERE id = ?", USER_COLUMNS, USER_TABLE_NAME))
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(user)
+    }
+
+    async fn get_user_by_identity(&self, tenant_id: u64, identity_type: IdentityType, provider: &str, identifier: &str) -> AuthixResult<Option<User>> {
+        let pool = &self.db;
+        let columns = USER_COLUMNS.split(", ").map(|c| format!("u.{}", c)).collect::<Vec<_>>().join(", ");
+        let user = storage::query(format!(
+            "SELECT {} FROM {} u JOIN {} i ON i.user_id = u.id WHERE i.tenant_id = ? AND i.identity_type = ? AND i.provider = ? AND i.identifier = ?",
+            columns, USER_TABLE_NAME, USER_IDENTITY_TABLE_NAME
+        ))
+            .bind(tenant_id)
+            .bind(identity_type)
+            .bind(provider)
+            .bind(identifier)
+            .fetch_optional(pool)
+            .await?;
+        Ok(user)
+    }
+
+    async fn get_user_by_username(&self, tenant_id: u64, username: String) -> AuthixResult<Option<User>> {
+        self.get_user_by_identity(tenant_id, IdentityType::Username, "", &username).await
+    }
+
+    async fn get_user_by_phone(&self, tenant_id: u64, phone: String) -> AuthixResult<Option<User>> {
+        self.get_user_by_identity(tenant_id, IdentityType::Phone, "", &phone).await
+    }
+
+    async fn get_user_by_email(&self, tenant_id: u64, email: String) -> AuthixResult<Option<User>> {
+        self.get_user_by_identity(tenant_id, IdentityType::Email, "", &email).await
+    }
+
+    async fn update_last_login_time(&self, id: u64) -> AuthixResult<User> {
+        let pool = &self.db;
+        
+        // 使用数据库当前时间
+        let rows = storage::query(format!("UPDATE {} SET last_login = CURRENT_TIMESTAMP WHERE id = ?", USER_TABLE_NAME))
+            .bind(id)
+            .execute(pool)
+            .await
+            .map_err(|e| crate::errors::AuthixError::DatabaseError(format!("Database error: {}", e)))?;
+        
+        if rows == 0 {
+            return Err(crate::errors::AuthixError::UserNotFound("user.not_found".into()));
+        }
+        
+        // 查询更新后的用户信息
+        let user = storage::query(format!("SELECT {} FROM {} WHERE id = ?", USER_COLUMNS, USER_TABLE_NAME))
+            .bind(id)
+            .fetch_one(pool)
+            .await
+            .map_err(|e| crate::errors::AuthixError::DatabaseError(format!("Database error: {}", e)))?;
+        
+        Ok(user)
+    }
+
+    async fn search_users(&self, query: &UserSearchQuery) -> AuthixResult<CursorPage<UserSummary>> {
+        let pool = &self.db;
+        let backend = pool.backend();
+        let limit = query.limit.unwrap_or(20).clamp(1, 200);
+        let desc = query.order.as_deref() != Some("asc");
+        // 排序字段统一转换为整数，便于游标比较；last_login 为空时按 0 处理
+        let sort_expr = match query.sort.as_deref().unwrap_or("id") {
+            "id" => "id".to_owned(),
+            "created_at" => format!("COALESCE({}, 0)", backend.epoch_seconds("created_at")),
+            "last_login" => format!("COALESCE({}, 0)", backend.epoch_seconds("last_login")),
+            other => return Err(AuthixError::InvalidParameter(Msg::new("param.unsupported_sort_field").arg("field", other))),
+        };
+
+        let mut conditions: Vec<String> = Vec::new();
+        let mut args: Vec<Value> = Vec::new();
+        if let Some(tenant_id) = query.tenant_id {
+            conditions.push("tenant_id = ?".into());
+            args.push(tenant_id.into());
+        }
+        for (column, prefix) in [("username", &query.username), ("phone", &query.phone), ("email", &query.email)] {
+            if let Some(prefix) = prefix.as_deref().filter(|p| !p.is_empty()) {
+                conditions.push(format!("{} LIKE ? ESCAPE '!'", column));
+                args.push(like_prefix(prefix).into());
+            }
+        }
+        if let Some(status) = query.status {
+            conditions.push("status = ?".into());
+            args.push(status.into());
+        }
+        for (cond, value) in [
+            ("created_at >= ?", query.created_from),
+            ("created_at < ?", query.created_to),
+            ("last_login >= ?", query.last_login_from),
+            ("last_login < ?", query.last_login_to),
+        ] {
+            if let Some(v) = value {
+                conditions.push(cond.into());
+                args.push(v.into());
+            }
+        }
+        if let Some((value, id)) = parse_cursor(query.cursor.as_deref())? {
+            let op = if desc { "<" } else { ">" };
+            conditions.push(format!("({expr} {op} ? OR ({expr} = ? AND id {op} ?))", expr = sort_expr, op = op));
+            args.push(value.into());
+            args.push(value.into());
+            args.push(id.into());
+        }
+
+        let where_clause = if conditions.is_empty() { String::new() } else { format!("WHERE {}", conditions.join(" AND ")) };
+        let direction = if desc { "DESC" } else { "ASC" };
+        let sql = format!(
+            "SELECT id, tenant_id, username, phone, email, nickname, status, last_login, created_at, {sort_value} AS sort_value FROM {table} {where_clause} ORDER BY {expr} {dir}, id {dir} LIMIT ?",
+            sort_value = backend.cast_int(&sort_expr), expr = sort_expr, table = USER_TABLE_NAME, where_clause = where_clause, dir = direction
+        );
+        let q = args.into_iter().fold(storage::query(sql), |q, arg| q.bind(arg));
+        let rows = q.bind(limit + 1).fetch_rows(pool).await?;
+
+        let mut records = Vec::with_capacity(rows.len());
+        let mut next_cursor = None;
+        for (i, row) in rows.iter().enumerate() {
+            if i as u32 == limit {
+                break;
+            }
+            let user = UserSummary::from_record(row)?;
+            let sort_value: i64 = row.get("sort_value")?;
+            next_cursor = Some(format!("{}_{}", sort_value, user.id));
+            records.push(user);
+        }
+        if rows.len() as u32 <= limit {
+            next_cursor = None;
+        }
+        Ok(CursorPage { records, next_cursor })
+    }
+
+    async fn get_user_summary(&self, id: u64) -> AuthixResult<Option<UserSummary>> {
+        let pool = &self.db;
+        let user = storage::query(format!("SELECT id, tenant_id, username, phone, email, nickname, status, last_login, created_at FROM {} WHERE id = ?", USER_TABLE_NAME))
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(user)
+    }
+
+    async fn update_user(&self, id: u64, req: &UpdateUserRequest) -> AuthixResult<()> {
+        let mut sets: Vec<&str> = Vec::new();
+        let mut q_args: Vec<Value> = Vec::new();
+        for (set, value) in [
+            ("username = ?", req.username.as_ref().map(Value::from)),
+            ("phone = ?", req.phone.as_ref().map(Value::from)),
+            ("email = ?", req.email.as_ref().map(Value::from)),
+            ("nickname = ?", req.nickname.as_ref().map(Value::from)),
+            ("avatar = ?", req.avatar.as_ref().map(Value::from)),
+            ("gender = ?", req.gender.map(Value::from)),
+            ("birthday = ?", req.birthday.map(Value::from)),
+        ] {
+            if let Some(value) = value {
+                sets.push(set);
+                q_args.push(value);
+            }
+        }
+        if sets.is_empty() {
+            return Ok(());
+        }
+        let mut tx = self.db.begin().await?;
+        let user: Option<User> = storage::query(format!("SELECT {} FROM {} WHERE id = ?{}", USER_COLUMNS, USER_TABLE_NAME, tx.backend().for_update()))
+            .bind(id)
+            .fetch_optional(&mut tx)
+            .await?;
+        let Some(user) = user else { return Err(AuthixError::UserNotFound("user.not_found".into())) };
+        let sql = format!("UPDATE {} SET {} WHERE id = ?", USER_TABLE_NAME, sets.join(", "));
+        let q = q_args.into_iter().fold(storage::query(sql), |q, arg| q.bind(arg));
+        q.bind(id).execute(&mut tx).await?;
+
+        // 修改用户名/手机号/邮箱时替换对应的登录标识
+        for (identity_type, old, new) in [
+            (IdentityType::Username, &user.username, &req.username),
+            (IdentityType::Phone, &user.phone, &req.phone),
+            (IdentityType::Email, &user.email, &req.email),
+        ] {
+            let Some(new) = new.as_ref().filter(|n| Some(*n) != old.as_ref()) else { continue };
+            if let Some(old) = old {
+                storage::query(format!("DELETE FROM {} WHERE user_id = ? AND identity_type = ? AND provider = '' AND identifier = ?", USER_IDENTITY_TABLE_NAME))
+                    .bind(id)
+                    .bind(identity_type)
+                    .bind(old)
+                    .execute(&mut tx)
+                    .await?;
+            }
+            // 新标识可能已作为该用户的其他标识关联
+            let owner: Option<u64> = storage::query(format!("SELECT user_id FROM {} WHERE tenant_id = ? AND identity_type = ? AND provider = '' AND identifier = ?", USER_IDENTITY_TABLE_NAME))
+                .bind(user.tenant_id)
+                .bind(identity_type)
+                .bind(new)
+                .fetch_optional_scalar(&mut tx)
+                .await?;
+            match owner {
+                Some(owner) if owner == id => {}
+                Some(_) => return Err(identity_exists(identity_type)),
+                None => {
+                    insert_identity(&mut tx, &UserIdentity::new(&user, identity_type, "", new)).await?;
+                }
+            }
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn update_user_status(&self, id: u64, status: UserStatus) -> AuthixResult<()> {
+        let pool = &self.db;
+        storage::query(format!("UPDATE {} SET status = ? WHERE id = ?", USER_TABLE_NAME))
+            .bind(status)
+            .bind(id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn update_password(&self, id: u64, password: String) -> AuthixResult<()> {
+        let mut tx = self.db.begin().await?;
+        let tenant_id: Option<u64> = storage::query(format!("SELECT tenant_id FROM {} WHERE id = ? AND status <> ?{}", USER_TABLE_NAME, tx.backend().for_update()))
+            .bind(id)
+            .bind(UserStatus::Deleted)
+            .fetch_optional_scalar(&mut tx)
+            .await?;
+        let Some(tenant_id) = tenant_id else { return Err(AuthixError::UserNotFound("user.not_found".into())) };
+        storage::query(format!("UPDATE {} SET password = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?", USER_TABLE_NAME))
+            .bind(&password)
+            .bind(id)
+            .execute(&mut tx)
+            .await?;
+        enqueue_event(&mut tx, tenant_id, EVENT_PASSWORD_CHANGED, json!({ "user_id": id })).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn list_identities(&self, user_id: u64) -> AuthixResult<Vec<UserIdentity>> {
+        let pool = &self.db;
+        let list = storage::query(format!("SELECT {} FROM {} WHERE user_id = ? ORDER BY id", IDENTITY_COLUMNS, USER_IDENTITY_TABLE_NAME))
+            .bind(user_id)
+            .fetch_all(pool)
+            .await?;
+        Ok(list)
+    }
+
+    async fn link_identity(&self, identity: UserIdentity) -> AuthixResult<UserIdentity> {
+        let mut tx = self.db.begin().await?;
+        let id = insert_identity(&mut tx, &identity).await?;
+        if let Some(column) = identity_column(identity.identity_type) {
+            storage::query(format!("UPDATE {table} SET {col} = ? WHERE id = ? AND {col} IS NULL", table = USER_TABLE_NAME, col = column))
+                .bind(&identity.identifier)
+                .bind(identity.user_id)
+                .execute(&mut tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(UserIdentity { id, created_at: Some(Local::now()), ..identity })
+    }
+
+    async fn unlink_identity(&self, user_id: u64, identity_id: u64) -> AuthixResult<bool> {
+        let mut tx = self.db.begin().await?;
+        let identity: Option<UserIdentity> = storage::query(format!("SELECT {} FROM {} WHERE id = ? AND user_id = ?", IDENTITY_COLUMNS, USER_IDENTITY_TABLE_NAME))
+            .bind(identity_id)
+            .bind(user_id)
+            .fetch_optional(&mut tx)
+            .await?;
+        let Some(identity) = identity else { return Ok(false) };
+        storage::query(format!("DELETE FROM {} WHERE id = ?", USER_IDENTITY_TABLE_NAME))
+            .bind(identity_id)
+            .execute(&mut tx)
+            .await?;
+        if let Some(column) = identity_column(identity.identity_type) {
+            // 清空后以其余同类标识中最早关联的一个补上
+            storage::query(format!(
+                "UPDATE {table} SET {col} = (SELECT identifier FROM {identities} WHERE user_id = ? AND identity_type = ? AND provider = '' ORDER BY id LIMIT 1) WHERE id = ? AND {col} = ?",
+                table = USER_TABLE_NAME, identities = USER_IDENTITY_TABLE_NAME, col = column
+            ))
+                .bind(user_id)
+                .bind(identity.identity_type)
+                .bind(user_id)
+                .bind(&identity.identifier)
+                .execute(&mut tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn verify_identity(&self, tenant_id: u64, identity_type: IdentityType, provider: &str, identifier: &str) -> AuthixResult<()> {
+        let pool = &self.db;
+        storage::query(format!(
+            "UPDATE {} SET verified_at = CURRENT_TIMESTAMP WHERE tenant_id = ? AND identity_type = ? AND provider = ? AND identifier = ? AND verified_at IS NULL",
+            USER_IDENTITY_TABLE_NAME
+        ))
+            .bind(tenant_id)
+            .bind(identity_type)
+            .bind(provider)
+            .bind(identifier)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+}
+
+pub async fn user_profile(
+    State(user_provider): State<Arc<dyn UserProvider>>,
+    TypedHeader(uid): TypedHeader<UidHeader>,
+) -> AuthixResult<(StatusCode, Json<R<ProfileInfo>>)> {
+    let id: u64 = uid.0.parse().map_err(|_| AuthixError::InvalidParameter("param.invalid_uid".into()))?;
+    let user = user_provider.get_user_profile(id).await?;
+    Ok((StatusCode::OK, Json(R::ok_data(user))))
+}
+
+/// 当前租户的在线用户数
+pub async fn online_count(
+    CurrentTenant(ctx): CurrentTenant,
+    State(sessions): State<Arc<dyn SessionStore>>,
+) -> AuthixResult<(StatusCode, Json<R<u64>>)> {
+    let count = sessions.online_count(Some(ctx.tenant.id)).await?;
+    Ok((StatusCode::OK, Json(R::ok_data(count))))
+}
+
+/// 当前租户的在线用户
+pub async fn online_users(
+    CurrentTenant(ctx): CurrentTenant,
+    State(sessions): State<Arc<dyn SessionStore>>,
+    State(user_provider): State<Arc<dyn UserProvider>>,
+    Query(q): Query<PageQuery>,
+) -> AuthixResult<(StatusCode, Json<R<PageResult<ProfileInfo>>>)> {
+    let page = q.page.unwrap_or(1).max(1);
+    let page_size = q.page_size.unwrap_or(20).clamp(1, 200);
+    let page_result = crate::cache::get_online_user_ids_paginated(&*sessions, ctx.tenant.id, page, page_size).await?;
+    let profiles = user_provider.get_user_profiles(page_result.records).await?;
+    let data = PageResult { total: page_result.total, records: profiles };
+    Ok((StatusCode::OK, Json(R::ok_data(data))))
+}
+
+pub async fn delete_user(
+    State(sessions): State<Arc<dyn SessionStore>>,
+    State(user_provider): State<Arc<dyn UserProvider>>,
+    State(audit): State<Arc<dyn AuditProvider>>,
+    State(publisher): State<Arc<dyn EventPublisher>>,
+    client: ClientInfo,
+    TypedHeader(uid): TypedHeader<UidHeader>
+) -> AuthixResult<(StatusCode, Json<R<String>>)> {
+    let id: u64 = uid.0.parse().map_err(|_| AuthixError::InvalidParameter("param.invalid_uid".into()))?;
+    let mut event = AuditEvent::new(AuditEventType::UserDelete, AuditOutcome::Success, &client).actor(id).subject_id(Some(id));
+    let tenant_id = user_provider.get_user_by_id(id).await?.map(|u| u.tenant_id);
+    if let Some(tenant_id) = tenant_id {
+        event = event.tenant(tenant_id);
+    }
+    if let Err(e) = user_provider.delete_user(id).await {
+        audit.record(AuditEvent { outcome: AuditOutcome::Failure, ..event }.reason(e.to_string())).await;
+        return Err(e);
+    }
+    audit.record(event).await;
+    if let Some(tenant_id) = tenant_id {
+        emit(&publisher, tenant_id, DomainEvent::UserDeleted { user_id: id }).await;
+        crate::cache::revoke_user_tokens(&*sessions, tenant_id, id).await?;
+    }
+    Ok((StatusCode::OK, Json(R::<String>::ok())))
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChangePasswordRequest {
+    pub old_password: String,
+    pub new_password: String,
+}
+
+/// 修改密码，需校验原密码；成功后吊销该用户的全部令牌
+pub async fn change_password(
+    CurrentTenant(ctx): CurrentTenant,
+    client: ClientInfo,
+    State(sessions): State<Arc<dyn SessionStore>>,
+    State(users): State<Arc<dyn UserProvider>>,
+    State(audit): State<Arc<dyn AuditProvider>>,
+    TypedHeader(uid): TypedHeader<UidHeader>,
+    Json(payload): Json<ChangePasswordRequest>,
+) -> AuthixResult<(StatusCode, Json<R<String>>)> {
+    let user = identity::current_user(&*users, &ctx, &uid).await?;
+    let event = AuditEvent::new(AuditEventType::PasswordChange, AuditOutcome::Success, &client)
+        .tenant(ctx.tenant.id)
+        .actor(user.id)
+        .subject_id(Some(user.id));
+    let parsed_hash = PasswordHash::new(&user.password)
+        .map_err(|e| AuthixError::Internal(format!("invalid password hash for user {}: {}", user.id, e)))?;
+    let argon2 = Argon2::default();
+    if argon2.verify_password(payload.old_password.as_bytes(), &parsed_hash).is_err() {
+        let e = AuthixError::InvalidCredentials("auth.invalid_credentials".into());
+        audit.record(AuditEvent { outcome: AuditOutcome::Failure, ..event }.reason(e.to_string())).await;
+        return Err(e);
+    }
+    ctx.settings.password_policy.check(&payload.new_password).map_err(AuthixError::InvalidParameter)?;
+    let password_hash = argon2
+        .hash_password(payload.new_password.as_bytes(), &SaltString::generate(&mut OsRng))
+        .map(|h| h.to_string())
+        .map_err(|e| AuthixError::Internal(format!("hash password error: {}", e)))?;
+    if let Err(e) = users.update_password(user.id, password_hash).await {
+        audit.record(AuditEvent { outcome: AuditOutcome::Failure, ..event }.reason(e.to_string())).await;
+        return Err(e);
+    }
+    audit.record(event).await;
+    crate::cache::revoke_user_tokens(&*sessions, user.tenant_id, user.id).await?;
+    Ok((StatusCode::OK, Json(R::<String>::ok())))
+}
+
+/// 启动注销用户清理任务，每小时执行一次，收到停机信号后退出
+/// 保留天数由配置 `user.delete_retention_days` 指定，默认 30 天，每次执行时读取以支持热更新
+pub fn spawn_purge_task(user_provider: Arc<dyn UserProvider>, config: ConfigHandle, mut shutdown: watch::Receiver<bool>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(60 * 60));
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown.wait_for(|v| *v) => {
+                    info!("deleted user purge task stopped");
+                    return;
+                }
+            }
+            let retention_days = config.get().user.delete_retention_days;
+            match user_provider.purge_deleted_users(retention_days).await {
+                Ok(0) => {}
+                Ok(n) => info!("purged {} deleted users older than {} days", n, retention_days),
+                Err(e) => warn!("purge deleted users error: {}", e),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utils::database::test_db, webhook::WEBHOOK_OUTBOX_TABLE_NAME};
+
+    async fn count(db: &Db, table: &str) -> i64 {
+        storage::query(format!("SELECT COUNT(*) FROM {}", table)).fetch_scalar(db).await.unwrap()
+    }
+
+    fn new_user(username: &str) -> User {
+        User {
+            id: 0,
+            tenant_id: 1,
+            username: Some(username.into()),
+            phone: None,
+            email: None,
+            password: "hash".into(),
+            status: UserStatus::Active,
+            crt_by: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn update_password_enqueues_password_changed() {
+        let db = test_db().await;
+        let users = UserService::new(db.clone());
+        let user = users.create_user(new_user("alice")).await.unwrap();
+        users.update_password(user.id, "new-hash".into()).await.unwrap();
+        assert_eq!(users.get_user_by_id(user.id).await.unwrap().unwrap().password, "new-hash");
+        let event: String = storage::query(format!("SELECT event_type FROM {} ORDER BY id DESC LIMIT 1", WEBHOOK_OUTBOX_TABLE_NAME))
+            .fetch_scalar(&db)
+            .await
+            .unwrap();
+        assert_eq!(event, EVENT_PASSWORD_CHANGED);
+
+        users.delete_user(user.id).await.unwrap();
+        assert!(matches!(users.update_password(user.id, "hash".into()).await, Err(AuthixError::UserNotFound(_))));
+    }
+
+    #[test]
+    fn ensure_can_login_rejects_inactive_status() {
+        let user = |status| User { status, ..new_user("alice") };
+        assert!(user(UserStatus::Active).ensure_can_login().is_ok());
+        assert!(matches!(user(UserStatus::Disabled).ensure_can_login(), Err(AuthixError::AccountDisabled(_))));
+        assert!(matches!(user(UserStatus::Locked).ensure_can_login(), Err(AuthixError::AccountLocked(_))));
+        assert!(matches!(user(UserStatus::Pending).ensure_can_login(), Err(AuthixError::AccountPending(_))));
+        assert!(matches!(user(UserStatus::Deleted).ensure_can_login(), Err(AuthixError::InvalidCredentials(_))));
+    }
+
+    /// 注销释放登录标识，恢复时移回并还原注销前的状态；标识被占用时恢复失败
+    async fn delete_and_restore(users: &dyn UserProvider) {
+        let alice = users.create_user(User { phone: Some("+8613800000000".into()), ..new_user("alice") }).await.unwrap();
+        users.update_user_status(alice.id, UserStatus::Disabled).await.unwrap();
+        users.delete_user(alice.id).await.unwrap();
+        assert!(matches!(users.get_user_profile(alice.id).await, Err(AuthixError::UserNotFound(_))));
+        assert!(users.get_user_by_username(1, "alice".into()).await.unwrap().is_none());
+        assert!(users.list_identities(alice.id).await.unwrap().is_empty());
+
+        assert!(users.restore_user(alice.id).await.unwrap());
+        assert!(!users.restore_user(alice.id).await.unwrap());
+        let restored = users.get_user_by_phone(1, "+8613800000000".into()).await.unwrap().unwrap();
+        assert_eq!((restored.id, restored.username.as_deref(), restored.status), (alice.id, Some("alice"), UserStatus::Disabled));
+        assert_eq!(users.list_identities(alice.id).await.unwrap().len(), 2);
+
+        // 注销期间用户名被重新注册
+        users.delete_user(alice.id).await.unwrap();
+        let bob = users.create_user(new_user("alice")).await.unwrap();
+        assert!(matches!(users.restore_user(alice.id).await, Err(AuthixError::UserAlreadyExists(_))));
+        assert_eq!(users.get_user_by_id(alice.id).await.unwrap().unwrap().status, UserStatus::Deleted);
+        assert_eq!(users.get_user_by_username(1, "alice".into()).await.unwrap().unwrap().id, bob.id);
+    }
+
+    #[tokio::test]
+    async fn delete_releases_identities_until_restore() {
+        delete_and_restore(&UserService::new(test_db().await)).await;
+        delete_and_restore(&InMemoryUserService::default()).await;
+    }
+
+    #[tokio::test]
+    async fn purge_removes_user_data_past_retention() {
+        let db = test_db().await;
+        let users = UserService::new(db.clone());
+        let user = users.create_user(new_user("alice")).await.unwrap();
+        storage::query(format!("INSERT INTO {} (tenant_id, user_id, login_type, device_id, success) VALUES (1, ?, 'password', 'd', ?)", LOGIN_HISTORY_TABLE_NAME))
+            .bind(user.id)
+            .bind(true)
+            .execute(&db)
+            .await
+            .unwrap();
+        users.delete_user(user.id).await.unwrap();
+        assert_eq!(users.purge_deleted_users(30).await.unwrap(), 0);
+
+        storage::query(format!("UPDATE {} SET deleted_at = ?", USER_TABLE_NAME))
+            .bind(Local::now() - chrono::Duration::days(31))
+            .execute(&db)
+            .await
+            .unwrap();
+        assert_eq!(users.purge_deleted_users(30).await.unwrap(), 1);
+        for table in [USER_TABLE_NAME, DELETED_USER_IDENTITY_TABLE_NAME, LOGIN_HISTORY_TABLE_NAME] {
+            assert_eq!(count(&db, table).await, 0, "{}", table);
+        }
+    }
+
+    /// 关联的标识可用于查找用户；用户的同类列为空时写入，解除关联时改用其余同类标识
+    async fn link_and_unlink(users: &dyn UserProvider) {
+        let alice = users.create_user(User { email: Some("a@example.com".into()), ..new_user("alice") }).await.unwrap();
+        let bob = users.create_user(new_user("bob_user")).await.unwrap();
+        let second = users.link_identity(UserIdentity::new(&alice, IdentityType::Email, "", "a2@example.com").verified()).await.unwrap();
+        let phone = users.link_identity(UserIdentity::new(&alice, IdentityType::Phone, "", "+8613800000000")).await.unwrap();
+        let github = users.link_identity(UserIdentity::new(&alice, IdentityType::External, "github", "1001")).await.unwrap();
+        assert!(matches!(
+            users.link_identity(UserIdentity::new(&bob, IdentityType::Email, "", "a2@example.com")).await,
+            Err(AuthixError::UserAlreadyExists(_))
+        ));
+
+        let find = |t, p: &'static str, i: &'static str| users.get_user_by_identity(1, t, p, i);
+        assert_eq!(find(IdentityType::Email, "", "a2@example.com").await.unwrap().unwrap().id, alice.id);
+        assert_eq!(find(IdentityType::External, "github", "1001").await.unwrap().unwrap().id, alice.id);
+        assert!(find(IdentityType::External, "gitlab", "1001").await.unwrap().is_none());
+        let user = users.get_user_by_id(alice.id).await.unwrap().unwrap();
+        assert_eq!((user.email.as_deref(), user.phone.as_deref()), (Some("a@example.com"), Some("+8613800000000")));
+        assert!(second.verified_at.is_some() && phone.verified_at.is_none());
+
+        let primary = users.list_identities(alice.id).await.unwrap().into_iter().find(|i| i.identifier == "a@example.com").unwrap();
+        assert!(users.unlink_identity(alice.id, primary.id).await.unwrap());
+        assert!(!users.unlink_identity(bob.id, github.id).await.unwrap());
+        assert!(users.unlink_identity(alice.id, phone.id).await.unwrap());
+        let user = users.get_user_by_id(alice.id).await.unwrap().unwrap();
+        assert_eq!((user.email.as_deref(), user.phone), (Some("a2@example.com"), None));
+        assert!(find(IdentityType::Email, "", "a@example.com").await.unwrap().is_none());
+        assert_eq!(users.list_identities(alice.id).await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn link_and_unlink_identities() {
+        link_and_unlink(&UserService::new(test_db().await)).await;
+        link_and_unlink(&InMemoryUserService::default()).await;
+    }
+
+    /// 前缀过滤（通配符按字面匹配）、状态过滤与游标分页
+    async fn search(users: &dyn UserProvider) {
+        for name in ["alice", "alina", "a_b", "bob"] {
+            users.create_user(new_user(name)).await.unwrap();
+        }
+        users.create_user(User { tenant_id: 2, ..new_user("alex") }).await.unwrap();
+        let bob = users.get_user_by_username(1, "bob".into()).await.unwrap().unwrap();
+        users.update_user_status(bob.id, UserStatus::Disabled).await.unwrap();
+
+        let names = |page: CursorPage<UserSummary>| page.records.into_iter().map(|u| u.username.unwrap()).collect::<Vec<_>>();
+        let query = UserSearchQuery { tenant_id: Some(1), username: Some("al".into()), order: Some("asc".into()), ..Default::default() };
+        assert_eq!(names(users.search_users(&query).await.unwrap()), ["alice", "alina"]);
+        let query = UserSearchQuery { username: Some("a_".into()), ..Default::default() };
+        assert_eq!(names(users.search_users(&query).await.unwrap()), ["a_b"]);
+        let query = UserSearchQuery { status: Some(UserStatus::Disabled), ..Default::default() };
+        assert_eq!(names(users.search_users(&query).await.unwrap()), ["bob"]);
+
+        let mut query = UserSearchQuery { tenant_id: Some(1), limit: Some(3), ..Default::default() };
+        let first = users.search_users(&query).await.unwrap();
+        query.cursor = first.next_cursor.clone();
+        assert_eq!(names(first), ["bob", "a_b", "alina"]);
+        let second = users.search_users(&query).await.unwrap();
+        assert!(second.next_cursor.is_none());
+        assert_eq!(names(second), ["alice"]);
+
+        query.cursor = Some("x".into());
+        assert!(matches!(users.search_users(&query).await, Err(AuthixError::InvalidParameter(_))));
+        let query = UserSearchQuery { sort: Some("password".into()), ..Default::default() };
+        assert!(matches!(users.search_users(&query).await, Err(AuthixError::InvalidParameter(_))));
+    }
+
+    #[tokio::test]
+    async fn search_users_filters_and_paginates() {
+        search(&UserService::new(test_db().await)).await;
+        search(&InMemoryUserService::default()).await;
+    }
+}
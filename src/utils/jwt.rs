@@ -1,64 +1,74 @@
-use std::{env, time::{Duration, SystemTime, UNIX_EPOCH}};
+use std::{sync::Arc, time::{Duration, SystemTime, UNIX_EPOCH}};
 use jsonwebtoken::{encode, decode, Algorithm, EncodingKey, DecodingKey, Header, Validation};
-use crate::{cache, errors::{AuthixError, AuthixResult}, provider::login::LoginResponse, utils::Claims};
+use crate::{cache, errors::{AuthixError, AuthixResult}, i18n::Msg, metrics::TOKEN_VERIFY_FAILURES_TOTAL, provider::login::LoginResponse, state::AppState, utils::Claims};
 
-pub const ACCESS_TOKEN_EXP: usize = 1000 * 60 * 5;
-pub const REFRESH_TOKEN_EXP: usize = 1000 * 60 * 60 * 24 * 7;
+/// 令牌签名与校验密钥
+#[derive(Clone)]
+pub struct KeyRing {
+    encoding: Arc<EncodingKey>,
+    decoding: Arc<DecodingKey>,
+}
+
+impl KeyRing {
+    /// HS256 共享密钥
+    pub fn from_secret(secret: &str) -> Self {
+        Self {
+            encoding: Arc::new(EncodingKey::from_secret(secret.as_bytes())),
+            decoding: Arc::new(DecodingKey::from_secret(secret.as_bytes())),
+        }
+    }
+}
 
-pub async fn create_token(sub: String, tenant_id: String) -> AuthixResult<LoginResponse> {
-    let (access_token,access_exp,iat) = get_token(&sub, &tenant_id, ACCESS_TOKEN_EXP, "access").await?;
-    let (refresh_token,_,_) = get_token(&sub, &tenant_id, REFRESH_TOKEN_EXP, "refresh").await?;
-    Ok(LoginResponse { access_token, refresh_token, exp: access_exp, iat })
+pub async fn create_token(state: &AppState, sub: String, tenant_id: String, org_id: Option<u64>, access_exp: usize, refresh_exp: usize) -> AuthixResult<LoginResponse> {
+    let (access_token,access_exp,iat) = get_token(state, &sub, &tenant_id, org_id, access_exp, "access").await?;
+    let (refresh_token,_,_) = get_token(state, &sub, &tenant_id, org_id, refresh_exp, "refresh").await?;
+    Ok(LoginResponse { uid: sub.parse().unwrap_or_default(), access_token, refresh_token, exp: access_exp, iat })
 }
 
-pub async fn get_token(sub: &str, tenant_id: &str, exp: usize, token_type: &str) -> AuthixResult<(String,usize,usize)> {
+pub async fn get_token(state: &AppState, sub: &str, tenant_id: &str, org_id: Option<u64>, exp: usize, token_type: &str) -> AuthixResult<(String,usize,usize)> {
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or(Duration::from_millis(0))
         .as_millis() as usize;
-    let jwt_secret = env::var("JWT_DECODING_KEY")?;
-    let claims = Claims { sub: sub.to_string(), tenant_id: tenant_id.to_string(), exp: now + exp, iat: now, token_type: token_type.to_string() };
+    let claims = Claims { sub: sub.to_string(), tenant_id: tenant_id.to_string(), exp: now + exp, iat: now, token_type: token_type.to_string(), org_id };
     let token = encode(
         &Header::new(Algorithm::HS256),
         &claims,
-        &EncodingKey::from_secret(jwt_secret.as_bytes()),
+        &state.keys.encoding,
     )?;
     if "access" == token_type {
         let uid: u64 = sub
             .parse()
-            .map_err(|e| AuthixError::InvalidCredentials(format!("invalid user id: {}", e)))?;
-        cache::save_user_access_token(uid, &token, ACCESS_TOKEN_EXP)
-            .await
-            .map_err(|e| AuthixError::InvalidCredentials(format!("cache save error: {}", e)))?;
+            .map_err(|e| AuthixError::Internal(format!("invalid user id {}: {}", sub, e)))?;
+        let tenant: u64 = tenant_id
+            .parse()
+            .map_err(|e| AuthixError::Internal(format!("invalid tenant id {}: {}", tenant_id, e)))?;
+        cache::save_user_access_token(&*state.sessions, tenant, uid, &token, exp).await?;
     }
     Ok((token,claims.exp,claims.iat))
 }
 
-#[allow(dead_code)]
-pub async fn verify_access_token(token: &str) -> AuthixResult<Claims> {
-    let jwt_secret = env::var("JWT_DECODING_KEY")?;
-    let data = decode::<Claims>(
-        token,
-        &DecodingKey::from_secret(jwt_secret.as_bytes()),
-        &Validation::new(Algorithm::HS256),
-    )?;
-    let claims = data.claims;
-    if claims.token_type != "access" {
-        return Err(AuthixError::InvalidCredentials("token type must be access".into()));
-    }
-    Ok(claims)
+pub async fn verify_access_token(keys: &KeyRing, token: &str) -> AuthixResult<Claims> {
+    verify_token(keys, token, "access").inspect_err(|_| {
+        TOKEN_VERIFY_FAILURES_TOTAL.with_label_values(&["access"]).inc();
+    })
+}
+
+pub async fn verify_refresh_token(keys: &KeyRing, token: &str) -> AuthixResult<Claims> {
+    verify_token(keys, token, "refresh").inspect_err(|_| {
+        TOKEN_VERIFY_FAILURES_TOTAL.with_label_values(&["refresh"]).inc();
+    })
 }
 
-pub async fn verify_refresh_token(token: &str) -> AuthixResult<Claims> {
-    let jwt_secret = env::var("JWT_DECODING_KEY")?;
+fn verify_token(keys: &KeyRing, token: &str, token_type: &str) -> AuthixResult<Claims> {
     let data = decode::<Claims>(
         token,
-        &DecodingKey::from_secret(jwt_secret.as_bytes()),
+        &keys.decoding,
         &Validation::new(Algorithm::HS256),
     )?;
     let claims = data.claims;
-    if claims.token_type != "refresh" {
-        return Err(AuthixError::InvalidCredentials("token type must be refresh".into()));
+    if claims.token_type != token_type {
+        return Err(AuthixError::TokenInvalid(Msg::new("auth.token_type_mismatch").arg("type", token_type)));
     }
     Ok(claims)
 }
@@ -13,4 +13,6 @@ pub struct Claims {
     pub iat: usize,        // 签发时间
     pub tenant_id: String, // 多租户 ID
     pub token_type: String, // "access" | "refresh"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub org_id: Option<u64>, // 当前激活的组织
 }
\ No newline at end of file
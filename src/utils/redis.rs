@@ -1,16 +1,14 @@
-use std::env;
-
-use deadpool_redis::{Config, Pool};
-use once_cell::sync::Lazy;
+use deadpool_redis::{Config, Pool, PoolConfig};
 use tracing::info;
 
-/// 全局 Redis 连接池
-pub static REDIS_POOL: Lazy<Pool> = Lazy::new(|| {
-    let redis_url = env::var("REDIS_URL")
-        .unwrap_or_else(|_| "redis://127.0.0.1:6379/0".to_string());
-    info!("initialize redis url = {}", redis_url);
+use crate::{config::RedisConfig, utils::database::redact_url};
+
+/// 创建 Redis 连接池，首次使用时才建立连接
+pub fn connect(redis: &RedisConfig) -> Pool {
+    info!("initialize redis url = {}", redact_url(&redis.url));
 
-    let cfg = Config::from_url(redis_url);
+    let mut cfg = Config::from_url(redis.url.clone());
+    cfg.pool = redis.max_connections.map(PoolConfig::new);
     cfg.create_pool(Some(deadpool_redis::Runtime::Tokio1))
         .expect("Failed to create redis pool")
-});
+}
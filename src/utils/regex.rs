@@ -6,6 +6,7 @@ static CN: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\+86(1[3-9][0-9]{9})$").unwr
 static USERNAME_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[A-Za-z0-9_\-\.@#$%^&*]{6,32}$").unwrap());
 static PASSWORD_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[A-Za-z0-9_\-\.@#$%^&*]{8,32}$").unwrap());
 static EMAIL_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[^@\s]+@[^@\s]+\.[^@\s]+$").unwrap());
+static AUTH_TYPE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[a-z0-9_\-]{1,32}$").unwrap());
 
 pub fn is_valid_username(username: &str) -> bool {
     // 允许大小写字母、数字和常见符号 _-.@#$%^&*
@@ -39,4 +40,8 @@ pub fn is_valid_phone(phone: &str) -> bool {
 pub fn is_valid_email(email: &str) -> bool {
     // 简单邮箱校验
     EMAIL_REGEX.is_match(email)
-}
\ No newline at end of file
+}
+pub fn is_valid_auth_type(auth_type: &str) -> bool {
+    // 登录/注册方式标识：小写字母、数字、_ 和 -
+    AUTH_TYPE_REGEX.is_match(auth_type)
+}
@@ -1,24 +1,25 @@
-use axum::response::IntoResponse;
+use std::sync::Arc;
+
+use axum::extract::State;
 use axum_extra::extract::TypedHeader;
-use deadpool_redis::redis::AsyncCommands;
 use uuid::Uuid;
 
-use crate::{common::{TenantIdHeader, UidHeader}, utils::redis::REDIS_POOL};
+use crate::{cache::CodeStore, common::{TenantIdHeader, UidHeader}, errors::AuthixResult};
 
 const ONE_TIME_TOKEN_KEY: &str = "one_time_token";
 
 /// 生成token
 pub async fn get_token(
+    State(codes): State<Arc<dyn CodeStore>>,
     TypedHeader(tenant_id): TypedHeader<TenantIdHeader>,
     TypedHeader(uid): TypedHeader<UidHeader>,
-) -> impl IntoResponse {
+) -> AuthixResult<String> {
     let token = Uuid::new_v4().to_string().replace("-", "");
     let key = format!("{}:{}:{}:{}", ONE_TIME_TOKEN_KEY, tenant_id.0, uid.0, token);
 
-    let mut conn = REDIS_POOL.get().await.unwrap();
-    let _: () = conn.set_ex(&key, "1", 60 * 5).await.unwrap();
+    codes.set(&key, "1", 60 * 5).await?;
 
-    token
+    Ok(token)
 }
 
 /// 生成6位数字验证码
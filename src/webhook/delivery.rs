@@ -0,0 +1,434 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use chrono::Local;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use tokio::{sync::watch, task::JoinHandle};
+use tracing::{info, warn};
+
+use crate::{
+    config::ConfigHandle,
+    errors::AuthixResult,
+    storage::{self, Db, FromRecord, Row},
+    webhook::{
+        DELIVERY_STATUS_FAILED, DELIVERY_STATUS_PENDING, DELIVERY_STATUS_SUCCEEDED, WEBHOOK_DELIVERY_TABLE_NAME,
+        WEBHOOK_OUTBOX_TABLE_NAME, WEBHOOK_TABLE_NAME, Webhook,
+    },
+};
+
+/// 签名请求头，值为 `sha256=<hex>`，签名内容为 `{timestamp}.{body}`
+pub const SIGNATURE_HEADER: &str = "x-authix-signature";
+pub const TIMESTAMP_HEADER: &str = "x-authix-timestamp";
+pub const EVENT_HEADER: &str = "x-authix-event";
+pub const DELIVERY_HEADER: &str = "x-authix-delivery";
+
+const POLL_INTERVAL_SECS: u64 = 5;
+const BATCH_SIZE: i64 = 100;
+const REQUEST_TIMEOUT_SECS: u64 = 10;
+/// 最大投递次数，超过后标记为失败
+const MAX_ATTEMPTS: u32 = 8;
+/// 重试间隔：30s 起指数增长，最长 1 小时
+const RETRY_BASE_SECS: u64 = 30;
+const RETRY_MAX_SECS: u64 = 60 * 60;
+/// 领取投递记录后的租约，投递进程在租约内异常退出时，到期后由其他实例重新投递
+const CLAIM_LEASE_SECS: i64 = 60;
+
+#[derive(Debug)]
+struct OutboxEvent {
+    id: u64,
+    tenant_id: u64,
+    event_type: String,
+}
+
+impl FromRecord for OutboxEvent {
+    fn from_record(row: &Row) -> Result<Self, sqlx::Error> {
+        Ok(OutboxEvent { id: row.get("id")?, tenant_id: row.get("tenant_id")?, event_type: row.get("event_type")? })
+    }
+}
+
+#[derive(Debug)]
+struct DueDelivery {
+    id: u64,
+    attempts: u32,
+    event_type: String,
+    url: String,
+    secret: String,
+    event_id: String,
+    payload: String,
+}
+
+impl FromRecord for DueDelivery {
+    fn from_record(row: &Row) -> Result<Self, sqlx::Error> {
+        Ok(DueDelivery {
+            id: row.get("id")?,
+            attempts: row.get("attempts")?,
+            event_type: row.get("event_type")?,
+            url: row.get("url")?,
+            secret: row.get("secret")?,
+            event_id: row.get("event_id")?,
+            payload: row.get("payload")?,
+        })
+    }
+}
+
+/// 计算 Webhook 签名
+pub fn sign(secret: &str, timestamp: u64, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// 第 `attempts` 次失败后的重试间隔（秒）
+fn retry_delay_secs(attempts: u32) -> u64 {
+    RETRY_BASE_SECS.saturating_mul(1u64 << attempts.saturating_sub(1).min(16)).min(RETRY_MAX_SECS)
+}
+
+/// 启动 Webhook 投递任务：将发件箱事件展开为各订阅的投递记录，再投递到期的记录
+/// 多实例部署时各实例均可运行，发件箱事件与投递记录先以条件更新领取，领取成功才处理
+/// 收到停机信号后再执行一轮投递并退出，未投递完的事件保留在数据库中，下次启动继续
+pub fn spawn_webhook_dispatcher(db: Db, mut shutdown: watch::Receiver<bool>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .build()
+            .unwrap_or_default();
+        let mut interval = tokio::time::interval(Duration::from_secs(POLL_INTERVAL_SECS));
+        loop {
+            let stopping = tokio::select! {
+                _ = interval.tick() => false,
+                _ = shutdown.wait_for(|v| *v) => true,
+            };
+            dispatch(&db, &client).await;
+            if stopping {
+                info!("webhook dispatcher stopped");
+                return;
+            }
+        }
+    })
+}
+
+/// 启动投递历史清理任务，每小时执行一次，收到停机信号后退出
+/// 保留天数由配置 `webhook.retention_days` 指定，每次执行时读取以支持热更新
+pub fn spawn_webhook_purge_task(db: Db, config: ConfigHandle, mut shutdown: watch::Receiver<bool>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(60 * 60));
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown.wait_for(|v| *v) => {
+                    info!("webhook purge task stopped");
+                    return;
+                }
+            }
+            let retention_days = config.get().webhook.retention_days;
+            match purge_history(&db, retention_days).await {
+                Ok(0) => {}
+                Ok(n) => info!("purged {} webhook outbox events older than {} days", n, retention_days),
+                Err(e) => warn!("purge webhook history error: {}", e),
+            }
+        }
+    })
+}
+
+/// 删除超过保留期的已完成投递记录，以及已展开且没有剩余投递记录的发件箱事件，返回删除的事件数量
+async fn purge_history(pool: &Db, retention_days: u32) -> AuthixResult<u64> {
+    let cutoff = Local::now() - chrono::Duration::days(retention_days as i64);
+    let mut tx = pool.begin().await?;
+    storage::query(format!("DELETE FROM {} WHERE status IN (?, ?) AND updated_at < ?", WEBHOOK_DELIVERY_TABLE_NAME))
+        .bind(DELIVERY_STATUS_SUCCEEDED)
+        .bind(DELIVERY_STATUS_FAILED)
+        .bind(cutoff)
+        .execute(&mut tx)
+        .await?;
+    let rows = storage::query(format!(
+        "DELETE FROM {outbox} WHERE dispatched = ? AND created_at < ? AND NOT EXISTS (SELECT 1 FROM {deliveries} WHERE outbox_id = {outbox}.id)",
+        outbox = WEBHOOK_OUTBOX_TABLE_NAME, deliveries = WEBHOOK_DELIVERY_TABLE_NAME
+    ))
+        .bind(true)
+        .bind(cutoff)
+        .execute(&mut tx)
+        .await?;
+    tx.commit().await?;
+    Ok(rows)
+}
+
+async fn dispatch(db: &Db, client: &reqwest::Client) {
+    if let Err(e) = fan_out(db).await {
+        warn!("webhook fan out error: {}", e);
+    }
+    if let Err(e) = deliver_due(db, client).await {
+        warn!("webhook delivery error: {}", e);
+    }
+}
+
+async fn fan_out(pool: &Db) -> AuthixResult<()> {
+    let events: Vec<OutboxEvent> = storage::query(format!(
+        "SELECT id, tenant_id, event_type FROM {} WHERE dispatched = ? ORDER BY id LIMIT ?",
+        WEBHOOK_OUTBOX_TABLE_NAME
+    ))
+        .bind(false)
+        .bind(BATCH_SIZE)
+        .fetch_all(pool)
+        .await?;
+    for event in events {
+        let mut tx = pool.begin().await?;
+        // 先标记为已展开以领取事件，已被其他实例领取时跳过
+        let claimed = storage::query(format!("UPDATE {} SET dispatched = ? WHERE id = ? AND dispatched = ?", WEBHOOK_OUTBOX_TABLE_NAME))
+            .bind(true)
+            .bind(event.id)
+            .bind(false)
+            .execute(&mut tx)
+            .await?;
+        if claimed == 0 {
+            continue;
+        }
+        // 订阅事件以逗号分隔存储，按订阅逐条展开，避免依赖 FIND_IN_SET 等方言函数
+        let webhooks: Vec<Webhook> = storage::query(format!(
+            "SELECT id, tenant_id, url, events, enabled, created_at FROM {} WHERE tenant_id = ? AND enabled = ?",
+            WEBHOOK_TABLE_NAME
+        ))
+            .bind(event.tenant_id)
+            .bind(true)
+            .fetch_all(&mut tx)
+            .await?;
+        for webhook in webhooks.iter().filter(|w| w.events.contains(&event.event_type)) {
+            storage::query(format!(
+                "INSERT INTO {} (webhook_id, outbox_id, event_type, status, attempts, next_retry_at) VALUES (?, ?, ?, ?, 0, CURRENT_TIMESTAMP)",
+                WEBHOOK_DELIVERY_TABLE_NAME
+            ))
+                .bind(webhook.id)
+                .bind(event.id)
+                .bind(&event.event_type)
+                .bind(DELIVERY_STATUS_PENDING)
+                .execute(&mut tx)
+                .await?;
+        }
+        tx.commit().await?;
+    }
+    Ok(())
+}
+
+async fn deliver_due(pool: &Db, client: &reqwest::Client) -> AuthixResult<()> {
+    let due: Vec<DueDelivery> = storage::query(format!(
+        "SELECT d.id, d.attempts, d.event_type, w.url, w.secret, o.event_id, o.payload FROM {} d \
+         JOIN {} w ON w.id = d.webhook_id JOIN {} o ON o.id = d.outbox_id \
+         WHERE d.status = ? AND d.next_retry_at <= CURRENT_TIMESTAMP AND w.enabled = ? ORDER BY d.next_retry_at LIMIT ?",
+        WEBHOOK_DELIVERY_TABLE_NAME, WEBHOOK_TABLE_NAME, WEBHOOK_OUTBOX_TABLE_NAME
+    ))
+        .bind(DELIVERY_STATUS_PENDING)
+        .bind(true)
+        .bind(BATCH_SIZE)
+        .fetch_all(pool)
+        .await?;
+    for delivery in due {
+        // 计入投递次数并顺延到租约结束以领取记录，次数已变化说明已被其他实例领取
+        let attempts = delivery.attempts + 1;
+        let claimed = storage::query(format!(
+            "UPDATE {} SET attempts = ?, next_retry_at = ? WHERE id = ? AND status = ? AND attempts = ?",
+            WEBHOOK_DELIVERY_TABLE_NAME
+        ))
+            .bind(attempts)
+            .bind(Local::now() + chrono::Duration::seconds(CLAIM_LEASE_SECS))
+            .bind(delivery.id)
+            .bind(DELIVERY_STATUS_PENDING)
+            .bind(delivery.attempts)
+            .execute(pool)
+            .await?;
+        if claimed == 0 {
+            continue;
+        }
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let result = client
+            .post(&delivery.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, &delivery.event_type)
+            .header(DELIVERY_HEADER, &delivery.event_id)
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(SIGNATURE_HEADER, sign(&delivery.secret, timestamp, &delivery.payload))
+            .body(delivery.payload.clone())
+            .send()
+            .await;
+        let (response_status, error) = match result {
+            Ok(resp) if resp.status().is_success() => (Some(resp.status().as_u16()), None),
+            Ok(resp) => (Some(resp.status().as_u16()), Some(format!("unexpected status {}", resp.status()))),
+            Err(e) => (None, Some(e.to_string().chars().take(255).collect::<String>())),
+        };
+
+        let status = match &error {
+            None => DELIVERY_STATUS_SUCCEEDED,
+            Some(_) if attempts >= MAX_ATTEMPTS => DELIVERY_STATUS_FAILED,
+            Some(_) => DELIVERY_STATUS_PENDING,
+        };
+        if status == DELIVERY_STATUS_FAILED {
+            info!("webhook delivery {} to {} failed after {} attempts", delivery.id, delivery.url, attempts);
+        }
+        let next_retry_at = Local::now() + chrono::Duration::seconds(retry_delay_secs(attempts) as i64);
+        storage::query(format!(
+            "UPDATE {} SET status = ?, attempts = ?, response_status = ?, error = ?, next_retry_at = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+            WEBHOOK_DELIVERY_TABLE_NAME
+        ))
+            .bind(status)
+            .bind(attempts)
+            .bind(response_status)
+            .bind(&error)
+            .bind(next_retry_at)
+            .bind(delivery.id)
+            .execute(pool)
+            .await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicU16, Ordering},
+        Arc, Mutex,
+    };
+
+    use axum::{extract::State, http::{HeaderMap, StatusCode}, routing::post, Router};
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        utils::database::test_db,
+        webhook::{enqueue_event, CreateWebhookRequest, WebhookDelivery, WebhookProvider, WebhookService, EVENT_USER_DELETED, EVENT_USER_REGISTERED},
+    };
+
+    /// 本地接收端，记录收到的请求，按 `status` 返回响应码
+    #[derive(Clone, Default)]
+    struct Receiver {
+        requests: Arc<Mutex<Vec<(HeaderMap, String)>>>,
+        status: Arc<AtomicU16>,
+    }
+
+    impl Receiver {
+        async fn start(status: u16) -> (Self, String) {
+            let receiver = Receiver { status: Arc::new(AtomicU16::new(status)), ..Default::default() };
+            let app = Router::new()
+                .route("/hook", post(|State(r): State<Receiver>, headers: HeaderMap, body: String| async move {
+                    r.requests.lock().unwrap().push((headers, body));
+                    StatusCode::from_u16(r.status.load(Ordering::SeqCst)).unwrap()
+                }))
+                .with_state(receiver.clone());
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}/hook", listener.local_addr().unwrap());
+            tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+            (receiver, url)
+        }
+
+        fn requests(&self) -> Vec<(HeaderMap, String)> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    async fn setup(status: u16) -> (Db, WebhookService, Receiver) {
+        let db = test_db().await;
+        let (receiver, url) = Receiver::start(status).await;
+        let webhooks = WebhookService::new(db.clone());
+        webhooks
+            .create_webhook(CreateWebhookRequest { tenant_id: 1, url, events: vec![EVENT_USER_REGISTERED.into()], secret: Some("s3cret".into()) })
+            .await
+            .unwrap();
+        (db, webhooks, receiver)
+    }
+
+    async fn delivery(webhooks: &WebhookService) -> WebhookDelivery {
+        let mut page = webhooks.list_deliveries(1, 1, 10).await.unwrap();
+        assert_eq!(page.total, 1);
+        page.records.remove(0)
+    }
+
+    #[test]
+    fn sign_uses_hmac_sha256_over_timestamp_and_body() {
+        assert_eq!(
+            sign("secret", 1700000000, r#"{"a":1}"#),
+            "sha256=49f24e537407743fa4a0242bb63b94b9a47ee99cbbe071ccd8a22550ae411686"
+        );
+    }
+
+    #[test]
+    fn retry_delay_backs_off_exponentially_up_to_one_hour() {
+        let delays: Vec<u64> = (1..=8).map(retry_delay_secs).collect();
+        assert_eq!(delays, [30, 60, 120, 240, 480, 960, 1920, 3600]);
+        assert_eq!(retry_delay_secs(u32::MAX), RETRY_MAX_SECS);
+    }
+
+    #[tokio::test]
+    async fn delivers_subscribed_events_with_signature() {
+        let (db, webhooks, receiver) = setup(200).await;
+        enqueue_event(&db, 1, EVENT_USER_REGISTERED, json!({ "user_id": 7 })).await.unwrap();
+        enqueue_event(&db, 1, EVENT_USER_DELETED, json!({ "user_id": 7 })).await.unwrap();
+        dispatch(&db, &reqwest::Client::new()).await;
+
+        let requests = receiver.requests();
+        assert_eq!(requests.len(), 1);
+        let (headers, body) = &requests[0];
+        let header = |name: &str| headers.get(name).unwrap().to_str().unwrap().to_owned();
+        let payload: serde_json::Value = serde_json::from_str(body).unwrap();
+        assert_eq!(header(EVENT_HEADER), EVENT_USER_REGISTERED);
+        assert_eq!(header(DELIVERY_HEADER), payload["id"].as_str().unwrap());
+        assert_eq!(payload["data"]["user_id"], 7);
+        let timestamp: u64 = header(TIMESTAMP_HEADER).parse().unwrap();
+        assert_eq!(header(SIGNATURE_HEADER), sign("s3cret", timestamp, body));
+
+        let delivery = delivery(&webhooks).await;
+        assert_eq!((delivery.status, delivery.attempts, delivery.response_status), (DELIVERY_STATUS_SUCCEEDED, 1, Some(200)));
+    }
+
+    #[tokio::test]
+    async fn failed_delivery_is_retried_after_backoff() {
+        let (db, webhooks, receiver) = setup(500).await;
+        enqueue_event(&db, 1, EVENT_USER_REGISTERED, json!({ "user_id": 7 })).await.unwrap();
+        let client = reqwest::Client::new();
+        dispatch(&db, &client).await;
+
+        let failed = delivery(&webhooks).await;
+        assert_eq!((failed.status, failed.attempts, failed.response_status), (DELIVERY_STATUS_PENDING, 1, Some(500)));
+        assert!(failed.error.is_some());
+        assert!(failed.next_retry_at > Local::now() + chrono::Duration::seconds(RETRY_BASE_SECS as i64 - 5));
+
+        // 未到重试时间不投递
+        dispatch(&db, &client).await;
+        assert_eq!(receiver.requests().len(), 1);
+
+        receiver.status.store(200, Ordering::SeqCst);
+        assert!(webhooks.retry_delivery(failed.id).await.unwrap());
+        dispatch(&db, &client).await;
+        assert_eq!(receiver.requests().len(), 2);
+        let delivered = delivery(&webhooks).await;
+        assert_eq!((delivered.status, delivered.attempts), (DELIVERY_STATUS_SUCCEEDED, 2));
+    }
+
+    #[tokio::test]
+    async fn concurrent_dispatchers_deliver_once() {
+        let (db, webhooks, receiver) = setup(200).await;
+        enqueue_event(&db, 1, EVENT_USER_REGISTERED, json!({ "user_id": 7 })).await.unwrap();
+        let client = reqwest::Client::new();
+        tokio::join!(dispatch(&db, &client), dispatch(&db, &client), dispatch(&db, &client));
+
+        assert_eq!(receiver.requests().len(), 1);
+        assert_eq!(delivery(&webhooks).await.attempts, 1);
+    }
+
+    #[tokio::test]
+    async fn purge_removes_finished_history_past_retention() {
+        let (db, webhooks, _receiver) = setup(200).await;
+        enqueue_event(&db, 1, EVENT_USER_REGISTERED, json!({ "user_id": 7 })).await.unwrap();
+        dispatch(&db, &reqwest::Client::new()).await;
+        enqueue_event(&db, 1, EVENT_USER_REGISTERED, json!({ "user_id": 8 })).await.unwrap();
+        // 保留期内不清理
+        assert_eq!(purge_history(&db, 14).await.unwrap(), 0);
+
+        let past = Local::now() - chrono::Duration::days(30);
+        for table in [WEBHOOK_OUTBOX_TABLE_NAME, WEBHOOK_DELIVERY_TABLE_NAME] {
+            storage::query(format!("UPDATE {} SET created_at = ?", table)).bind(past).execute(&db).await.unwrap();
+        }
+        storage::query(format!("UPDATE {} SET updated_at = ?", WEBHOOK_DELIVERY_TABLE_NAME)).bind(past).execute(&db).await.unwrap();
+        // 未展开的事件保留
+        assert_eq!(purge_history(&db, 14).await.unwrap(), 1);
+        assert_eq!(webhooks.list_deliveries(1, 1, 10).await.unwrap().total, 0);
+        let pending: i64 = storage::query(format!("SELECT COUNT(*) FROM {}", WEBHOOK_OUTBOX_TABLE_NAME)).fetch_scalar(&db).await.unwrap();
+        assert_eq!(pending, 1);
+    }
+}
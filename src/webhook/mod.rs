@@ -0,0 +1,294 @@
+use std::sync::Arc;
+
+use axum::{async_trait, extract::{Path, Query, State}, http::StatusCode, Json};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::{
+    common::{PageQuery, PageResult, R},
+    errors::{AuthixError, AuthixResult},
+    i18n::Msg,
+    storage::{self, Conn, Db, FromRecord, Row},
+    tenant::TenantProvider,
+};
+
+pub mod delivery;
+
+pub const WEBHOOK_TABLE_NAME: &str = "i18n_webhooks";
+pub const WEBHOOK_OUTBOX_TABLE_NAME: &str = "i18n_webhook_outbox";
+pub const WEBHOOK_DELIVERY_TABLE_NAME: &str = "i18n_webhook_deliveries";
+
+pub const EVENT_USER_REGISTERED: &str = "user.registered";
+pub const EVENT_USER_DELETED: &str = "user.deleted";
+pub const EVENT_USER_LOGIN: &str = "user.login";
+pub const EVENT_SESSION_REVOKED: &str = "session.revoked";
+pub const EVENT_PASSWORD_CHANGED: &str = "password.changed";
+/// 可订阅的事件类型
+pub const WEBHOOK_EVENTS: [&str; 5] = [EVENT_USER_REGISTERED, EVENT_USER_DELETED, EVENT_USER_LOGIN, EVENT_SESSION_REVOKED, EVENT_PASSWORD_CHANGED];
+
+/// 投递状态
+pub const DELIVERY_STATUS_PENDING: u8 = 0;
+pub const DELIVERY_STATUS_SUCCEEDED: u8 = 1;
+pub const DELIVERY_STATUS_FAILED: u8 = 2;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Webhook {
+    pub id: u64,
+    pub tenant_id: u64,
+    pub url: String,
+    pub events: Vec<String>,
+    pub enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,     // 签名密钥，仅创建时返回
+    pub created_at: Option<DateTime<Local>>,
+}
+
+impl FromRecord for Webhook {
+    fn from_record(row: &Row) -> Result<Self, sqlx::Error> {
+        let events: String = row.get("events")?;
+        Ok(Webhook {
+            id: row.get("id")?,
+            tenant_id: row.get("tenant_id")?,
+            url: row.get("url")?,
+            events: events.split(',').filter(|e| !e.is_empty()).map(str::to_owned).collect(),
+            enabled: row.get("enabled")?,
+            secret: None,
+            created_at: row.get("created_at")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookDelivery {
+    pub id: u64,
+    pub webhook_id: u64,
+    pub outbox_id: u64,
+    pub event_type: String,
+    pub status: u8,                     // 0 待投递 1 成功 2 失败
+    pub attempts: u32,
+    pub response_status: Option<u16>,
+    pub error: Option<String>,
+    pub next_retry_at: DateTime<Local>,
+    pub created_at: DateTime<Local>,
+    pub updated_at: DateTime<Local>,
+}
+
+impl FromRecord for WebhookDelivery {
+    fn from_record(row: &Row) -> Result<Self, sqlx::Error> {
+        Ok(WebhookDelivery {
+            id: row.get("id")?,
+            webhook_id: row.get("webhook_id")?,
+            outbox_id: row.get("outbox_id")?,
+            event_type: row.get("event_type")?,
+            status: row.get("status")?,
+            attempts: row.get("attempts")?,
+            response_status: row.get("response_status")?,
+            error: row.get("error")?,
+            next_retry_at: row.get("next_retry_at")?,
+            created_at: row.get("created_at")?,
+            updated_at: row.get("updated_at")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateWebhookRequest {
+    pub tenant_id: u64,
+    pub url: String,
+    pub events: Vec<String>,
+    pub secret: Option<String>,         // 不传时自动生成
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebhookListQuery {
+    pub tenant_id: u64,
+}
+
+/// 写入发件箱，传入事务可与业务数据同时提交，由投递任务异步分发给订阅方
+pub async fn enqueue_event<'c>(conn: impl Into<Conn<'c>>, tenant_id: u64, event_type: &str, data: Value) -> AuthixResult<()> {
+    let event_id = Uuid::new_v4().simple().to_string();
+    let payload = json!({
+        "id": event_id,
+        "type": event_type,
+        "tenant_id": tenant_id,
+        "occurred_at": Local::now().to_rfc3339(),
+        "data": data,
+    });
+    storage::query(format!("INSERT INTO {} (event_id, tenant_id, event_type, payload, dispatched) VALUES (?, ?, ?, ?, ?)", WEBHOOK_OUTBOX_TABLE_NAME))
+        .bind(&event_id)
+        .bind(tenant_id)
+        .bind(event_type)
+        .bind(payload.to_string())
+        .bind(false)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+#[async_trait]
+pub trait WebhookProvider: Send + Sync {
+    async fn create_webhook(&self, req: CreateWebhookRequest) -> AuthixResult<Webhook>;
+    async fn list_webhooks(&self, tenant_id: u64) -> AuthixResult<Vec<Webhook>>;
+    async fn delete_webhook(&self, id: u64) -> AuthixResult<bool>;
+    async fn list_deliveries(&self, webhook_id: u64, page: isize, page_size: isize) -> AuthixResult<PageResult<WebhookDelivery>>;
+    /// 将投递重置为待投递，立即重试
+    async fn retry_delivery(&self, id: u64) -> AuthixResult<bool>;
+    /// 发布事件（非事务），用于不涉及数据库写入的场景，如登录、强制下线
+    async fn publish(&self, tenant_id: u64, event_type: &str, data: Value) -> AuthixResult<()>;
+}
+
+pub struct WebhookService {
+    db: Db,
+}
+
+impl WebhookService {
+    pub fn new(db: Db) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl WebhookProvider for WebhookService {
+    async fn create_webhook(&self, req: CreateWebhookRequest) -> AuthixResult<Webhook> {
+        let pool = &self.db;
+        let secret = req.secret.unwrap_or_else(|| Uuid::new_v4().simple().to_string());
+        let id = storage::query(format!("INSERT INTO {} (tenant_id, url, secret, events, enabled) VALUES (?, ?, ?, ?, ?)", WEBHOOK_TABLE_NAME))
+            .bind(req.tenant_id)
+            .bind(&req.url)
+            .bind(&secret)
+            .bind(req.events.join(","))
+            .bind(true)
+            .insert(pool)
+            .await?;
+        Ok(Webhook {
+            id,
+            tenant_id: req.tenant_id,
+            url: req.url,
+            events: req.events,
+            enabled: true,
+            secret: Some(secret),
+            created_at: Some(Local::now()),
+        })
+    }
+
+    async fn list_webhooks(&self, tenant_id: u64) -> AuthixResult<Vec<Webhook>> {
+        let pool = &self.db;
+        let webhooks = storage::query(format!("SELECT id, tenant_id, url, events, enabled, created_at FROM {} WHERE tenant_id = ? ORDER BY id", WEBHOOK_TABLE_NAME))
+            .bind(tenant_id)
+            .fetch_all(pool)
+            .await?;
+        Ok(webhooks)
+    }
+
+    async fn delete_webhook(&self, id: u64) -> AuthixResult<bool> {
+        let mut tx = self.db.begin().await?;
+        storage::query(format!("DELETE FROM {} WHERE webhook_id = ?", WEBHOOK_DELIVERY_TABLE_NAME))
+            .bind(id)
+            .execute(&mut tx)
+            .await?;
+        let rows = storage::query(format!("DELETE FROM {} WHERE id = ?", WEBHOOK_TABLE_NAME))
+            .bind(id)
+            .execute(&mut tx)
+            .await?;
+        tx.commit().await?;
+        Ok(rows > 0)
+    }
+
+    async fn list_deliveries(&self, webhook_id: u64, page: isize, page_size: isize) -> AuthixResult<PageResult<WebhookDelivery>> {
+        let pool = &self.db;
+        let total: i64 = storage::query(format!("SELECT COUNT(*) FROM {} WHERE webhook_id = ?", WEBHOOK_DELIVERY_TABLE_NAME))
+            .bind(webhook_id)
+            .fetch_scalar(pool)
+            .await?;
+        let records = storage::query(format!(
+            "SELECT id, webhook_id, outbox_id, event_type, status, attempts, response_status, error, next_retry_at, created_at, updated_at FROM {} WHERE webhook_id = ? ORDER BY id DESC LIMIT ? OFFSET ?",
+            WEBHOOK_DELIVERY_TABLE_NAME
+        ))
+            .bind(webhook_id)
+            .bind(page_size as i64)
+            .bind(((page - 1) * page_size) as i64)
+            .fetch_all(pool)
+            .await?;
+        Ok(PageResult { total: total as u64, records })
+    }
+
+    async fn retry_delivery(&self, id: u64) -> AuthixResult<bool> {
+        let pool = &self.db;
+        let rows = storage::query(format!(
+            "UPDATE {} SET status = ?, next_retry_at = CURRENT_TIMESTAMP, updated_at = CURRENT_TIMESTAMP WHERE id = ? AND status <> ?",
+            WEBHOOK_DELIVERY_TABLE_NAME
+        ))
+            .bind(DELIVERY_STATUS_PENDING)
+            .bind(id)
+            .bind(DELIVERY_STATUS_SUCCEEDED)
+            .execute(pool)
+            .await?;
+        Ok(rows > 0)
+    }
+
+    async fn publish(&self, tenant_id: u64, event_type: &str, data: Value) -> AuthixResult<()> {
+        enqueue_event(&self.db, tenant_id, event_type, data).await
+    }
+}
+
+pub async fn create_webhook(
+    State(webhook_provider): State<Arc<dyn WebhookProvider>>,
+    State(tenant_provider): State<Arc<dyn TenantProvider>>,
+    Json(payload): Json<CreateWebhookRequest>,
+) -> AuthixResult<(StatusCode, Json<R<Webhook>>)> {
+    if !(payload.url.starts_with("https://") || payload.url.starts_with("http://")) {
+        return Err(AuthixError::InvalidParameter("webhook.invalid_url".into()));
+    }
+    if payload.events.is_empty() {
+        return Err(AuthixError::InvalidParameter("webhook.events_required".into()));
+    }
+    if let Some(e) = payload.events.iter().find(|e| !WEBHOOK_EVENTS.contains(&e.as_str())) {
+        return Err(AuthixError::InvalidParameter(Msg::new("webhook.unknown_event").arg("event", e)));
+    }
+    if tenant_provider.get_tenant_by_id(payload.tenant_id).await?.is_none() {
+        return Err(AuthixError::TenantNotFound("tenant.not_found".into()));
+    }
+    let webhook = webhook_provider.create_webhook(payload).await?;
+    Ok((StatusCode::OK, Json(R::ok_data(webhook))))
+}
+
+pub async fn list_webhooks(
+    State(webhook_provider): State<Arc<dyn WebhookProvider>>,
+    Query(q): Query<WebhookListQuery>,
+) -> AuthixResult<(StatusCode, Json<R<Vec<Webhook>>>)> {
+    let list = webhook_provider.list_webhooks(q.tenant_id).await?;
+    Ok((StatusCode::OK, Json(R::ok_data(list))))
+}
+
+pub async fn delete_webhook(
+    State(webhook_provider): State<Arc<dyn WebhookProvider>>,
+    Path(id): Path<u64>,
+) -> AuthixResult<(StatusCode, Json<R<String>>)> {
+    if !webhook_provider.delete_webhook(id).await? {
+        return Err(AuthixError::NotFound("webhook.not_found".into()));
+    }
+    Ok((StatusCode::OK, Json(R::ok())))
+}
+
+pub async fn list_deliveries(
+    State(webhook_provider): State<Arc<dyn WebhookProvider>>,
+    Path(id): Path<u64>,
+    Query(q): Query<PageQuery>,
+) -> AuthixResult<(StatusCode, Json<R<PageResult<WebhookDelivery>>>)> {
+    let page = q.page.unwrap_or(1).max(1);
+    let page_size = q.page_size.unwrap_or(20).clamp(1, 200);
+    let data = webhook_provider.list_deliveries(id, page, page_size).await?;
+    Ok((StatusCode::OK, Json(R::ok_data(data))))
+}
+
+pub async fn retry_delivery(
+    State(webhook_provider): State<Arc<dyn WebhookProvider>>,
+    Path(id): Path<u64>,
+) -> AuthixResult<(StatusCode, Json<R<String>>)> {
+    if !webhook_provider.retry_delivery(id).await? {
+        return Err(AuthixError::NotFound("webhook.delivery_not_found".into()));
+    }
+    Ok((StatusCode::OK, Json(R::ok())))
+}